@@ -0,0 +1,149 @@
+//! Benchmarks for parsing, reducing a single model and enumerating all minimal models
+//!
+//! Run with `cargo bench`, optionally followed by `-- FILTER` to only run benchmarks whose name
+//! contains `FILTER`. The instances are random 3-CNF formulas generated from a fixed seed, so
+//! results are comparable across changes. This uses a small timing loop instead of a benchmark
+//! framework to avoid further dependencies.
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use minimal_models::{input, Builder, CandidateOrder, Finder, Rng, Solved};
+
+/// Random 3-CNF formula over the variables `1..=vars`
+fn random_formula(seed: u64, vars: usize, clauses: usize) -> Vec<Vec<isize>> {
+    let mut rng = Rng(seed);
+    (0..clauses)
+        .map(|_| {
+            (0..3)
+                .map(|_| {
+                    let var = 1 + rng.below(vars) as isize;
+                    if rng.below(2) == 0 {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Runs the benchmarks whose name contains the filter given on the command line
+struct Runner {
+    filter: Option<String>,
+}
+
+impl Runner {
+    /// Repeats `routine` for about a second after a warm-up run, reporting the time per run
+    ///
+    /// `setup` is not included in the measured time.
+    fn bench<T>(&self, name: &str, mut setup: impl FnMut() -> T, mut routine: impl FnMut(T)) {
+        if let Some(filter) = &self.filter {
+            if !name.contains(&filter[..]) {
+                return;
+            }
+        }
+
+        routine(setup());
+        let mut times = vec![];
+        let start = Instant::now();
+        while times.len() < 5 || start.elapsed() < Duration::from_secs(1) {
+            let input = setup();
+            let run_start = Instant::now();
+            routine(input);
+            times.push(run_start.elapsed());
+        }
+        times.sort_unstable();
+        println!(
+            "{:<32} median {:>12.3?}  min {:>12.3?}  ({} runs)",
+            name,
+            times[times.len() / 2],
+            times[0],
+            times.len()
+        );
+    }
+}
+
+/// Returns a finder for `clauses` together with a full model of them
+fn solved(builder: Builder, clauses: &[Vec<isize>]) -> (Finder, Vec<isize>) {
+    let mut finder = builder.build().unwrap();
+    for clause in clauses {
+        finder.add_clause(clause);
+    }
+    match finder.solve() {
+        Solved::Model(model) => (finder, model),
+        _ => panic!("benchmark instance is not satisfiable"),
+    }
+}
+
+fn main() {
+    let runner = Runner {
+        filter: std::env::args().skip(1).find(|arg| !arg.starts_with("--")),
+    };
+
+    let text: String = random_formula(1, 10_000, 100_000)
+        .iter()
+        .map(|clause| {
+            let lits: Vec<String> = clause.iter().map(|lit| lit.to_string()).collect();
+            format!("{} 0\n", lits.join(" "))
+        })
+        .collect();
+    runner.bench(
+        "parse 100000 clauses",
+        || (),
+        |()| {
+            for line in text.lines() {
+                black_box(input::parse_clause(line, usize::MAX).unwrap());
+            }
+        },
+    );
+
+    // Below the satisfiability threshold, so there are models with many removable literals
+    let medium = random_formula(2, 300, 900);
+    for (name, order) in [
+        ("reduce 300 vars arbitrary", CandidateOrder::Arbitrary),
+        ("reduce 300 vars occurrence", CandidateOrder::Occurrence),
+        ("reduce 300 vars core", CandidateOrder::Core),
+    ] {
+        runner.bench(
+            name,
+            || solved(Finder::builder().order(order), &medium),
+            |(mut finder, model)| {
+                black_box(finder.reduce(&model));
+            },
+        );
+    }
+    runner.bench(
+        "reduce 300 vars speculate 2",
+        || solved(Finder::builder().speculate(2), &medium),
+        |(mut finder, model)| {
+            black_box(finder.reduce(&model));
+        },
+    );
+
+    let small = random_formula(3, 12, 30);
+    for (name, preprocess) in [
+        ("enumerate 12 vars", false),
+        ("enumerate 12 vars preprocess", true),
+    ] {
+        runner.bench(
+            name,
+            || (),
+            |()| {
+                let mut finder = Finder::builder().preprocess(preprocess).build().unwrap();
+                for clause in &small {
+                    finder.add_clause(clause);
+                }
+                let mut models = 0;
+                while let Solved::Model(model) = finder.solve() {
+                    let reduced = finder.reduce(&model);
+                    finder.block(&reduced.model);
+                    models += 1;
+                }
+                black_box(models);
+            },
+        );
+    }
+}
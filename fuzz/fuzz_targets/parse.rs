@@ -0,0 +1,22 @@
+//! Feeds arbitrary bytes to the input parser, which has to reject invalid lines without panicking
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use minimal_models::input::parse_clause;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    for line in text.lines() {
+        if let Ok(clause) = parse_clause(line, 1000) {
+            assert!(clause
+                .iter()
+                .all(|&lit| lit != 0 && lit.unsigned_abs() <= 1000));
+        }
+        // Without a cap every variable still has to be negatable
+        if let Ok(clause) = parse_clause(line, usize::MAX) {
+            assert!(clause
+                .iter()
+                .all(|&lit| lit != 0 && lit.checked_neg().is_some()));
+        }
+    }
+});
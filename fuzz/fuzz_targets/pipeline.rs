@@ -0,0 +1,73 @@
+//! Enumerates minimal models of structured random formulas, validating every result without a
+//! solver
+#![no_main]
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+
+use minimal_models::{verify, CandidateOrder, Finder, Solved};
+
+/// Number of models to enumerate at most
+const MAX_MODELS: usize = 16;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    clauses: Vec<Vec<i8>>,
+    order: u8,
+    preprocess: bool,
+    equivalences: bool,
+    warm_start: bool,
+    speculate: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let order = match input.order % 3 {
+        0 => CandidateOrder::Arbitrary,
+        1 => CandidateOrder::Occurrence,
+        _ => CandidateOrder::Core,
+    };
+    let mut finder = Finder::builder()
+        .order(order)
+        .preprocess(input.preprocess)
+        .equivalences(input.equivalences)
+        .warm_start(input.warm_start)
+        .speculate(if input.speculate { 1 } else { 0 })
+        .build()
+        .unwrap();
+
+    // Small literals keep the number of variables and thus of models small
+    let mut clauses: Vec<Vec<isize>> = input
+        .clauses
+        .iter()
+        .map(|clause| {
+            clause
+                .iter()
+                .filter(|&&lit| lit != 0)
+                .map(|&lit| lit as isize % 8)
+                .filter(|&lit| lit != 0)
+                .collect()
+        })
+        .collect();
+    for clause in &clauses {
+        finder.add_clause(clause);
+    }
+
+    for _ in 0..MAX_MODELS {
+        match finder.solve() {
+            Solved::Model(model) => {
+                verify::check_full_model(&clauses, &model).unwrap();
+                let reduced = if finder.clause_count() > 0 {
+                    let reduced = finder.reduce(&model);
+                    verify::check_entailment(&clauses, &reduced.model).unwrap();
+                    assert!(reduced.is_minimal());
+                    verify::check_minimality(&clauses, &reduced.model).unwrap();
+                    reduced.model
+                } else {
+                    vec![]
+                };
+                finder.block(&reduced);
+                clauses.push(reduced.iter().map(|&lit| -lit).collect());
+            }
+            Solved::Unsat => break,
+            Solved::Unknown => panic!("unknown without limits"),
+        }
+    }
+});
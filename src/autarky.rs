@@ -0,0 +1,117 @@
+//! Maximal autarkies and the lean kernel of a formula, for `analyze autarky`
+//!
+//! An autarky is a partial assignment satisfying every clause it touches, i.e. every clause
+//! containing one of its variables. Removing those clauses preserves satisfiability, and the
+//! remaining clauses that no autarky touches form the lean kernel. Autarkies of the remaining
+//! clauses compose with the removed ones, so repeatedly finding any nonempty autarky and removing
+//! the clauses it touches ends with a maximal autarky and the lean kernel.
+//!
+//! Each autarky is found by a single solver call. Every variable `x` has two selector variables,
+//! for `x` and `-x` being part of the autarky. A clause is touched when a selector of one of its
+//! variables is true and then it has to contain a selected literal.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    backend::{Lbool, Lit},
+    solver_config::SolverConfig,
+    SolverOptions,
+};
+
+/// Result of [`maximal_autarky`]
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Autarky {
+    /// A maximal autarky, sorted by variable
+    pub assignment: Vec<isize>,
+    /// Indices of the clauses of the lean kernel, which the autarky doesn't touch
+    pub kernel: Vec<usize>,
+}
+
+/// Finds a maximal autarky of `clauses` using a solver configured by `options`
+///
+/// Returns `None` if the solver gave up.
+pub fn maximal_autarky(
+    clauses: &[Vec<isize>],
+    options: &SolverOptions,
+) -> anyhow::Result<Option<Autarky>> {
+    let mut solver = SolverConfig::new(options)?.new_solver();
+    // Each variable has the selectors `2 * i` and `2 * i + 1`, for its positive and negative
+    // literal, followed by an activation variable for each clause
+    let vars: BTreeMap<isize, u32> = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.abs())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .zip(0..)
+        .collect();
+    let selector = |lit: isize| Lit::new(2 * vars[&lit.abs()] + (lit < 0) as u32, false).unwrap();
+    let activation = |index: usize| Lit::new(2 * vars.len() as u32 + index as u32, false).unwrap();
+    // Variables for the rounds are allocated as needed, so there is at least one
+    solver.new_vars(2 * vars.len() + clauses.len() + 1);
+
+    for &var in vars.keys() {
+        solver.add_clause(&[!selector(var), !selector(-var)]);
+    }
+    for (index, clause) in clauses.iter().enumerate() {
+        let satisfied: Vec<Lit> = clause.iter().map(|&lit| selector(lit)).collect();
+        // Selecting the negation of a literal of the clause touches it without satisfying it
+        for &lit in clause {
+            let mut constraint = vec![!activation(index), !selector(-lit)];
+            constraint.extend(&satisfied);
+            solver.add_clause(&constraint);
+        }
+    }
+
+    let mut remaining: Vec<usize> = (0..clauses.len()).collect();
+    let mut assignment = vec![];
+    let mut round = Lit::new(solver.nvars() - 1, false).unwrap();
+    let mut model = vec![];
+    loop {
+        let remaining_vars: Vec<isize> = remaining
+            .iter()
+            .flat_map(|&index| &clauses[index])
+            .map(|lit| lit.abs())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if remaining_vars.is_empty() {
+            break;
+        }
+        // The autarky of this round has to select a literal of the remaining clauses
+        let mut nonempty = vec![!round];
+        nonempty.extend(
+            remaining_vars
+                .iter()
+                .flat_map(|&var| [selector(var), selector(-var)]),
+        );
+        solver.add_clause(&nonempty);
+        let mut assumptions = vec![round];
+        assumptions.extend(remaining.iter().map(|&index| activation(index)));
+        match solver.solve_with_assumptions(&assumptions) {
+            Lbool::True => solver.model(&mut model),
+            Lbool::False => break,
+            Lbool::Undef => return Ok(None),
+        }
+        solver.add_clause(&[!round]);
+        round = solver.new_var();
+
+        let selected = |lit: isize| model[selector(lit).var() as usize] == Lbool::True;
+        for &var in &remaining_vars {
+            if selected(var) {
+                assignment.push(var);
+            } else if selected(-var) {
+                assignment.push(-var);
+            }
+        }
+        remaining.retain(|&index| {
+            !clauses[index]
+                .iter()
+                .any(|&lit| selected(lit) || selected(-lit))
+        });
+    }
+    assignment.sort_unstable_by_key(|lit| lit.abs());
+    Ok(Some(Autarky {
+        assignment,
+        kernel: remaining,
+    }))
+}
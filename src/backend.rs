@@ -0,0 +1,396 @@
+//! The interface to the SAT solvers, isolating the rest of the crate from the solver's API
+//!
+//! All solvers are used through [`SatBackend`], with literals and truth values of this module.
+//! cryptominisat is available with the default `cryptominisat` feature, CaDiCaL with the `cadical`
+//! feature, MiniSat with the `minisat` feature and, for the first call only, kissat with the
+//! `kissat` feature. The builtin solver is always available, and on unix any IPASIR solver can be
+//! loaded at runtime.
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+mod builtin;
+#[cfg(feature = "cadical")]
+mod cadical;
+#[cfg(feature = "cryptominisat")]
+mod cryptominisat;
+#[cfg(unix)]
+mod ipasir;
+#[cfg(feature = "kissat")]
+mod kissat;
+#[cfg(feature = "minisat")]
+mod minisat;
+mod proof;
+
+#[cfg(feature = "cryptominisat")]
+pub use self::cryptominisat::CryptoMiniSat;
+pub use builtin::Builtin;
+#[cfg(feature = "cadical")]
+pub use cadical::CaDiCaL;
+#[cfg(unix)]
+pub use ipasir::{Ipasir, IpasirLibrary};
+#[cfg(feature = "kissat")]
+pub use kissat::Kissat;
+#[cfg(feature = "minisat")]
+pub use minisat::MiniSat;
+pub use proof::{formula_path, Proof};
+
+/// A literal of a solver variable
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Lit(u32);
+
+impl Lit {
+    /// Returns `None` if `var >= 1 << 31`
+    pub fn new(var: u32, negated: bool) -> Option<Lit> {
+        (var < 1 << 31).then_some(Lit(var << 1 | negated as u32))
+    }
+
+    pub fn var(self) -> u32 {
+        self.0 >> 1
+    }
+
+    pub fn isneg(self) -> bool {
+        self.0 & 1 != 0
+    }
+}
+
+impl std::ops::Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+/// Result of a solver call or value of a variable in a model
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Lbool {
+    True,
+    False,
+    /// The solver gave up, or the variable is unassigned
+    Undef,
+}
+
+/// Makes a running solver call give up as soon as possible, see [`SatBackend::interrupter`]
+///
+/// It must remain safe to call after the solver was dropped.
+pub type Interrupter = Box<dyn Fn() + Send + Sync>;
+
+/// Thread calling the interrupters of some solvers once an interrupt flag is set
+///
+/// Dropping it stops the thread, so it doesn't outlive the solvers.
+pub struct InterruptForwarder {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl InterruptForwarder {
+    /// Spawns a thread that calls all `interrupters` once `interrupt` is set
+    ///
+    /// Unless at least one solver can be interrupted, no thread is spawned. Neither is one on
+    /// WASI, which has no threads, so nothing can set `interrupt` while a solver runs anyway.
+    pub fn new(interrupt: Arc<AtomicBool>, interrupters: Vec<Interrupter>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        if interrupters.is_empty() || cfg!(target_os = "wasi") {
+            return InterruptForwarder { stop, thread: None };
+        }
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || {
+            while !interrupt.load(Ordering::SeqCst) {
+                if thread_stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                // Unparked when dropped, so the thread exits without waiting for the timeout
+                thread::park_timeout(Duration::from_millis(100));
+            }
+            for interrupter in &interrupters {
+                interrupter();
+            }
+        });
+        InterruptForwarder {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for InterruptForwarder {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            // A panic of the thread was already reported
+            let _ = thread.join();
+        }
+    }
+}
+
+/// An incremental SAT solver
+pub trait SatBackend {
+    /// Number of variables allocated so far
+    fn nvars(&self) -> u32;
+
+    /// Allocates the next variable, returning its positive literal
+    fn new_var(&mut self) -> Lit;
+
+    fn new_vars(&mut self, count: usize) {
+        for _ in 0..count {
+            self.new_var();
+        }
+    }
+
+    /// Adds a clause over allocated variables
+    fn add_clause(&mut self, clause: &[Lit]);
+
+    fn solve(&mut self) -> Lbool {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Solves the formula with all `assumptions` being true
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool;
+
+    /// Stores the value of each variable in `model`, after a call returned [`Lbool::True`]
+    fn model(&self, model: &mut Vec<Lbool>);
+
+    /// Stores the negations of a subset of the assumptions that suffices for the formula to be
+    /// unsatisfiable in `conflict`, after a call returned [`Lbool::False`]
+    fn conflict(&self, conflict: &mut Vec<Lit>);
+
+    /// Returns a handle making a running call return [`Lbool::Undef`] as soon as possible, or
+    /// `None` if the solver can't be interrupted
+    fn interrupter(&self) -> Option<Interrupter> {
+        None
+    }
+
+    /// Number of conflicts of all calls so far, or `None` if the solver doesn't report it
+    fn conflicts(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the learned clauses the solver currently keeps, which are implied by the clauses
+    /// added before the last call, or `None` if the solver can't export them
+    fn learned_clauses(&self) -> Option<Vec<Vec<Lit>>> {
+        None
+    }
+}
+
+/// Answers the first call with a separate, possibly non-incremental, backend
+///
+/// Until then every clause is added to both backends. When the initial backend finds no model,
+/// e.g. as the conflict of the assumptions is needed, the main backend answers the call instead.
+pub struct InitialSolve {
+    initial: Option<Box<dyn SatBackend>>,
+    main: Box<dyn SatBackend>,
+    /// The model found by the initial backend, until the next call
+    initial_model: Option<Vec<Lbool>>,
+}
+
+impl InitialSolve {
+    pub fn new(initial: Box<dyn SatBackend>, main: Box<dyn SatBackend>) -> Self {
+        InitialSolve {
+            initial: Some(initial),
+            main,
+            initial_model: None,
+        }
+    }
+}
+
+impl SatBackend for InitialSolve {
+    fn nvars(&self) -> u32 {
+        self.main.nvars()
+    }
+
+    fn new_var(&mut self) -> Lit {
+        if let Some(initial) = &mut self.initial {
+            initial.new_var();
+        }
+        self.main.new_var()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        if let Some(initial) = &mut self.initial {
+            initial.add_clause(clause);
+        }
+        self.main.add_clause(clause);
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        self.initial_model = None;
+        if let Some(mut initial) = self.initial.take() {
+            if initial.solve_with_assumptions(assumptions) == Lbool::True {
+                let mut model = vec![];
+                initial.model(&mut model);
+                self.initial_model = Some(model);
+                return Lbool::True;
+            }
+        }
+        self.main.solve_with_assumptions(assumptions)
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        match &self.initial_model {
+            Some(initial_model) => model.clone_from(initial_model),
+            None => self.main.model(model),
+        }
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        self.main.conflict(conflict);
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        self.main.interrupter()
+    }
+
+    fn conflicts(&self) -> Option<u64> {
+        self.main.conflicts()
+    }
+
+    fn learned_clauses(&self) -> Option<Vec<Vec<Lit>>> {
+        self.main.learned_clauses()
+    }
+}
+
+/// Number of variables, clauses and literals added to a solver
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct SolverSize {
+    pub vars: usize,
+    pub clauses: usize,
+    pub lits: usize,
+}
+
+impl SolverSize {
+    /// A rough estimate of the memory used by the solver in bytes
+    ///
+    /// This assumes a typical CDCL solver: per variable its assignment, activity and two watch
+    /// lists, per clause a header and two watches, and the literals themselves. Learnt clauses
+    /// and preprocessing aren't included, so the actual usage can be considerably larger.
+    pub fn estimated_bytes(&self) -> usize {
+        64 * self.vars + 32 * self.clauses + 4 * self.lits
+    }
+}
+
+/// Counts what is added to a backend, see [`SolverSize`]
+///
+/// It also hashes the added clauses, so learned clauses of an earlier run can be added once the
+/// backend was given the same clauses as the backend that learned them.
+pub struct Measured {
+    inner: Box<dyn SatBackend>,
+    size: SolverSize,
+    /// FNV-1a hash of the clauses added so far, in order
+    formula: u64,
+    /// Value of `formula` at the start of the last call
+    solved_formula: u64,
+    /// Learned clauses waiting for the clauses they were learned from, with their hash
+    import: Option<(u64, Vec<Vec<Lit>>)>,
+    /// Learned clauses that were imported, which are exported again
+    imported: Vec<Vec<Lit>>,
+}
+
+impl Measured {
+    pub fn new(inner: Box<dyn SatBackend>) -> Self {
+        let size = SolverSize {
+            vars: inner.nvars() as usize,
+            ..SolverSize::default()
+        };
+        Measured {
+            inner,
+            size,
+            formula: 0xcbf29ce484222325,
+            solved_formula: 0xcbf29ce484222325,
+            import: None,
+            imported: vec![],
+        }
+    }
+
+    pub fn size(&self) -> SolverSize {
+        self.size
+    }
+
+    /// Hash of the clauses added before the last call, which imply the learned clauses
+    pub fn solved_formula(&self) -> u64 {
+        self.solved_formula
+    }
+
+    /// Adds `clauses` before the next call for which the clauses added so far hash to `formula`
+    ///
+    /// Learned clauses don't count towards the size or the hash, and clauses over variables that
+    /// weren't allocated by then are dropped.
+    pub fn import(&mut self, formula: u64, clauses: Vec<Vec<Lit>>) {
+        self.import = Some((formula, clauses));
+    }
+}
+
+impl SatBackend for Measured {
+    fn nvars(&self) -> u32 {
+        self.inner.nvars()
+    }
+
+    fn new_var(&mut self) -> Lit {
+        self.size.vars += 1;
+        self.inner.new_var()
+    }
+
+    fn new_vars(&mut self, count: usize) {
+        self.size.vars += count;
+        self.inner.new_vars(count);
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.size.clauses += 1;
+        self.size.lits += clause.len();
+        // The length followed by the literals, each as a 32-bit little endian integer
+        let codes = clause.iter().map(|lit| lit.var() << 1 | lit.isneg() as u32);
+        for code in std::iter::once(clause.len() as u32).chain(codes) {
+            for byte in code.to_le_bytes() {
+                self.formula ^= byte as u64;
+                self.formula = self.formula.wrapping_mul(0x100000001b3);
+            }
+        }
+        self.inner.add_clause(clause);
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        if let Some((formula, _)) = &self.import {
+            if *formula == self.formula {
+                let (_, clauses) = self.import.take().unwrap();
+                let nvars = self.inner.nvars();
+                for clause in clauses {
+                    if clause.iter().all(|lit| lit.var() < nvars) {
+                        self.inner.add_clause(&clause);
+                        self.imported.push(clause);
+                    }
+                }
+            }
+        }
+        self.solved_formula = self.formula;
+        self.inner.solve_with_assumptions(assumptions)
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        self.inner.model(model);
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        self.inner.conflict(conflict);
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        self.inner.interrupter()
+    }
+
+    fn conflicts(&self) -> Option<u64> {
+        self.inner.conflicts()
+    }
+
+    fn learned_clauses(&self) -> Option<Vec<Vec<Lit>>> {
+        let mut clauses = self.inner.learned_clauses()?;
+        clauses.extend(self.imported.iter().cloned());
+        Some(clauses)
+    }
+}
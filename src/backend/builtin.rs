@@ -0,0 +1,833 @@
+//! A small CDCL solver written in Rust, so the crate can be built without a C++ toolchain
+//!
+//! Neither Varisat nor another pure Rust solver is available as a dependency, so this implements
+//! the essentials of MiniSat: two watched literals, first UIP learning with clause minimization,
+//! VSIDS with phase saving, Luby restarts and activity based deletion of learned clauses.
+//! Assumptions are the first decisions, and the failed ones are found like in MiniSat's
+//! `analyzeFinal`. It is meant for the moderately sized formulas of the reductions, not to compete
+//! with the C++ solvers.
+//!
+//! With a [`Proof`], every derived clause is recorded with the clauses it was derived from. The
+//! units of level 0 get clauses of their own, so hints never need to follow their reasons.
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{
+    proof::{ClauseId, Proof},
+    Interrupter, Lbool, Lit, SatBackend,
+};
+use crate::order::lit_index;
+
+/// Conflicts of the first restart, scaled by the Luby sequence
+const RESTART_BASE: u64 = 100;
+
+const VAR_DECAY: f64 = 0.95;
+
+const CLAUSE_DECAY: f64 = 0.999;
+
+const UNDEF: u8 = 2;
+
+#[derive(Copy, Clone)]
+struct Watch {
+    clause: usize,
+    /// Some other literal of the clause, if it is true the clause needn't be visited
+    blocker: Lit,
+}
+
+struct Clause {
+    /// The first two literals are watched, for reasons the first one is the implied literal
+    lits: Vec<Lit>,
+    learnt: bool,
+    deleted: bool,
+    activity: f64,
+    /// Its clause in the proof, if any
+    id: ClauseId,
+}
+
+/// Binary max-heap of the unassigned variables by activity
+#[derive(Default)]
+struct VarHeap {
+    heap: Vec<u32>,
+    /// Position of each variable in `heap`
+    positions: Vec<Option<usize>>,
+}
+
+impl VarHeap {
+    fn contains(&self, var: u32) -> bool {
+        self.positions[var as usize].is_some()
+    }
+
+    fn insert(&mut self, var: u32, activity: &[f64]) {
+        if self.contains(var) {
+            return;
+        }
+        self.positions[var as usize] = Some(self.heap.len());
+        self.heap.push(var);
+        self.sift_up(self.heap.len() - 1, activity);
+    }
+
+    fn pop(&mut self, activity: &[f64]) -> Option<u32> {
+        let top = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.positions[top as usize] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.positions[last as usize] = Some(0);
+            self.sift_down(0, activity);
+        }
+        Some(top)
+    }
+
+    /// Restores the heap order after the activity of `var` increased
+    fn increased(&mut self, var: u32, activity: &[f64]) {
+        if let Some(position) = self.positions[var as usize] {
+            self.sift_up(position, activity);
+        }
+    }
+
+    fn sift_up(&mut self, mut position: usize, activity: &[f64]) {
+        let var = self.heap[position];
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            let parent_var = self.heap[parent];
+            if activity[parent_var as usize] >= activity[var as usize] {
+                break;
+            }
+            self.heap[position] = parent_var;
+            self.positions[parent_var as usize] = Some(position);
+            position = parent;
+        }
+        self.heap[position] = var;
+        self.positions[var as usize] = Some(position);
+    }
+
+    fn sift_down(&mut self, mut position: usize, activity: &[f64]) {
+        let var = self.heap[position];
+        loop {
+            let mut child = 2 * position + 1;
+            if child >= self.heap.len() {
+                break;
+            }
+            if child + 1 < self.heap.len()
+                && activity[self.heap[child + 1] as usize] > activity[self.heap[child] as usize]
+            {
+                child += 1;
+            }
+            let child_var = self.heap[child];
+            if activity[child_var as usize] <= activity[var as usize] {
+                break;
+            }
+            self.heap[position] = child_var;
+            self.positions[child_var as usize] = Some(position);
+            position = child;
+        }
+        self.heap[position] = var;
+        self.positions[var as usize] = Some(position);
+    }
+}
+
+/// Value of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ... at `index`
+fn luby(mut index: u64) -> u64 {
+    let mut size = 1;
+    let mut exponent = 0;
+    while size < index + 1 {
+        exponent += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != index {
+        size = (size - 1) / 2;
+        exponent -= 1;
+        index %= size;
+    }
+    1 << exponent
+}
+
+/// The builtin backend
+pub struct Builtin {
+    clauses: Vec<Clause>,
+    /// Indices of the learned clauses that weren't deleted
+    learnts: Vec<usize>,
+    /// Clauses watching each literal, by [`lit_index`]
+    watches: Vec<Vec<Watch>>,
+
+    /// Value of each variable, `0` or `1`, or [`UNDEF`]
+    assigns: Vec<u8>,
+    level: Vec<u32>,
+    reason: Vec<Option<usize>>,
+    /// Saved phase of each variable
+    phase: Vec<bool>,
+    activity: Vec<f64>,
+    seen: Vec<bool>,
+    heap: VarHeap,
+
+    trail: Vec<Lit>,
+    /// Start of each decision level in `trail`
+    trail_lim: Vec<usize>,
+    /// Next literal of `trail` to propagate
+    propagated: usize,
+
+    var_inc: f64,
+    clause_inc: f64,
+    max_learnts: f64,
+    /// `false` once the clauses are unsatisfiable without any assumptions
+    ok: bool,
+    /// Conflicts of all calls
+    conflicts: u64,
+    /// Length of `trail` at the end of the last call, when it only contains level 0
+    solved_units: usize,
+
+    /// Fixed polarity, otherwise the saved phase is used
+    polarity: Option<bool>,
+    time_limit: Option<Duration>,
+    /// Conflicts allowed per call
+    conflict_limit: Option<u64>,
+    interrupted: Arc<AtomicBool>,
+
+    model: Vec<Lbool>,
+    conflict: Vec<Lit>,
+
+    proof: Option<Proof>,
+    /// Clause of the proof containing just the value of each variable assigned at level 0
+    unit_ids: Vec<ClauseId>,
+}
+
+impl Builtin {
+    pub fn new(
+        polarity: Option<bool>,
+        time_limit: Option<f64>,
+        conflict_limit: Option<u64>,
+    ) -> Self {
+        Builtin {
+            clauses: vec![],
+            learnts: vec![],
+            watches: vec![],
+            assigns: vec![],
+            level: vec![],
+            reason: vec![],
+            phase: vec![],
+            activity: vec![],
+            seen: vec![],
+            heap: VarHeap::default(),
+            trail: vec![],
+            trail_lim: vec![],
+            propagated: 0,
+            var_inc: 1.0,
+            clause_inc: 1.0,
+            max_learnts: 0.0,
+            ok: true,
+            conflicts: 0,
+            solved_units: 0,
+            polarity,
+            time_limit: time_limit.map(Duration::from_secs_f64),
+            conflict_limit,
+            interrupted: Arc::default(),
+            model: vec![],
+            conflict: vec![],
+            proof: None,
+            unit_ids: vec![],
+        }
+    }
+
+    /// Records the clauses and derivations in `proof`
+    pub fn with_proof(mut self, proof: Proof) -> Self {
+        self.proof = Some(proof);
+        self
+    }
+
+    fn value(&self, lit: Lit) -> Lbool {
+        match self.assigns[lit.var() as usize] {
+            UNDEF => Lbool::Undef,
+            value if (value != 0) != lit.isneg() => Lbool::True,
+            _ => Lbool::False,
+        }
+    }
+
+    fn decision_level(&self) -> u32 {
+        self.trail_lim.len() as u32
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var() as usize;
+        self.assigns[var] = !lit.isneg() as u8;
+        self.level[var] = self.decision_level();
+        self.reason[var] = reason;
+        self.trail.push(lit);
+        match reason {
+            Some(reason) if self.trail_lim.is_empty() && self.proof.is_some() => {
+                let clause = &self.clauses[reason];
+                let mut hints: Vec<ClauseId> = clause.lits[1..]
+                    .iter()
+                    .map(|other| self.unit_ids[other.var() as usize])
+                    .collect();
+                hints.push(clause.id);
+                self.unit_ids[var] = self.proof.as_mut().unwrap().add(&[lit], &hints);
+            }
+            _ => (),
+        }
+    }
+
+    fn backtrack(&mut self, level: u32) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let start = self.trail_lim[level as usize];
+        for index in (start..self.trail.len()).rev() {
+            let lit = self.trail[index];
+            let var = lit.var() as usize;
+            self.assigns[var] = UNDEF;
+            self.reason[var] = None;
+            self.phase[var] = !lit.isneg();
+            self.heap.insert(var as u32, &self.activity);
+        }
+        self.trail.truncate(start);
+        self.trail_lim.truncate(level as usize);
+        self.propagated = start;
+    }
+
+    fn attach(&mut self, lits: Vec<Lit>, learnt: bool, id: ClauseId) -> usize {
+        let clause = self.clauses.len();
+        for (watched, other) in [(0, 1), (1, 0)] {
+            self.watches[lit_index(lits[watched])].push(Watch {
+                clause,
+                blocker: lits[other],
+            });
+        }
+        if learnt {
+            self.learnts.push(clause);
+        }
+        self.clauses.push(Clause {
+            lits,
+            learnt,
+            deleted: false,
+            activity: 0.0,
+            id,
+        });
+        clause
+    }
+
+    /// Propagates all assignments of the trail, returning a conflicting clause
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = !self.trail[self.propagated];
+            self.propagated += 1;
+            let mut watches = std::mem::take(&mut self.watches[lit_index(false_lit)]);
+            let mut kept = 0;
+            let mut conflict = None;
+            let mut index = 0;
+            while index < watches.len() {
+                let watch = watches[index];
+                index += 1;
+                if self.clauses[watch.clause].deleted {
+                    continue;
+                }
+                if self.value(watch.blocker) == Lbool::True {
+                    watches[kept] = watch;
+                    kept += 1;
+                    continue;
+                }
+
+                // Make sure the false literal is the second one
+                let lits = &mut self.clauses[watch.clause].lits;
+                if lits[0] == false_lit {
+                    lits.swap(0, 1);
+                }
+                let first = lits[0];
+                let watch = Watch {
+                    clause: watch.clause,
+                    blocker: first,
+                };
+                if self.value(first) == Lbool::True {
+                    watches[kept] = watch;
+                    kept += 1;
+                    continue;
+                }
+
+                // Look for a new literal to watch
+                let lits = &self.clauses[watch.clause].lits;
+                if let Some(position) =
+                    (2..lits.len()).find(|&position| self.value(lits[position]) != Lbool::False)
+                {
+                    let lits = &mut self.clauses[watch.clause].lits;
+                    lits.swap(1, position);
+                    let new_watch = lits[1];
+                    self.watches[lit_index(new_watch)].push(watch);
+                    continue;
+                }
+
+                watches[kept] = watch;
+                kept += 1;
+                if self.value(first) == Lbool::False {
+                    conflict = Some(watch.clause);
+                    while index < watches.len() {
+                        watches[kept] = watches[index];
+                        kept += 1;
+                        index += 1;
+                    }
+                } else {
+                    self.assign(first, Some(watch.clause));
+                }
+            }
+            watches.truncate(kept);
+            self.watches[lit_index(false_lit)] = watches;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    fn bump_var(&mut self, var: u32) {
+        self.activity[var as usize] += self.var_inc;
+        if self.activity[var as usize] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+        }
+        self.heap.increased(var, &self.activity);
+    }
+
+    fn bump_clause(&mut self, clause: usize) {
+        self.clauses[clause].activity += self.clause_inc;
+        if self.clauses[clause].activity > 1e20 {
+            for &learnt in &self.learnts {
+                self.clauses[learnt].activity *= 1e-20;
+            }
+            self.clause_inc *= 1e-20;
+        }
+    }
+
+    /// Derives the first UIP clause of a conflict, returning it with the level to backtrack to and
+    /// its hints, if there is a proof
+    ///
+    /// The first literal of the clause is the asserting one.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, u32, Vec<ClauseId>) {
+        let first_conflict = conflict;
+        let mut conflict = conflict;
+        let mut reasons = vec![];
+        let mut learnt = vec![Lit::new(0, false).unwrap()];
+        let mut pending = 0;
+        let mut implied = None;
+        let mut index = self.trail.len();
+        loop {
+            if self.clauses[conflict].learnt {
+                self.bump_clause(conflict);
+            }
+            // The first literal of a reason is the implied literal itself
+            let skip = implied.is_some() as usize;
+            for position in skip..self.clauses[conflict].lits.len() {
+                let lit = self.clauses[conflict].lits[position];
+                let var = lit.var() as usize;
+                if !self.seen[var] && self.level[var] > 0 {
+                    self.seen[var] = true;
+                    self.bump_var(var as u32);
+                    if self.level[var] >= self.decision_level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(lit);
+                    }
+                }
+            }
+            // Continue with the latest assignment taking part in the conflict
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var() as usize] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            self.seen[lit.var() as usize] = false;
+            implied = Some(lit);
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            conflict = self.reason[lit.var() as usize].unwrap();
+            reasons.push(conflict);
+        }
+        learnt[0] = !implied.unwrap();
+
+        // Remove literals implied by the others through their reason
+        let candidates = learnt.clone();
+        learnt.retain(|&lit| match self.reason[lit.var() as usize] {
+            Some(reason) if lit != candidates[0] => {
+                let implied = self.clauses[reason].lits[1..].iter().all(|other| {
+                    self.seen[other.var() as usize] || self.level[other.var() as usize] == 0
+                });
+                if implied {
+                    reasons.push(reason);
+                }
+                !implied
+            }
+            _ => true,
+        });
+        for lit in &candidates {
+            self.seen[lit.var() as usize] = false;
+        }
+
+        // The literal of the highest remaining level is watched, it is the one to backtrack to
+        let mut backtrack = 0;
+        if learnt.len() > 1 {
+            let mut highest = 1;
+            for position in 2..learnt.len() {
+                if self.level[learnt[position].var() as usize]
+                    > self.level[learnt[highest].var() as usize]
+                {
+                    highest = position;
+                }
+            }
+            learnt.swap(1, highest);
+            backtrack = self.level[learnt[1].var() as usize];
+        }
+        let hints = self.hints(reasons, Some(first_conflict));
+        (learnt, backtrack, hints)
+    }
+
+    /// Hints for a clause whose negation propagates along `reasons` to the falsified `conflict`,
+    /// or, without one, to the falsified reason of the latest literal, if there is a proof
+    ///
+    /// The units of level 0 come first, followed by the reasons in the order of the literals they
+    /// implied, so each one propagates when the hints are checked in order.
+    fn hints(&self, mut reasons: Vec<usize>, conflict: Option<usize>) -> Vec<ClauseId> {
+        if self.proof.is_none() {
+            return vec![];
+        }
+        let positions: HashMap<u32, usize> = self
+            .trail
+            .iter()
+            .enumerate()
+            .map(|(position, lit)| (lit.var(), position))
+            .collect();
+        reasons.sort_unstable_by_key(|&reason| positions[&self.clauses[reason].lits[0].var()]);
+        reasons.extend(conflict);
+
+        let mut hints: Vec<ClauseId> = reasons
+            .iter()
+            .flat_map(|&reason| &self.clauses[reason].lits)
+            .map(|lit| lit.var() as usize)
+            .filter(|&var| self.level[var] == 0)
+            .map(|var| self.unit_ids[var])
+            .collect();
+        hints.sort_unstable();
+        hints.dedup();
+        hints.extend(reasons.iter().map(|&reason| self.clauses[reason].id));
+        hints
+    }
+
+    /// Records the empty clause, derived from a conflict at level 0
+    fn derive_empty(&mut self, conflict: usize) {
+        let hints = self.hints(vec![], Some(conflict));
+        if let Some(proof) = &mut self.proof {
+            proof.add(&[], &hints);
+        }
+    }
+
+    /// Stores the assumptions responsible for `lit` being false in `conflict`, as in MiniSat
+    fn analyze_final(&mut self, lit: Lit) {
+        self.conflict.clear();
+        self.conflict.push(lit);
+        let var = lit.var() as usize;
+        if self.level[var] == 0 {
+            if let Some(proof) = &mut self.proof {
+                proof.add(&[lit], &[self.unit_ids[var]]);
+            }
+            return;
+        }
+        // If the assumption contradicts an earlier one, the conflict is a tautology
+        let derived = self.reason[var].is_some();
+        let mut reasons = vec![];
+        self.seen[var] = true;
+        for index in (self.trail_lim[0]..self.trail.len()).rev() {
+            let var = self.trail[index].var() as usize;
+            if !self.seen[var] {
+                continue;
+            }
+            match self.reason[var] {
+                None => self.conflict.push(!self.trail[index]),
+                Some(reason) => {
+                    reasons.push(reason);
+                    for position in 1..self.clauses[reason].lits.len() {
+                        let other = self.clauses[reason].lits[position].var() as usize;
+                        if self.level[other] > 0 {
+                            self.seen[other] = true;
+                        }
+                    }
+                }
+            }
+            self.seen[var] = false;
+        }
+        self.seen[lit.var() as usize] = false;
+        if derived {
+            let hints = self.hints(reasons, None);
+            if let Some(proof) = &mut self.proof {
+                proof.add(&self.conflict, &hints);
+            }
+        }
+    }
+
+    fn locked(&self, clause: usize) -> bool {
+        let first = self.clauses[clause].lits[0];
+        self.reason[first.var() as usize] == Some(clause) && self.value(first) == Lbool::True
+    }
+
+    /// Deletes the less active half of the learned clauses, except for binary ones and reasons
+    fn reduce_learnts(&mut self) {
+        let mut learnts = std::mem::take(&mut self.learnts);
+        learnts.sort_by(|&a, &b| {
+            self.clauses[a]
+                .activity
+                .partial_cmp(&self.clauses[b].activity)
+                .unwrap()
+        });
+        let half = learnts.len() / 2;
+        let mut kept = vec![];
+        for (position, clause) in learnts.into_iter().enumerate() {
+            if position < half && self.clauses[clause].lits.len() > 2 && !self.locked(clause) {
+                if let Some(proof) = &mut self.proof {
+                    proof.delete(self.clauses[clause].id);
+                }
+                // Watches of deleted clauses are dropped during propagation
+                self.clauses[clause].deleted = true;
+                self.clauses[clause].lits = vec![];
+            } else {
+                kept.push(clause);
+            }
+        }
+        self.learnts = kept;
+    }
+
+    fn pick_branch(&mut self) -> Option<Lit> {
+        while let Some(var) = self.heap.pop(&self.activity) {
+            if self.assigns[var as usize] == UNDEF {
+                let positive = self.polarity.unwrap_or(self.phase[var as usize]);
+                return Some(Lit::new(var, !positive).unwrap());
+            }
+        }
+        None
+    }
+
+    /// Whether the current call has to give up, when interrupted or out of its budget
+    ///
+    /// The budget ends at `deadline` or once the total number of conflicts reaches `max_conflicts`.
+    fn give_up(&self, deadline: Option<Instant>, max_conflicts: Option<u64>) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+            || max_conflicts.is_some_and(|max_conflicts| self.conflicts >= max_conflicts)
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Searches until `conflicts` conflicts happened, returning `Undef` to restart
+    fn search(
+        &mut self,
+        assumptions: &[Lit],
+        conflicts: u64,
+        deadline: Option<Instant>,
+        max_conflicts: Option<u64>,
+    ) -> Lbool {
+        let mut conflict_count = 0;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                conflict_count += 1;
+                self.conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.derive_empty(conflict);
+                    self.ok = false;
+                    return Lbool::False;
+                }
+                let (learnt, backtrack, hints) = self.analyze(conflict);
+                let id = match &mut self.proof {
+                    Some(proof) => proof.add(&learnt, &hints),
+                    None => 0,
+                };
+                self.backtrack(backtrack);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                    self.unit_ids[learnt[0].var() as usize] = id;
+                } else {
+                    let asserting = learnt[0];
+                    let clause = self.attach(learnt, true, id);
+                    self.bump_clause(clause);
+                    self.assign(asserting, Some(clause));
+                }
+                self.var_inc /= VAR_DECAY;
+                self.clause_inc /= CLAUSE_DECAY;
+                if self.give_up(deadline, max_conflicts) {
+                    return Lbool::Undef;
+                }
+                continue;
+            }
+
+            if conflict_count >= conflicts {
+                self.backtrack(0);
+                return Lbool::Undef;
+            }
+            if self.learnts.len() as f64 - self.trail.len() as f64 >= self.max_learnts {
+                self.reduce_learnts();
+            }
+
+            // Assumptions are the first decisions
+            let mut next = None;
+            while (self.decision_level() as usize) < assumptions.len() {
+                let lit = assumptions[self.decision_level() as usize];
+                match self.value(lit) {
+                    Lbool::True => self.trail_lim.push(self.trail.len()),
+                    Lbool::False => {
+                        self.analyze_final(!lit);
+                        return Lbool::False;
+                    }
+                    Lbool::Undef => {
+                        next = Some(lit);
+                        break;
+                    }
+                }
+            }
+            let next = match next.or_else(|| self.pick_branch()) {
+                Some(next) => next,
+                None => return Lbool::True,
+            };
+            self.trail_lim.push(self.trail.len());
+            self.assign(next, None);
+        }
+    }
+}
+
+impl SatBackend for Builtin {
+    fn nvars(&self) -> u32 {
+        self.assigns.len() as u32
+    }
+
+    fn new_var(&mut self) -> Lit {
+        let var = self.assigns.len() as u32;
+        self.assigns.push(UNDEF);
+        self.level.push(0);
+        self.reason.push(None);
+        self.phase.push(false);
+        self.activity.push(0.0);
+        self.seen.push(false);
+        self.heap.positions.push(None);
+        self.heap.insert(var, &self.activity);
+        self.watches.push(vec![]);
+        self.watches.push(vec![]);
+        self.unit_ids.push(0);
+        Lit::new(var, false).unwrap()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        if !self.ok {
+            return;
+        }
+        let mut lits = clause.to_vec();
+        lits.sort_unstable();
+        lits.dedup();
+        // Drop tautologies and satisfied clauses as well as false literals, all at level 0
+        if lits.windows(2).any(|pair| pair[0] == !pair[1]) {
+            return;
+        }
+        let mut id = match &mut self.proof {
+            Some(proof) => proof.original(&lits),
+            None => 0,
+        };
+        if lits.iter().any(|&lit| self.value(lit) == Lbool::True) {
+            if let Some(proof) = &mut self.proof {
+                proof.delete(id);
+            }
+            return;
+        }
+        let mut hints: Vec<ClauseId> = lits
+            .iter()
+            .filter(|&&lit| self.value(lit) == Lbool::False)
+            .map(|lit| self.unit_ids[lit.var() as usize])
+            .collect();
+        lits.retain(|&lit| self.value(lit) == Lbool::Undef);
+        if let (false, Some(proof)) = (hints.is_empty(), &mut self.proof) {
+            hints.push(id);
+            let original = id;
+            id = proof.add(&lits, &hints);
+            proof.delete(original);
+        }
+        match lits.len() {
+            0 => self.ok = false,
+            1 => {
+                self.assign(lits[0], None);
+                self.unit_ids[lits[0].var() as usize] = id;
+                if let Some(conflict) = self.propagate() {
+                    self.derive_empty(conflict);
+                    self.ok = false;
+                }
+            }
+            _ => {
+                self.attach(lits, false, id);
+            }
+        }
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        self.conflict.clear();
+        if !self.ok {
+            return Lbool::False;
+        }
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        let max_conflicts = self.conflict_limit.map(|limit| self.conflicts + limit);
+        self.max_learnts = self
+            .max_learnts
+            .max(self.clauses.len() as f64 / 3.0 + 1000.0);
+        let mut restarts = 0;
+        let result = loop {
+            let conflicts = luby(restarts) * RESTART_BASE;
+            restarts += 1;
+            match self.search(assumptions, conflicts, deadline, max_conflicts) {
+                Lbool::Undef => {
+                    if self.give_up(deadline, max_conflicts) {
+                        break Lbool::Undef;
+                    }
+                    self.max_learnts *= 1.1;
+                }
+                result => break result,
+            }
+        };
+        if result == Lbool::True {
+            self.model.clear();
+            self.model
+                .extend(self.assigns.iter().map(|&value| match value {
+                    UNDEF => Lbool::Undef,
+                    0 => Lbool::False,
+                    _ => Lbool::True,
+                }));
+        }
+        self.backtrack(0);
+        self.solved_units = self.trail.len();
+        result
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clone_from(&self.model);
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clone_from(&self.conflict);
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        let interrupted = self.interrupted.clone();
+        Some(Box::new(move || interrupted.store(true, Ordering::Relaxed)))
+    }
+
+    fn conflicts(&self) -> Option<u64> {
+        Some(self.conflicts)
+    }
+
+    fn learned_clauses(&self) -> Option<Vec<Vec<Lit>>> {
+        // Clauses added since the last call may have extended the assignments of level 0
+        let units = self.trail[..self.solved_units].iter().map(|&lit| vec![lit]);
+        let learnts = self
+            .learnts
+            .iter()
+            .map(|&clause| self.clauses[clause].lits.clone());
+        Some(units.chain(learnts).collect())
+    }
+}
@@ -0,0 +1,198 @@
+//! The CaDiCaL backend, using CaDiCaL's C API
+//!
+//! No bindings are available as a dependency, so the few functions needed are declared here. The
+//! library `libcadical` has to be installed where the linker finds it, e.g. by setting
+//! `RUSTFLAGS=-L/path/to/cadical/build`.
+use std::{
+    cell::Cell,
+    ffi::CString,
+    os::raw::{c_char, c_int, c_void},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{Interrupter, Lbool, Lit, SatBackend};
+
+/// Opaque solver handle of the C API
+#[repr(C)]
+struct CCaDiCaL {
+    _private: [u8; 0],
+}
+
+#[link(name = "cadical")]
+#[cfg_attr(target_os = "macos", link(name = "c++"))]
+#[cfg_attr(not(target_os = "macos"), link(name = "stdc++"))]
+extern "C" {
+    fn ccadical_init() -> *mut CCaDiCaL;
+    fn ccadical_release(solver: *mut CCaDiCaL);
+    fn ccadical_add(solver: *mut CCaDiCaL, lit: c_int);
+    fn ccadical_assume(solver: *mut CCaDiCaL, lit: c_int);
+    fn ccadical_solve(solver: *mut CCaDiCaL) -> c_int;
+    fn ccadical_val(solver: *mut CCaDiCaL, lit: c_int) -> c_int;
+    fn ccadical_failed(solver: *mut CCaDiCaL, lit: c_int) -> c_int;
+    fn ccadical_set_terminate(
+        solver: *mut CCaDiCaL,
+        state: *mut c_void,
+        terminate: extern "C" fn(state: *mut c_void) -> c_int,
+    );
+    fn ccadical_set_option(solver: *mut CCaDiCaL, name: *const c_char, value: c_int);
+    fn ccadical_limit(solver: *mut CCaDiCaL, name: *const c_char, limit: c_int);
+}
+
+/// State of the terminate callback, polled by CaDiCaL during a call
+struct Terminate {
+    interrupted: Arc<AtomicBool>,
+    deadline: Cell<Option<Instant>>,
+}
+
+extern "C" fn terminate(state: *mut c_void) -> c_int {
+    // The state is boxed and owned by the solver, so it outlives every call
+    let state = unsafe { &*(state as *const Terminate) };
+    let expired = state
+        .deadline
+        .get()
+        .is_some_and(|deadline| Instant::now() >= deadline);
+    (expired || state.interrupted.load(Ordering::Relaxed)) as c_int
+}
+
+/// The CaDiCaL backend
+///
+/// Once interrupted, every following call returns [`Lbool::Undef`] immediately.
+pub struct CaDiCaL {
+    solver: *mut CCaDiCaL,
+    terminate: Box<Terminate>,
+    time_limit: Option<Duration>,
+    /// Set before each call, as CaDiCaL's limits only apply to the next call
+    conflict_limit: Option<c_int>,
+    nvars: u32,
+    /// Number of variables that occurred in a clause or assumption, CaDiCaL doesn't know about
+    /// the others
+    used_vars: u32,
+    /// Of the last call, to find the failed ones
+    assumptions: Vec<Lit>,
+}
+
+/// Converts to the DIMACS style literals of the C API
+fn to_c(lit: Lit) -> c_int {
+    let var = lit.var() as c_int + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+impl CaDiCaL {
+    pub fn new(
+        options: &[(String, i32)],
+        time_limit: Option<f64>,
+        conflict_limit: Option<u64>,
+    ) -> Self {
+        let solver = unsafe { ccadical_init() };
+        assert!(!solver.is_null(), "failed to create a CaDiCaL instance");
+        let terminate = Box::new(Terminate {
+            interrupted: Arc::default(),
+            deadline: Cell::new(None),
+        });
+        unsafe {
+            ccadical_set_terminate(
+                solver,
+                &*terminate as *const Terminate as *mut c_void,
+                self::terminate,
+            );
+        }
+        // Options have to be set before any clauses are added
+        for (name, value) in options {
+            let name = CString::new(&name[..]).expect("option name contains a NUL byte");
+            unsafe { ccadical_set_option(solver, name.as_ptr(), *value) };
+        }
+        CaDiCaL {
+            solver,
+            terminate,
+            time_limit: time_limit.map(Duration::from_secs_f64),
+            conflict_limit: conflict_limit.map(|limit| limit.min(c_int::MAX as u64) as c_int),
+            nvars: 0,
+            used_vars: 0,
+            assumptions: vec![],
+        }
+    }
+
+    fn use_var(&mut self, lit: Lit) {
+        self.used_vars = self.used_vars.max(lit.var() + 1);
+    }
+}
+
+impl Drop for CaDiCaL {
+    fn drop(&mut self) {
+        unsafe { ccadical_release(self.solver) };
+    }
+}
+
+impl SatBackend for CaDiCaL {
+    fn nvars(&self) -> u32 {
+        self.nvars
+    }
+
+    fn new_var(&mut self) -> Lit {
+        self.nvars += 1;
+        Lit::new(self.nvars - 1, false).unwrap()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        for &lit in clause {
+            self.use_var(lit);
+            unsafe { ccadical_add(self.solver, to_c(lit)) };
+        }
+        unsafe { ccadical_add(self.solver, 0) };
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        for &lit in assumptions {
+            self.use_var(lit);
+            unsafe { ccadical_assume(self.solver, to_c(lit)) };
+        }
+        self.assumptions.clear();
+        self.assumptions.extend_from_slice(assumptions);
+        self.terminate
+            .deadline
+            .set(self.time_limit.map(|limit| Instant::now() + limit));
+        if let Some(limit) = self.conflict_limit {
+            let name = b"conflicts\0".as_ptr() as *const c_char;
+            unsafe { ccadical_limit(self.solver, name, limit) };
+        }
+        match unsafe { ccadical_solve(self.solver) } {
+            10 => Lbool::True,
+            20 => Lbool::False,
+            _ => Lbool::Undef,
+        }
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clear();
+        model.extend((0..self.nvars).map(|var| {
+            let lit = Lit::new(var, false).unwrap();
+            if var < self.used_vars && unsafe { ccadical_val(self.solver, to_c(lit)) } > 0 {
+                Lbool::True
+            } else {
+                Lbool::False
+            }
+        }));
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clear();
+        for &lit in &self.assumptions {
+            if unsafe { ccadical_failed(self.solver, to_c(lit)) } != 0 {
+                conflict.push(!lit);
+            }
+        }
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        let interrupted = self.terminate.interrupted.clone();
+        Some(Box::new(move || interrupted.store(true, Ordering::Relaxed)))
+    }
+}
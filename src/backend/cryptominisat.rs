@@ -0,0 +1,106 @@
+//! The cryptominisat backend
+use super::{Lbool, Lit, SatBackend};
+use crate::solver_config::SolverOption;
+
+/// The cryptominisat backend
+///
+/// The bindings can't interrupt a running call, which only stops at a configured time limit.
+pub struct CryptoMiniSat {
+    solver: cryptominisat::Solver,
+    /// Buffer for converting literals to the bindings' type
+    lits: Vec<cryptominisat::Lit>,
+}
+
+fn to_cms(lit: Lit) -> cryptominisat::Lit {
+    cryptominisat::Lit::new(lit.var(), lit.isneg()).unwrap()
+}
+
+fn from_cms(lit: cryptominisat::Lit) -> Lit {
+    Lit::new(lit.var(), lit.isneg()).unwrap()
+}
+
+fn from_cms_lbool(value: cryptominisat::Lbool) -> Lbool {
+    match value {
+        cryptominisat::Lbool::True => Lbool::True,
+        cryptominisat::Lbool::False => Lbool::False,
+        cryptominisat::Lbool::Undef => Lbool::Undef,
+    }
+}
+
+impl CryptoMiniSat {
+    pub fn new(threads: Option<u32>, options: &[SolverOption]) -> Self {
+        let mut solver = cryptominisat::Solver::new();
+        // Needs to happen before any clauses are added
+        if let Some(threads) = threads {
+            solver.set_num_threads(threads);
+        }
+        for &option in options {
+            match option {
+                SolverOption::Verbosity(verbosity) => solver.set_verbosity(verbosity),
+                SolverOption::MaxTime(max_time) => solver.set_max_time(max_time),
+                SolverOption::Polarity(Some(polarity)) => solver.set_default_polarity(polarity),
+                SolverOption::Polarity(None) => solver.set_polarity_auto(),
+                SolverOption::NoSimplify => solver.set_no_simplify(),
+                SolverOption::NoSimplifyAtStartup => solver.set_no_simplify_at_startup(),
+                SolverOption::NoEquivalentLitReplacement => {
+                    solver.set_no_equivalent_lit_replacement()
+                }
+                SolverOption::NoBva => solver.set_no_bva(),
+                SolverOption::NoBve => solver.set_no_bve(),
+                SolverOption::ComponentHandler => solver.set_yes_comphandler(),
+            }
+        }
+        CryptoMiniSat {
+            solver,
+            lits: vec![],
+        }
+    }
+
+    fn convert(&mut self, lits: &[Lit]) {
+        self.lits.clear();
+        self.lits.extend(lits.iter().map(|&lit| to_cms(lit)));
+    }
+}
+
+impl SatBackend for CryptoMiniSat {
+    fn nvars(&self) -> u32 {
+        self.solver.nvars()
+    }
+
+    fn new_var(&mut self) -> Lit {
+        from_cms(self.solver.new_var())
+    }
+
+    fn new_vars(&mut self, count: usize) {
+        self.solver.new_vars(count);
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.convert(clause);
+        self.solver.add_clause(&self.lits);
+    }
+
+    fn solve(&mut self) -> Lbool {
+        from_cms_lbool(self.solver.solve())
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        self.convert(assumptions);
+        from_cms_lbool(self.solver.solve_with_assumptions(&self.lits))
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clear();
+        model.extend(
+            self.solver
+                .get_model()
+                .iter()
+                .map(|&value| from_cms_lbool(value)),
+        );
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clear();
+        conflict.extend(self.solver.get_conflict().iter().map(|&lit| from_cms(lit)));
+    }
+}
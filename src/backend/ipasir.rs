@@ -0,0 +1,271 @@
+//! A backend for any solver implementing IPASIR, loaded from a shared library at runtime
+//!
+//! This allows using solvers that aren't built into the crate. The library is opened with
+//! `dlopen`, so this is only available on unix.
+use std::{
+    cell::Cell,
+    ffi::{CStr, CString},
+    fmt,
+    os::{
+        raw::{c_char, c_int, c_void},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+
+use super::{Interrupter, Lbool, Lit, SatBackend};
+
+type TerminateCallback = extern "C" fn(state: *mut c_void) -> c_int;
+
+/// A loaded IPASIR library, closed when dropped
+pub struct IpasirLibrary {
+    path: PathBuf,
+    signature: String,
+    handle: *mut c_void,
+    init: unsafe extern "C" fn() -> *mut c_void,
+    release: unsafe extern "C" fn(solver: *mut c_void),
+    add: unsafe extern "C" fn(solver: *mut c_void, lit: c_int),
+    assume: unsafe extern "C" fn(solver: *mut c_void, lit: c_int),
+    solve: unsafe extern "C" fn(solver: *mut c_void) -> c_int,
+    val: unsafe extern "C" fn(solver: *mut c_void, lit: c_int) -> c_int,
+    failed: unsafe extern "C" fn(solver: *mut c_void, lit: c_int) -> c_int,
+    set_terminate: unsafe extern "C" fn(
+        solver: *mut c_void,
+        state: *mut c_void,
+        terminate: Option<TerminateCallback>,
+    ),
+}
+
+// The handle is only used to look up symbols and to close the library, which `dlopen`'s
+// functions allow from any thread. IPASIR solver instances are separate, so different instances
+// can be used from different threads.
+unsafe impl Send for IpasirLibrary {}
+unsafe impl Sync for IpasirLibrary {}
+
+/// The last error of `dlopen` or `dlsym`
+fn dl_error() -> String {
+    let error = unsafe { libc::dlerror() };
+    if error.is_null() {
+        "unknown error".to_owned()
+    } else {
+        unsafe { CStr::from_ptr(error) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Looks up the function `name`, which is unsafe as it must have the function pointer type `F`
+unsafe fn symbol<F>(handle: *mut c_void, path: &Path, name: &str) -> anyhow::Result<F> {
+    let c_name = CString::new(name).unwrap();
+    let symbol = libc::dlsym(handle, c_name.as_ptr());
+    if symbol.is_null() {
+        anyhow::bail!("IPASIR library {} doesn't export {}", path.display(), name);
+    }
+    Ok(std::mem::transmute_copy(&symbol))
+}
+
+impl IpasirLibrary {
+    /// Opens the shared library at `path`, which must export all IPASIR functions used
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .with_context(|| format!("IPASIR library path {}", path.display()))?;
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            anyhow::bail!("loading IPASIR library {}: {}", path.display(), dl_error());
+        }
+
+        let load = || -> anyhow::Result<Self> {
+            // The symbols have the function types declared by `ipasir.h`
+            unsafe {
+                let signature: unsafe extern "C" fn() -> *const c_char =
+                    symbol(handle, path, "ipasir_signature")?;
+                Ok(IpasirLibrary {
+                    path: path.to_owned(),
+                    signature: CStr::from_ptr(signature()).to_string_lossy().into_owned(),
+                    handle,
+                    init: symbol(handle, path, "ipasir_init")?,
+                    release: symbol(handle, path, "ipasir_release")?,
+                    add: symbol(handle, path, "ipasir_add")?,
+                    assume: symbol(handle, path, "ipasir_assume")?,
+                    solve: symbol(handle, path, "ipasir_solve")?,
+                    val: symbol(handle, path, "ipasir_val")?,
+                    failed: symbol(handle, path, "ipasir_failed")?,
+                    set_terminate: symbol(handle, path, "ipasir_set_terminate")?,
+                })
+            }
+        };
+        load().inspect_err(|_| unsafe {
+            libc::dlclose(handle);
+        })
+    }
+}
+
+impl fmt::Debug for IpasirLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IpasirLibrary")
+            .field("path", &self.path)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+impl Drop for IpasirLibrary {
+    fn drop(&mut self) {
+        unsafe { libc::dlclose(self.handle) };
+    }
+}
+
+/// State of the terminate callback, polled by the solver during a call
+struct Terminate {
+    interrupted: Arc<AtomicBool>,
+    deadline: Cell<Option<Instant>>,
+}
+
+extern "C" fn terminate(state: *mut c_void) -> c_int {
+    // The state is boxed and owned by the solver, so it outlives every call
+    let state = unsafe { &*(state as *const Terminate) };
+    let expired = state
+        .deadline
+        .get()
+        .is_some_and(|deadline| Instant::now() >= deadline);
+    (expired || state.interrupted.load(Ordering::Relaxed)) as c_int
+}
+
+/// A solver instance of a loaded IPASIR library
+///
+/// Whether time limits and interrupts take effect depends on the solver checking the terminate
+/// callback.
+pub struct Ipasir {
+    /// Keeps the library loaded while the instance exists
+    library: Arc<IpasirLibrary>,
+    solver: *mut c_void,
+    terminate: Box<Terminate>,
+    time_limit: Option<Duration>,
+    nvars: u32,
+    /// Number of variables that occurred in a clause or assumption, the solver doesn't know about
+    /// the others
+    used_vars: u32,
+    /// Of the last call, to find the failed ones
+    assumptions: Vec<Lit>,
+}
+
+/// Converts to the DIMACS style literals of IPASIR
+fn to_c(lit: Lit) -> c_int {
+    let var = lit.var() as c_int + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+impl Ipasir {
+    pub fn new(library: Arc<IpasirLibrary>, time_limit: Option<f64>) -> Self {
+        let solver = unsafe { (library.init)() };
+        assert!(
+            !solver.is_null(),
+            "failed to create an instance of {}",
+            library.signature
+        );
+        let terminate = Box::new(Terminate {
+            interrupted: Arc::default(),
+            deadline: Cell::new(None),
+        });
+        unsafe {
+            (library.set_terminate)(
+                solver,
+                &*terminate as *const Terminate as *mut c_void,
+                Some(self::terminate),
+            );
+        }
+        Ipasir {
+            library,
+            solver,
+            terminate,
+            time_limit: time_limit.map(Duration::from_secs_f64),
+            nvars: 0,
+            used_vars: 0,
+            assumptions: vec![],
+        }
+    }
+
+    fn use_var(&mut self, lit: Lit) {
+        self.used_vars = self.used_vars.max(lit.var() + 1);
+    }
+}
+
+impl Drop for Ipasir {
+    fn drop(&mut self) {
+        unsafe { (self.library.release)(self.solver) };
+    }
+}
+
+impl SatBackend for Ipasir {
+    fn nvars(&self) -> u32 {
+        self.nvars
+    }
+
+    fn new_var(&mut self) -> Lit {
+        self.nvars += 1;
+        Lit::new(self.nvars - 1, false).unwrap()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        for &lit in clause {
+            self.use_var(lit);
+            unsafe { (self.library.add)(self.solver, to_c(lit)) };
+        }
+        unsafe { (self.library.add)(self.solver, 0) };
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        for &lit in assumptions {
+            self.use_var(lit);
+            unsafe { (self.library.assume)(self.solver, to_c(lit)) };
+        }
+        self.assumptions.clear();
+        self.assumptions.extend_from_slice(assumptions);
+        self.terminate
+            .deadline
+            .set(self.time_limit.map(|limit| Instant::now() + limit));
+        match unsafe { (self.library.solve)(self.solver) } {
+            10 => Lbool::True,
+            20 => Lbool::False,
+            _ => Lbool::Undef,
+        }
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clear();
+        model.extend((0..self.nvars).map(|var| {
+            let lit = Lit::new(var, false).unwrap();
+            // IPASIR returns the literal that is true, or 0 if either value works
+            if var < self.used_vars && unsafe { (self.library.val)(self.solver, to_c(lit)) } > 0 {
+                Lbool::True
+            } else {
+                Lbool::False
+            }
+        }));
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clear();
+        for &lit in &self.assumptions {
+            if unsafe { (self.library.failed)(self.solver, to_c(lit)) } != 0 {
+                conflict.push(!lit);
+            }
+        }
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        let interrupted = self.terminate.interrupted.clone();
+        Some(Box::new(move || interrupted.store(true, Ordering::Relaxed)))
+    }
+}
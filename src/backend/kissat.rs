@@ -0,0 +1,119 @@
+//! The kissat backend, using kissat's C API
+//!
+//! No bindings are available as a dependency, so the few functions needed are declared here. The
+//! library `libkissat` has to be installed where the linker finds it, e.g. by setting
+//! `RUSTFLAGS=-L/path/to/kissat/build`.
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_int},
+};
+
+use super::{Lbool, Lit, SatBackend};
+
+/// Opaque solver handle of the C API
+#[repr(C)]
+struct KissatSolver {
+    _private: [u8; 0],
+}
+
+#[link(name = "kissat")]
+extern "C" {
+    fn kissat_init() -> *mut KissatSolver;
+    fn kissat_release(solver: *mut KissatSolver);
+    fn kissat_add(solver: *mut KissatSolver, lit: c_int);
+    fn kissat_solve(solver: *mut KissatSolver) -> c_int;
+    fn kissat_value(solver: *mut KissatSolver, lit: c_int) -> c_int;
+    fn kissat_set_option(solver: *mut KissatSolver, name: *const c_char, value: c_int) -> c_int;
+}
+
+/// The kissat backend
+///
+/// kissat isn't incremental, so this supports a single call, for which the assumptions are added
+/// as unit clauses. It also can't report a conflict, which is why it is only used through
+/// [`InitialSolve`](super::InitialSolve).
+pub struct Kissat {
+    solver: *mut KissatSolver,
+    nvars: u32,
+    solved: bool,
+}
+
+/// Converts to the DIMACS style literals of the C API
+fn to_c(lit: Lit) -> c_int {
+    let var = lit.var() as c_int + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+impl Kissat {
+    pub fn new(options: &[(String, i32)]) -> Self {
+        let solver = unsafe { kissat_init() };
+        assert!(!solver.is_null(), "failed to create a kissat instance");
+        for (name, value) in options {
+            let name = CString::new(&name[..]).expect("option name contains a NUL byte");
+            unsafe { kissat_set_option(solver, name.as_ptr(), *value) };
+        }
+        Kissat {
+            solver,
+            nvars: 0,
+            solved: false,
+        }
+    }
+}
+
+impl Drop for Kissat {
+    fn drop(&mut self) {
+        unsafe { kissat_release(self.solver) };
+    }
+}
+
+impl SatBackend for Kissat {
+    fn nvars(&self) -> u32 {
+        self.nvars
+    }
+
+    fn new_var(&mut self) -> Lit {
+        self.nvars += 1;
+        Lit::new(self.nvars - 1, false).unwrap()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        assert!(!self.solved, "kissat isn't incremental");
+        for &lit in clause {
+            unsafe { kissat_add(self.solver, to_c(lit)) };
+        }
+        unsafe { kissat_add(self.solver, 0) };
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        assert!(!self.solved, "kissat isn't incremental");
+        for &lit in assumptions {
+            self.add_clause(&[lit]);
+        }
+        self.solved = true;
+        match unsafe { kissat_solve(self.solver) } {
+            10 => Lbool::True,
+            20 => Lbool::False,
+            _ => Lbool::Undef,
+        }
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clear();
+        model.extend((0..self.nvars).map(|var| {
+            let lit = Lit::new(var, false).unwrap();
+            // Variables that don't occur in any clause have the value 0
+            if unsafe { kissat_value(self.solver, to_c(lit)) } > 0 {
+                Lbool::True
+            } else {
+                Lbool::False
+            }
+        }));
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clear();
+    }
+}
@@ -0,0 +1,176 @@
+//! The MiniSat backend, using the standard IPASIR interface of incremental solvers
+//!
+//! No bindings are available as a dependency, so the IPASIR functions are declared here. They are
+//! linked statically from `libipasirminisat.a`, which has to be found by the linker, e.g. by
+//! setting `RUSTFLAGS=-L/path/to/minisat/build`. Being a fixed and well known solver, this is
+//! meant as a baseline when comparing the other backends.
+use std::{
+    cell::Cell,
+    os::raw::{c_int, c_void},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{Interrupter, Lbool, Lit, SatBackend};
+
+#[link(name = "ipasirminisat", kind = "static")]
+#[cfg_attr(target_os = "macos", link(name = "c++"))]
+#[cfg_attr(not(target_os = "macos"), link(name = "stdc++"))]
+extern "C" {
+    fn ipasir_init() -> *mut c_void;
+    fn ipasir_release(solver: *mut c_void);
+    fn ipasir_add(solver: *mut c_void, lit: c_int);
+    fn ipasir_assume(solver: *mut c_void, lit: c_int);
+    fn ipasir_solve(solver: *mut c_void) -> c_int;
+    fn ipasir_val(solver: *mut c_void, lit: c_int) -> c_int;
+    fn ipasir_failed(solver: *mut c_void, lit: c_int) -> c_int;
+    fn ipasir_set_terminate(
+        solver: *mut c_void,
+        state: *mut c_void,
+        terminate: Option<extern "C" fn(state: *mut c_void) -> c_int>,
+    );
+}
+
+/// State of the terminate callback, polled by MiniSat during a call
+struct Terminate {
+    interrupted: Arc<AtomicBool>,
+    deadline: Cell<Option<Instant>>,
+}
+
+extern "C" fn terminate(state: *mut c_void) -> c_int {
+    // The state is boxed and owned by the solver, so it outlives every call
+    let state = unsafe { &*(state as *const Terminate) };
+    let expired = state
+        .deadline
+        .get()
+        .is_some_and(|deadline| Instant::now() >= deadline);
+    (expired || state.interrupted.load(Ordering::Relaxed)) as c_int
+}
+
+/// The MiniSat backend
+///
+/// IPASIR has no options, so this always uses MiniSat's defaults. Once interrupted, every
+/// following call returns [`Lbool::Undef`] immediately.
+pub struct MiniSat {
+    solver: *mut c_void,
+    terminate: Box<Terminate>,
+    time_limit: Option<Duration>,
+    nvars: u32,
+    /// Number of variables that occurred in a clause or assumption, MiniSat doesn't know about
+    /// the others
+    used_vars: u32,
+    /// Of the last call, to find the failed ones
+    assumptions: Vec<Lit>,
+}
+
+/// Converts to the DIMACS style literals of IPASIR
+fn to_c(lit: Lit) -> c_int {
+    let var = lit.var() as c_int + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+impl MiniSat {
+    pub fn new(time_limit: Option<f64>) -> Self {
+        let solver = unsafe { ipasir_init() };
+        assert!(!solver.is_null(), "failed to create a MiniSat instance");
+        let terminate = Box::new(Terminate {
+            interrupted: Arc::default(),
+            deadline: Cell::new(None),
+        });
+        unsafe {
+            ipasir_set_terminate(
+                solver,
+                &*terminate as *const Terminate as *mut c_void,
+                Some(self::terminate),
+            );
+        }
+        MiniSat {
+            solver,
+            terminate,
+            time_limit: time_limit.map(Duration::from_secs_f64),
+            nvars: 0,
+            used_vars: 0,
+            assumptions: vec![],
+        }
+    }
+
+    fn use_var(&mut self, lit: Lit) {
+        self.used_vars = self.used_vars.max(lit.var() + 1);
+    }
+}
+
+impl Drop for MiniSat {
+    fn drop(&mut self) {
+        unsafe { ipasir_release(self.solver) };
+    }
+}
+
+impl SatBackend for MiniSat {
+    fn nvars(&self) -> u32 {
+        self.nvars
+    }
+
+    fn new_var(&mut self) -> Lit {
+        self.nvars += 1;
+        Lit::new(self.nvars - 1, false).unwrap()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        for &lit in clause {
+            self.use_var(lit);
+            unsafe { ipasir_add(self.solver, to_c(lit)) };
+        }
+        unsafe { ipasir_add(self.solver, 0) };
+    }
+
+    fn solve_with_assumptions(&mut self, assumptions: &[Lit]) -> Lbool {
+        for &lit in assumptions {
+            self.use_var(lit);
+            unsafe { ipasir_assume(self.solver, to_c(lit)) };
+        }
+        self.assumptions.clear();
+        self.assumptions.extend_from_slice(assumptions);
+        self.terminate
+            .deadline
+            .set(self.time_limit.map(|limit| Instant::now() + limit));
+        match unsafe { ipasir_solve(self.solver) } {
+            10 => Lbool::True,
+            20 => Lbool::False,
+            _ => Lbool::Undef,
+        }
+    }
+
+    fn model(&self, model: &mut Vec<Lbool>) {
+        model.clear();
+        model.extend((0..self.nvars).map(|var| {
+            let lit = Lit::new(var, false).unwrap();
+            // IPASIR returns the literal that is true, or 0 if either value works
+            if var < self.used_vars && unsafe { ipasir_val(self.solver, to_c(lit)) } > 0 {
+                Lbool::True
+            } else {
+                Lbool::False
+            }
+        }));
+    }
+
+    fn conflict(&self, conflict: &mut Vec<Lit>) {
+        conflict.clear();
+        for &lit in &self.assumptions {
+            if unsafe { ipasir_failed(self.solver, to_c(lit)) } != 0 {
+                conflict.push(!lit);
+            }
+        }
+    }
+
+    fn interrupter(&self) -> Option<Interrupter> {
+        let interrupted = self.terminate.interrupted.clone();
+        Some(Box::new(move || interrupted.store(true, Ordering::Relaxed)))
+    }
+}
@@ -0,0 +1,268 @@
+//! Proofs of the unsatisfiable calls of the builtin solver, in LRAT, FRAT or DRAT format
+//!
+//! Every derived clause comes with its hints, the clauses whose unit propagation, in this order,
+//! falsifies its negation, so the proofs can be checked without search. An unsatisfiable call
+//! derives the empty clause or, under assumptions, the negation of the failed assumptions.
+//!
+//! FRAT proofs list the original clauses themselves, so they are written while the solver runs.
+//! LRAT proofs refer to the original clauses by their position in a separate formula, before any
+//! derived clause, but clauses are added between calls. So an LRAT proof is kept in memory and
+//! written when the solver is dropped, together with its formula: the clauses added to the solver
+//! in order, except tautologies. A DRAT proof is the same stream of clauses without the ids and
+//! hints, and deletions list the literals of the deleted clause.
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use super::Lit;
+use crate::solver_config::ProofFormat;
+
+/// Identifies a clause of the proof
+pub type ClauseId = u64;
+
+/// Writes the proof of a solver
+pub struct Proof {
+    path: PathBuf,
+    target: Target,
+    next_id: ClauseId,
+}
+
+enum Target {
+    Frat {
+        file: io::BufWriter<fs::File>,
+        /// Clauses that weren't deleted, which are finalized at the end
+        live: HashMap<ClauseId, Vec<Lit>>,
+    },
+    Lrat {
+        file: fs::File,
+        formula: fs::File,
+        originals: Vec<(ClauseId, Vec<Lit>)>,
+        steps: Vec<Step>,
+        /// Writes the steps as DRAT instead
+        drat: bool,
+    },
+}
+
+enum Step {
+    Add(ClauseId, Vec<Lit>, Vec<ClauseId>),
+    Delete(ClauseId),
+}
+
+/// Converts to a DIMACS style literal
+fn dimacs(lit: Lit) -> i64 {
+    let var = lit.var() as i64 + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+fn write_lits(file: &mut impl Write, lits: &[Lit]) -> io::Result<()> {
+    for &lit in lits {
+        write!(file, "{} ", dimacs(lit))?;
+    }
+    write!(file, "0")
+}
+
+/// The formula of the LRAT proof at `path`, which is `path` followed by `.cnf`
+///
+/// Replacing the extension instead could overwrite the input, e.g. `input.cnf` for `input.lrat`.
+pub fn formula_path(path: &Path) -> PathBuf {
+    let mut formula = path.as_os_str().to_owned();
+    formula.push(".cnf");
+    formula.into()
+}
+
+impl Proof {
+    /// Creates the proof file at `path` and, for LRAT and DRAT, its formula at [`formula_path`]
+    pub fn create(path: &Path, format: ProofFormat) -> anyhow::Result<Proof> {
+        let file =
+            fs::File::create(path).with_context(|| format!("creating proof {}", path.display()))?;
+        let target = match format {
+            ProofFormat::Frat => Target::Frat {
+                file: io::BufWriter::new(file),
+                live: HashMap::new(),
+            },
+            ProofFormat::Lrat | ProofFormat::Drat => {
+                let formula_path = formula_path(path);
+                let formula = fs::File::create(&formula_path).with_context(|| {
+                    format!("creating proof formula {}", formula_path.display())
+                })?;
+                Target::Lrat {
+                    file,
+                    formula,
+                    originals: vec![],
+                    steps: vec![],
+                    drat: format == ProofFormat::Drat,
+                }
+            }
+        };
+        Ok(Proof {
+            path: path.to_owned(),
+            target,
+            next_id: 1,
+        })
+    }
+
+    /// Panics on errors, as the solver interface can't report them
+    fn check(&self, result: io::Result<()>) {
+        if let Err(error) = result {
+            panic!("writing proof {}: {}", self.path.display(), error);
+        }
+    }
+
+    /// Records a clause added to the solver
+    pub fn original(&mut self, lits: &[Lit]) -> ClauseId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let result = match &mut self.target {
+            Target::Frat { file, live } => {
+                live.insert(id, lits.to_vec());
+                write!(file, "o {} ", id)
+                    .and_then(|()| write_lits(file, lits))
+                    .and_then(|()| writeln!(file))
+            }
+            Target::Lrat { originals, .. } => {
+                originals.push((id, lits.to_vec()));
+                Ok(())
+            }
+        };
+        self.check(result);
+        id
+    }
+
+    /// Records a clause derived by unit propagation over the `hints`
+    pub fn add(&mut self, lits: &[Lit], hints: &[ClauseId]) -> ClauseId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let result = match &mut self.target {
+            Target::Frat { file, live } => {
+                live.insert(id, lits.to_vec());
+                write!(file, "a {} ", id)
+                    .and_then(|()| write_lits(file, lits))
+                    .and_then(|()| write!(file, " l "))
+                    .and_then(|()| hints.iter().try_for_each(|hint| write!(file, "{} ", hint)))
+                    .and_then(|()| writeln!(file, "0"))
+            }
+            Target::Lrat { steps, .. } => {
+                steps.push(Step::Add(id, lits.to_vec(), hints.to_vec()));
+                Ok(())
+            }
+        };
+        self.check(result);
+        id
+    }
+
+    /// Records that the clause `id` is no longer used
+    pub fn delete(&mut self, id: ClauseId) {
+        let result = match &mut self.target {
+            Target::Frat { file, live } => {
+                let lits = live.remove(&id).expect("clause of the proof");
+                write!(file, "d {} ", id)
+                    .and_then(|()| write_lits(file, &lits))
+                    .and_then(|()| writeln!(file))
+            }
+            Target::Lrat { steps, .. } => {
+                steps.push(Step::Delete(id));
+                Ok(())
+            }
+        };
+        self.check(result);
+    }
+
+    /// Finalizes a FRAT proof or writes an LRAT or DRAT proof and its formula
+    fn finish(&mut self) -> io::Result<()> {
+        match &mut self.target {
+            Target::Frat { file, live } => {
+                let mut live: Vec<_> = live.drain().collect();
+                live.sort_unstable();
+                for (id, lits) in live {
+                    write!(file, "f {} ", id)?;
+                    write_lits(file, &lits)?;
+                    writeln!(file)?;
+                }
+                file.flush()
+            }
+            Target::Lrat {
+                file,
+                formula,
+                originals,
+                steps,
+                drat,
+            } => {
+                // The originals are numbered by their position in the formula and the derived
+                // clauses after them
+                let mut ids = HashMap::new();
+                let vars = originals
+                    .iter()
+                    .flat_map(|(_, lits)| lits)
+                    .map(|lit| lit.var() + 1)
+                    .max()
+                    .unwrap_or(0);
+                let mut formula = io::BufWriter::new(formula);
+                writeln!(formula, "p cnf {} {}", vars, originals.len())?;
+                for (id, lits) in originals.iter() {
+                    ids.insert(*id, ids.len() as ClauseId + 1);
+                    write_lits(&mut formula, lits)?;
+                    writeln!(formula)?;
+                }
+                formula.flush()?;
+
+                let mut file = io::BufWriter::new(file);
+                if *drat {
+                    let mut clauses: HashMap<ClauseId, &[Lit]> = originals
+                        .iter()
+                        .map(|(id, lits)| (*id, &lits[..]))
+                        .collect();
+                    for step in steps.iter() {
+                        match step {
+                            Step::Add(id, lits, _) => {
+                                clauses.insert(*id, lits);
+                                write_lits(&mut file, lits)?;
+                            }
+                            Step::Delete(id) => {
+                                write!(file, "d ")?;
+                                write_lits(&mut file, clauses[id])?;
+                            }
+                        }
+                        writeln!(file)?;
+                    }
+                    return file.flush();
+                }
+                let mut last = ids.len() as ClauseId;
+                for step in steps.iter() {
+                    match step {
+                        Step::Add(id, lits, hints) => {
+                            last += 1;
+                            ids.insert(*id, last);
+                            write!(file, "{} ", last)?;
+                            write_lits(&mut file, lits)?;
+                            write!(file, " ")?;
+                            for hint in hints {
+                                write!(file, "{} ", ids[hint])?;
+                            }
+                            writeln!(file, "0")?;
+                        }
+                        Step::Delete(id) => writeln!(file, "{} d {} 0", last, ids[id])?,
+                    }
+                }
+                file.flush()
+            }
+        }
+    }
+}
+
+impl Drop for Proof {
+    fn drop(&mut self) {
+        // Panicking in drop would abort when already unwinding
+        if let Err(error) = self.finish() {
+            eprintln!("Error: writing proof {}: {}", self.path.display(), error);
+        }
+    }
+}
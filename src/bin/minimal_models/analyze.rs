@@ -0,0 +1,258 @@
+//! The `analyze` subcommand
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::BufRead,
+};
+
+use anyhow::Context;
+use minimal_models::{autarky, definability, input, structure, Finder, Solved, SolverOptions};
+
+/// What the `analyze` subcommand prints after the summary
+#[derive(Clone, Eq, PartialEq)]
+pub enum Analysis {
+    Summary,
+    /// The literal balance and structure
+    Stats,
+    /// The community decomposition, with `reduce` also reducing a model and reporting the
+    /// literals kept in each community
+    Communities {
+        reduce: bool,
+    },
+    /// A maximal autarky and the lean kernel
+    Autarky,
+    /// The variables defined by `basis` or, without one, by an independent support
+    Defined {
+        basis: Option<Vec<isize>>,
+    },
+}
+
+/// Runs the `analyze` subcommand, printing statistics about the clauses of the input
+pub fn analyze(reader: impl BufRead, max_var: usize, analysis: Analysis) -> anyhow::Result<()> {
+    let mut clauses = 0;
+    let mut solve_requests = 0;
+    let mut lengths: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut tautologies = 0;
+    let mut duplicate_lits = 0;
+    let mut distinct = HashSet::new();
+    let mut polarities: HashMap<isize, (bool, bool)> = HashMap::new();
+    let (mut positive, mut negative) = (0, 0);
+    for (index, line) in reader.lines().enumerate() {
+        let mut clause =
+            input::parse_clause(&line?, max_var).with_context(|| format!("line {}", index + 1))?;
+        if clause.is_empty() {
+            solve_requests += 1;
+            continue;
+        }
+        clauses += 1;
+        *lengths.entry(clause.len()).or_default() += 1;
+        for &lit in &clause {
+            let polarity = polarities.entry(lit.abs()).or_default();
+            if lit > 0 {
+                polarity.0 = true;
+                positive += 1;
+            } else {
+                polarity.1 = true;
+                negative += 1;
+            }
+        }
+        clause.sort_unstable();
+        tautologies += clause.iter().any(|lit| clause.contains(&-lit)) as usize;
+        let len = clause.len();
+        clause.dedup();
+        duplicate_lits += (clause.len() < len) as usize;
+        distinct.insert(clause);
+    }
+
+    println!("clauses: {}", clauses);
+    println!("distinct clauses: {}", distinct.len());
+    println!("variables: {}", polarities.len());
+    println!(
+        "max variable: {}",
+        polarities.keys().max().cloned().unwrap_or(0)
+    );
+    println!(
+        "pure variables: {}",
+        polarities
+            .values()
+            .filter(|&&(positive, negative)| positive != negative)
+            .count()
+    );
+    println!("tautologies: {}", tautologies);
+    println!("clauses with duplicate literals: {}", duplicate_lits);
+    println!("solve requests: {}", solve_requests);
+    for (length, count) in lengths {
+        println!("clauses of length {}: {}", length, count);
+    }
+    let mut distinct: Vec<Vec<isize>> = distinct.into_iter().collect();
+    distinct.sort_unstable();
+    match analysis {
+        Analysis::Summary => Ok(()),
+        Analysis::Stats => {
+            analyze_stats(&distinct, positive, negative);
+            Ok(())
+        }
+        Analysis::Communities { reduce } => analyze_communities(&distinct, reduce),
+        Analysis::Autarky => analyze_autarky(&distinct),
+        Analysis::Defined { basis } => analyze_defined(&distinct, basis),
+    }
+}
+
+/// Prints the literal balance and the constraints found in the `distinct` clauses
+fn analyze_stats(distinct: &[Vec<isize>], positive: usize, negative: usize) {
+    println!("positive literals: {}", positive);
+    println!("negative literals: {}", negative);
+    let xors = structure::find_xors(distinct);
+    let chains = structure::xor_chains(&xors);
+    println!("xor constraints: {}", xors.len());
+    println!("xor chains: {}", chains.len());
+    println!(
+        "longest xor chain: {}",
+        chains.first().cloned().unwrap_or(0)
+    );
+    let at_most_one = structure::find_at_most_one(distinct);
+    println!("at-most-one constraints: {}", at_most_one.len());
+    println!(
+        "largest at-most-one constraint: {}",
+        at_most_one.iter().map(Vec::len).max().unwrap_or(0)
+    );
+}
+
+/// Prints the communities of the `distinct` clauses and, with `reduce`, how many literals of each
+/// community a reduced model keeps
+fn analyze_communities(distinct: &[Vec<isize>], reduce: bool) -> anyhow::Result<()> {
+    let communities = structure::communities(distinct);
+    let mut community_of = HashMap::new();
+    for (index, community) in communities.iter().enumerate() {
+        for &var in community {
+            community_of.insert(var, index);
+        }
+    }
+    let within = distinct
+        .iter()
+        .filter(|clause| {
+            let community = community_of[&clause[0].abs()];
+            clause
+                .iter()
+                .all(|lit| community_of[&lit.abs()] == community)
+        })
+        .count();
+    println!("communities: {}", communities.len());
+    println!(
+        "modularity: {:.3}",
+        structure::modularity(distinct, &communities)
+    );
+    println!("clauses within communities: {}", within);
+    println!("clauses between communities: {}", distinct.len() - within);
+    for (index, community) in communities.iter().enumerate() {
+        print!("community {}: {} variables:", index + 1, community.len());
+        for var in community {
+            print!(" {}", var);
+        }
+        println!();
+    }
+    if !reduce {
+        return Ok(());
+    }
+
+    let mut finder = Finder::builder().build()?;
+    for clause in distinct {
+        finder.add_clause(clause);
+    }
+    let model = match finder.solve() {
+        Solved::Model(model) => model,
+        Solved::Unsat => {
+            println!("reduced model: unsat");
+            return Ok(());
+        }
+        Solved::Unknown => {
+            println!("reduced model: unknown");
+            return Ok(());
+        }
+    };
+    let reduced = finder.reduce(&model).model;
+    print!("reduced model:");
+    for lit in &reduced {
+        print!(" {}", lit);
+    }
+    println!();
+    let mut kept = vec![0; communities.len()];
+    for lit in &reduced {
+        kept[community_of[&lit.abs()]] += 1;
+    }
+    for (index, community) in communities.iter().enumerate() {
+        println!(
+            "community {}: kept {} of {} literals",
+            index + 1,
+            kept[index],
+            community.len()
+        );
+    }
+    Ok(())
+}
+
+/// Prints a maximal autarky of the `distinct` clauses and their lean kernel
+fn analyze_autarky(distinct: &[Vec<isize>]) -> anyhow::Result<()> {
+    let found = match autarky::maximal_autarky(distinct, &SolverOptions::default())? {
+        Some(found) => found,
+        None => {
+            println!("autarky: unknown");
+            return Ok(());
+        }
+    };
+    print!("autarky: ");
+    for lit in &found.assignment {
+        print!("{} ", lit);
+    }
+    println!();
+    println!("autark clauses: {}", distinct.len() - found.kernel.len());
+    println!("lean kernel clauses: {}", found.kernel.len());
+    for &index in &found.kernel {
+        print!("kernel: ");
+        for lit in &distinct[index] {
+            print!("{} ", lit);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Prints the variables of the `distinct` clauses that `basis` defines and those it doesn't, where
+/// the basis defaults to an independent support
+fn analyze_defined(distinct: &[Vec<isize>], basis: Option<Vec<isize>>) -> anyhow::Result<()> {
+    let options = SolverOptions::default();
+    let basis = match basis {
+        Some(basis) => Some(basis),
+        None => definability::independent_support(distinct, &options)?,
+    };
+    let defined = match &basis {
+        Some(basis) => definability::defined_vars(distinct, basis, &options)?,
+        None => None,
+    };
+    let (basis, defined) = match (basis, defined) {
+        (Some(basis), Some(defined)) => (basis, defined),
+        _ => {
+            println!("defined: unknown");
+            return Ok(());
+        }
+    };
+    let basis: BTreeSet<isize> = basis.iter().map(|lit| lit.abs()).collect();
+    let vars: BTreeSet<isize> = distinct.iter().flatten().map(|lit| lit.abs()).collect();
+    print!("basis: ");
+    for var in &basis {
+        print!("{} ", var);
+    }
+    println!();
+    print!("defined: ");
+    for var in &defined {
+        print!("{} ", var);
+    }
+    println!();
+    print!("undefined: ");
+    for var in vars.difference(&basis) {
+        if !defined.contains(var) {
+            print!("{} ", var);
+        }
+    }
+    println!();
+    Ok(())
+}
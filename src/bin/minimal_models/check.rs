@@ -0,0 +1,309 @@
+//! The `check`, `diff`, `extend`, `reduce` and `verify-cert` subcommands, which work on given
+//! assignments
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::Context;
+use minimal_models::{certificate, input, verify, Assumed, Finder, Solved};
+
+/// Reads the non-empty clauses of an input file together with their line numbers
+fn read_clauses(input: &Path, max_var: usize) -> anyhow::Result<(Vec<Vec<isize>>, Vec<usize>)> {
+    let mut clauses = vec![];
+    let mut lines = vec![];
+    let text = fs::read_to_string(input).with_context(|| format!("reading {}", input.display()))?;
+    for (index, line) in text.lines().enumerate() {
+        let clause =
+            input::parse_clause(line, max_var).with_context(|| format!("line {}", index + 1))?;
+        if !clause.is_empty() {
+            clauses.push(clause);
+            lines.push(index + 1);
+        }
+    }
+    Ok((clauses, lines))
+}
+
+/// Checks a certificate written by `--certificate` for the given input
+pub fn verify_cert(input: &Path, certificate: &Path, max_var: usize) -> anyhow::Result<()> {
+    let (clauses, _) = read_clauses(input, max_var)?;
+    let checked = certificate::check(&clauses, &fs::read_to_string(certificate)?)?;
+    println!(
+        "verified {} minimal models and {} possibly non-minimal models",
+        checked.minimal, checked.unverified
+    );
+    Ok(())
+}
+
+/// Checks whether a partial assignment entails the clauses of the given input and is minimal
+///
+/// The assignment is read as literals spread over any number of lines, each optionally
+/// terminated by a `0`, so a reported model can be used directly.
+pub fn check_assignment(input: &Path, assignment: &Path, max_var: usize) -> anyhow::Result<()> {
+    let (clauses, lines) = read_clauses(input, max_var)?;
+    let model = read_assignment(assignment, max_var)?;
+    match find_flaw(&clauses, &model)? {
+        Some(Flaw::Unentailed(unentailed)) => {
+            print!("unentailed clauses on lines: ");
+            for index in unentailed {
+                print!("{} ", lines[index]);
+            }
+            println!();
+            anyhow::bail!("the assignment doesn't entail the formula");
+        }
+        Some(Flaw::Redundant(redundant)) => {
+            print!("redundant: ");
+            for lit in redundant {
+                print!("{} ", lit);
+            }
+            println!();
+            anyhow::bail!("the assignment entails the formula, but isn't minimal");
+        }
+        None => {
+            println!("minimal");
+            Ok(())
+        }
+    }
+}
+
+/// Reads a (partial) assignment, given by the literals on all lines of a file
+///
+/// A line may start with a label ending in `:`, which is skipped, so lines like `reduced model:
+/// 1 -2` can be copied from the output.
+pub fn read_assignment(path: &Path, max_var: usize) -> anyhow::Result<Vec<isize>> {
+    let mut model = vec![];
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    for (index, line) in text.lines().enumerate() {
+        let lits = line.rsplit_once(':').map_or(line, |(_, lits)| lits);
+        model.extend(
+            input::parse_clause(lits, max_var)
+                .with_context(|| format!("line {} of {}", index + 1, path.display()))?,
+        );
+    }
+    Ok(model)
+}
+
+/// Reads the models reported by earlier runs, one per line
+///
+/// Lines of literals are models, as are the `reduced model:` and `circumscribed model:` lines of
+/// the output, where a `projected model:` line replaces the model before it. Other lines are
+/// ignored, so the complete output of a run can be used.
+pub fn read_models(path: &Path, max_var: usize) -> anyhow::Result<Vec<Vec<isize>>> {
+    let mut models = vec![];
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_start();
+        let lits = match line.split_once(':') {
+            Some(("reduced model" | "circumscribed model", lits)) => lits,
+            Some(("projected model", lits)) => {
+                models.pop();
+                lits
+            }
+            None if line.starts_with(|c: char| c == '-' || c.is_ascii_digit()) => line,
+            _ => continue,
+        };
+        models.push(
+            input::parse_clause(lits, max_var)
+                .with_context(|| format!("line {} of {}", index + 1, path.display()))?,
+        );
+    }
+    Ok(models)
+}
+
+/// Reads a model written by another solver
+///
+/// Accepts the DIMACS solution format, with the literals on `v` lines and `c` and `s` lines
+/// ignored, as well as literals without any prefix, e.g. one per line.
+fn read_model(path: &Path, max_var: usize) -> anyhow::Result<Vec<isize>> {
+    let mut model = vec![];
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_start();
+        let lits = match line.split_once(|c: char| c.is_ascii_whitespace()) {
+            Some(("c", _)) => continue,
+            Some(("s", status)) => {
+                if status.trim() != "SATISFIABLE" {
+                    anyhow::bail!("{} reports {}", path.display(), status.trim());
+                }
+                continue;
+            }
+            Some(("v", lits)) => lits,
+            _ if line == "c" || line == "v" => continue,
+            _ => line,
+        };
+        model.extend(
+            input::parse_clause(lits, max_var)
+                .with_context(|| format!("line {} of {}", index + 1, path.display()))?,
+        );
+    }
+    Ok(model)
+}
+
+/// Why an assignment isn't a minimal model
+enum Flaw {
+    /// Indices of the clauses it doesn't entail
+    Unentailed(Vec<usize>),
+    /// Literals that can be removed while still entailing all clauses
+    Redundant(Vec<isize>),
+}
+
+/// Checks whether `model` is a minimal model of `clauses`
+fn find_flaw(clauses: &[Vec<isize>], model: &[isize]) -> anyhow::Result<Option<Flaw>> {
+    let unentailed = verify::unentailed(clauses, model)?;
+    if !unentailed.is_empty() {
+        return Ok(Some(Flaw::Unentailed(unentailed)));
+    }
+    let redundant: Vec<isize> = model
+        .iter()
+        .zip(verify::witnesses(clauses, model)?)
+        .filter(|(_, witness)| witness.is_none())
+        .map(|(&lit, _)| lit)
+        .collect();
+    Ok((!redundant.is_empty()).then_some(Flaw::Redundant(redundant)))
+}
+
+/// Runs the `diff` subcommand, comparing two assignments and, given `input`, checking both
+pub fn diff_assignments(
+    a: &Path,
+    b: &Path,
+    input: Option<&Path>,
+    max_var: usize,
+) -> anyhow::Result<()> {
+    let models = [read_assignment(a, max_var)?, read_assignment(b, max_var)?];
+    let sets: Vec<HashSet<isize>> = models
+        .iter()
+        .map(|model| model.iter().copied().collect())
+        .collect();
+    let print_lits = |label: &str, lits: &mut dyn Iterator<Item = &isize>| {
+        print!("{}: ", label);
+        for lit in lits {
+            print!("{} ", lit);
+        }
+        println!();
+    };
+    print_lits(
+        "agreements",
+        &mut models[0].iter().filter(|lit| sets[1].contains(lit)),
+    );
+    print_lits(
+        "conflicts",
+        &mut models[0].iter().filter(|lit| sets[1].contains(&-**lit)),
+    );
+    for (label, index) in [("only a", 0), ("only b", 1)] {
+        let other = &sets[1 - index];
+        print_lits(
+            label,
+            &mut models[index]
+                .iter()
+                .filter(|lit| !other.contains(lit) && !other.contains(&-**lit)),
+        );
+    }
+
+    let input = match input {
+        Some(input) => input,
+        None => return Ok(()),
+    };
+    let (clauses, lines) = read_clauses(input, max_var)?;
+    for (label, model) in ["a", "b"].iter().zip(&models) {
+        match find_flaw(&clauses, model)? {
+            Some(Flaw::Unentailed(unentailed)) => {
+                print!("{}: unentailed clauses on lines: ", label);
+                for index in unentailed {
+                    print!("{} ", lines[index]);
+                }
+                println!();
+            }
+            Some(Flaw::Redundant(redundant)) => {
+                print!("{}: redundant: ", label);
+                for lit in redundant {
+                    print!("{} ", lit);
+                }
+                println!();
+            }
+            None => println!("{}: minimal", label),
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `reduce` subcommand, reducing a model of the input found by another solver
+pub fn reduce_model(input: &Path, model: &Path, max_var: usize) -> anyhow::Result<()> {
+    let (clauses, lines) = read_clauses(input, max_var)?;
+    let model = read_model(model, max_var)?;
+    let lits: HashSet<isize> = model.iter().copied().collect();
+    if let Some(lit) = model.iter().find(|lit| lits.contains(&-**lit)) {
+        anyhow::bail!("the model contains both {} and {}", lit, -lit);
+    }
+    let unsatisfied = verify::unentailed(&clauses, &model)?;
+    if !unsatisfied.is_empty() {
+        print!("unsatisfied clauses on lines: ");
+        for index in unsatisfied {
+            print!("{} ", lines[index]);
+        }
+        println!();
+        anyhow::bail!("the model doesn't satisfy the formula");
+    }
+
+    let mut finder = Finder::builder().build()?;
+    for clause in &clauses {
+        finder.add_clause(clause);
+    }
+    // Variables outside of the formula are never needed
+    let vars: HashSet<isize> = clauses.iter().flatten().map(|lit| lit.abs()).collect();
+    let model: Vec<isize> = model
+        .into_iter()
+        .filter(|lit| vars.contains(&lit.abs()))
+        .collect();
+    let reduced = finder.reduce(&model);
+    print!("reduced model: ");
+    for lit in &reduced.model {
+        print!("{} ", lit);
+    }
+    println!();
+    Ok(())
+}
+
+/// Runs the `extend` subcommand, completing a partial assignment to a full model of the input
+pub fn extend_assignment(input: &Path, partial: &Path, max_var: usize) -> anyhow::Result<()> {
+    let (clauses, _) = read_clauses(input, max_var)?;
+    let partial = read_assignment(partial, max_var)?;
+    let lits: HashSet<isize> = partial.iter().copied().collect();
+    if let Some(lit) = partial.iter().find(|lit| lits.contains(&-**lit)) {
+        anyhow::bail!("the assignment contains both {} and {}", lit, -lit);
+    }
+
+    let mut finder = Finder::builder().build()?;
+    for clause in &clauses {
+        finder.add_clause(clause);
+    }
+    finder.set_assumptions(&partial);
+    match finder.solve() {
+        Solved::Model(model) => {
+            print!("extension: ");
+            for lit in &model {
+                print!("{} ", lit);
+            }
+            println!();
+            print!("added: ");
+            for lit in model.iter().filter(|lit| !lits.contains(lit)) {
+                print!("{} ", lit);
+            }
+            println!();
+            Ok(())
+        }
+        Solved::Unsat => {
+            // The core isn't available from `solve`, so we ask again without the assumptions
+            finder.set_assumptions(&[]);
+            match finder.solve_under_assumptions(&partial) {
+                Assumed::Unsat { core } if !core.is_empty() => {
+                    print!("conflicting: ");
+                    for lit in core {
+                        print!("{} ", lit);
+                    }
+                    println!();
+                    anyhow::bail!("the assignment can't be extended to a model");
+                }
+                Assumed::Unknown => anyhow::bail!("the solver gave up"),
+                _ => anyhow::bail!("the formula is unsatisfiable"),
+            }
+        }
+        Solved::Unknown => anyhow::bail!("the solver gave up"),
+    }
+}
@@ -0,0 +1,605 @@
+//! The command line itself: usage, options, shell completions and configuration files
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use minimal_models::config::{self, OptionValue};
+
+use crate::arg_value;
+
+pub const USAGE: &str = "\
+usage: minimal_models [solve] [OPTIONS] [INPUT]
+       minimal_models enumerate [OPTIONS] [INPUT]
+       minimal_models check INPUT ASSIGNMENT
+       minimal_models diff ASSIGNMENT ASSIGNMENT [--cnf INPUT]
+       minimal_models extend INPUT ASSIGNMENT
+       minimal_models reduce INPUT MODEL
+       minimal_models verify-cert INPUT CERTIFICATE
+       minimal_models gen VARS CLAUSES [--length K] [--seed N]
+       minimal_models analyze [stats | communities [--reduce] | autarky | defined [--basis VARS]]
+                              [INPUT]
+       minimal_models maxsat [INPUT]
+
+Without INPUT, the input is read from stdin. The global flags --max-var N and --help are accepted
+by all subcommands. solve and enumerate also read options from --config FILE, unless --no-config is
+given. See the README for details.
+";
+
+pub const SUBCOMMANDS: &[&str] = &[
+    "solve",
+    "enumerate",
+    "check",
+    "diff",
+    "extend",
+    "reduce",
+    "verify-cert",
+    "gen",
+    "analyze",
+    "maxsat",
+    "completions",
+];
+
+/// All options, with the help printed by `--help`, for the usage, shell completions and to check
+/// the configuration
+///
+/// This has to contain every option parsed by [`GlobalOptions::parse_arg`],
+/// [`SolveOptions::parse_arg`](crate::solve::SolveOptions::parse_arg) and `main`, which
+/// `tests/options.rs` checks.
+pub const OPTIONS: &[(&str, OptionValue, &str)] = &[
+    (
+        "--max-var",
+        OptionValue::Any("N"),
+        "Reject variables above N",
+    ),
+    ("--help", OptionValue::None, "Print this help"),
+    ("--config", OptionValue::File, "Read options from FILE"),
+    (
+        "--no-config",
+        OptionValue::None,
+        "Don't read the default configuration file",
+    ),
+    (
+        "--order",
+        OptionValue::Choice(&["arbitrary", "occurrence", "core"]),
+        "Order in which candidates are tested",
+    ),
+    (
+        "--shuffle-candidates",
+        OptionValue::None,
+        "Shuffle the candidates before ordering them",
+    ),
+    (
+        "--script",
+        OptionValue::File,
+        "Rank the candidates by the expression in FILE",
+    ),
+    (
+        "--seed",
+        OptionValue::Any("S"),
+        "Seed for --shuffle-candidates, and for gen",
+    ),
+    (
+        "--portfolio",
+        OptionValue::Any("N"),
+        "Run N reductions in parallel, keeping the smallest",
+    ),
+    (
+        "--speculate",
+        OptionValue::Any("K"),
+        "Check K further candidates concurrently",
+    ),
+    (
+        "--preprocess",
+        OptionValue::None,
+        "Simplify the clauses before solving",
+    ),
+    (
+        "--equivalences",
+        OptionValue::None,
+        "Detect and print equivalent literals",
+    ),
+    (
+        "--pure-literals",
+        OptionValue::None,
+        "Drop literals occurring in no clause untested",
+    ),
+    (
+        "--warm-start",
+        OptionValue::None,
+        "Use the previous full model as phase hints",
+    ),
+    (
+        "--mem-limit",
+        OptionValue::Any("MIB"),
+        "Stop once the resident memory exceeds MIB",
+    ),
+    (
+        "--time-limit",
+        OptionValue::Any("SECS"),
+        "Stop after SECS seconds",
+    ),
+    (
+        "--all",
+        OptionValue::None,
+        "Keep finding minimal models after the input ends",
+    ),
+    (
+        "--max-models",
+        OptionValue::Any("N"),
+        "Stop --all after N models",
+    ),
+    (
+        "--coverage",
+        OptionValue::Any("N"),
+        "Print the coverage after every N models",
+    ),
+    (
+        "--coverage-target",
+        OptionValue::Any("PERCENT"),
+        "Stop --all once the coverage reaches PERCENT",
+    ),
+    (
+        "--verify",
+        OptionValue::None,
+        "Check every model against all clauses",
+    ),
+    (
+        "--oracle",
+        OptionValue::None,
+        "Check every reduced model by brute force",
+    ),
+    (
+        "--oracle-max-vars",
+        OptionValue::Any("N"),
+        "Largest formula checked by --oracle",
+    ),
+    (
+        "--certificate",
+        OptionValue::File,
+        "Write a minimality certificate to FILE",
+    ),
+    (
+        "--proof",
+        OptionValue::File,
+        "Write a proof of the positive solver to FILE",
+    ),
+    (
+        "--check-proof",
+        OptionValue::File,
+        "Write a proof of the candidate checks to FILE",
+    ),
+    (
+        "--proof-format",
+        OptionValue::Choice(&["lrat", "frat", "drat"]),
+        "Format of --proof and --check-proof",
+    ),
+    (
+        "--check-proofs",
+        OptionValue::None,
+        "Check the written proofs before exiting",
+    ),
+    (
+        "--checkpoint",
+        OptionValue::File,
+        "Save the blocking clauses to FILE",
+    ),
+    (
+        "--blocking-out",
+        OptionValue::File,
+        "Append every blocking clause to FILE",
+    ),
+    (
+        "--blocked-in",
+        OptionValue::File,
+        "Block the models of FILE first",
+    ),
+    (
+        "--learned-out",
+        OptionValue::File,
+        "Save the learned clauses to FILE",
+    ),
+    (
+        "--learned-in",
+        OptionValue::File,
+        "Load learned clauses from FILE",
+    ),
+    (
+        "--checkpoint-interval",
+        OptionValue::Any("SECS"),
+        "Seconds between checkpoint saves",
+    ),
+    (
+        "--resume",
+        OptionValue::File,
+        "Continue from the checkpoint FILE",
+    ),
+    (
+        "--watch",
+        OptionValue::File,
+        "Run again whenever FILE changes",
+    ),
+    (
+        "--deterministic",
+        OptionValue::None,
+        "Guarantee identical output across runs",
+    ),
+    (
+        "--unsat-core",
+        OptionValue::None,
+        "Print a minimal unsatisfiable subset",
+    ),
+    (
+        "--fingerprint",
+        OptionValue::None,
+        "Print a hash of all reported models",
+    ),
+    (
+        "--importance",
+        OptionValue::None,
+        "Print how often each literal occurred",
+    ),
+    (
+        "--backbone",
+        OptionValue::None,
+        "Print the backbone literals after each model",
+    ),
+    (
+        "--no-full-model",
+        OptionValue::None,
+        "Don't print the full models",
+    ),
+    (
+        "--prefer-false",
+        OptionValue::None,
+        "Try assigning false first when finding full models",
+    ),
+    (
+        "--query-stats",
+        OptionValue::None,
+        "Print statistics after each reduced model",
+    ),
+    ("--trace", OptionValue::None, "Print every candidate check"),
+    (
+        "--timing",
+        OptionValue::None,
+        "Print how the time was spent",
+    ),
+    ("--memory", OptionValue::None, "Print the memory usage"),
+    (
+        "--check-histogram",
+        OptionValue::None,
+        "Print distributions of the candidate checks",
+    ),
+    (
+        "--framed",
+        OptionValue::None,
+        "Mark where the output of each request ends",
+    ),
+    (
+        "--tui",
+        OptionValue::None,
+        "Show a live dashboard on the standard error",
+    ),
+    (
+        "--metrics",
+        OptionValue::Any("ADDR"),
+        "Serve Prometheus metrics on ADDR",
+    ),
+    (
+        "--hint",
+        OptionValue::File,
+        "Use the assignment of FILE as phase hints",
+    ),
+    (
+        "--exit-codes",
+        OptionValue::Choice(&["posix", "satcomp", "fail-on-unsat"]),
+        "Exit code convention",
+    ),
+    (
+        "--backend",
+        OptionValue::Choice(&["cryptominisat", "builtin", "cadical", "minisat", "ipasir"]),
+        "SAT solver of both solvers",
+    ),
+    (
+        "--pos-backend",
+        OptionValue::Choice(&["cryptominisat", "builtin", "cadical", "minisat", "ipasir"]),
+        "SAT solver finding full models",
+    ),
+    (
+        "--neg-backend",
+        OptionValue::Choice(&["cryptominisat", "builtin", "cadical", "minisat", "ipasir"]),
+        "SAT solver checking candidates",
+    ),
+    (
+        "--first-model-backend",
+        OptionValue::Choice(&[
+            "cryptominisat",
+            "builtin",
+            "cadical",
+            "minisat",
+            "ipasir",
+            "kissat",
+        ]),
+        "SAT solver for only the first full model",
+    ),
+    (
+        "--ipasir",
+        OptionValue::File,
+        "IPASIR library of both solvers",
+    ),
+    (
+        "--pos-ipasir",
+        OptionValue::File,
+        "IPASIR library of the positive solver",
+    ),
+    (
+        "--neg-ipasir",
+        OptionValue::File,
+        "IPASIR library of the negative solver",
+    ),
+    (
+        "--verbosity",
+        OptionValue::Any("N"),
+        "Verbosity of cryptominisat",
+    ),
+    (
+        "--solver-threads",
+        OptionValue::Any("N"),
+        "Threads of each cryptominisat instance",
+    ),
+    (
+        "--pos-solver-threads",
+        OptionValue::Any("N"),
+        "Threads of the positive solver",
+    ),
+    (
+        "--neg-solver-threads",
+        OptionValue::Any("N"),
+        "Threads of the negative solver",
+    ),
+    (
+        "--solve-time-limit",
+        OptionValue::Any("SECS"),
+        "Time limit of each call finding a full model",
+    ),
+    (
+        "--check-time-limit",
+        OptionValue::Any("SECS"),
+        "Time limit of each candidate check",
+    ),
+    (
+        "--solve-conflict-limit",
+        OptionValue::Any("N"),
+        "Conflict limit of each call finding a full model",
+    ),
+    (
+        "--check-conflict-limit",
+        OptionValue::Any("N"),
+        "Conflict limit of each candidate check",
+    ),
+    (
+        "--solver-opt",
+        OptionValue::Any("KEY=VAL"),
+        "Native option of both solvers",
+    ),
+    (
+        "--pos-solver-opt",
+        OptionValue::Any("KEY=VAL"),
+        "Native option of the positive solver",
+    ),
+    (
+        "--neg-solver-opt",
+        OptionValue::Any("KEY=VAL"),
+        "Native option of the negative solver",
+    ),
+    (
+        "--length",
+        OptionValue::Any("K"),
+        "gen: Variables per clause, defaults to 3",
+    ),
+    (
+        "--reduce",
+        OptionValue::None,
+        "analyze communities: Also reduce a model",
+    ),
+    (
+        "--basis",
+        OptionValue::Any("VARS"),
+        "analyze defined: Comma separated basis variables",
+    ),
+    (
+        "--cnf",
+        OptionValue::File,
+        "diff: Also check the assignments against FILE",
+    ),
+];
+
+/// Prints the usage followed by all options
+fn print_help() {
+    print!("{}", USAGE);
+    println!();
+    println!("options:");
+    for &(option, value, help) in OPTIONS {
+        let value = match value {
+            OptionValue::None => String::new(),
+            OptionValue::Any(name) => format!(" {}", name),
+            OptionValue::File => " FILE".to_owned(),
+            OptionValue::Choice(choices) => format!(" {}", choices.join("|")),
+        };
+        let option = format!("{}{}", option, value);
+        if option.len() <= 30 {
+            println!("  {:30}  {}", option, help);
+        } else {
+            println!("  {}", option);
+            println!("  {:30}  {}", "", help);
+        }
+    }
+}
+
+/// Prints a completion script for `shell`, one of `bash`, `zsh` or `fish`
+///
+/// The script completes subcommands, options, the values of `--order` and file names. It doesn't
+/// distinguish between the options of different subcommands.
+pub fn completions(shell: Option<&str>) -> anyhow::Result<()> {
+    // Like in the usage, `completions` itself is left out
+    let subcommands = SUBCOMMANDS
+        .iter()
+        .filter(|&&subcommand| subcommand != "completions")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    match shell {
+        Some("bash") => {
+            println!("_minimal_models() {{");
+            println!("    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"");
+            println!("    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
+            println!("    case \"$prev\" in");
+            for &(option, value, _) in OPTIONS {
+                match value {
+                    OptionValue::None => (),
+                    OptionValue::Any(_) => println!("        {}) return ;;", option),
+                    OptionValue::File => println!(
+                        "        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;",
+                        option
+                    ),
+                    OptionValue::Choice(choices) => println!(
+                        "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                        option,
+                        choices.join(" ")
+                    ),
+                }
+            }
+            println!("    esac");
+            let options: Vec<&str> = OPTIONS.iter().map(|&(option, _, _)| option).collect();
+            println!("    local words=\"{}\"", options.join(" "));
+            println!("    if [[ $COMP_CWORD -eq 1 ]]; then");
+            println!("        words=\"{} $words\"", subcommands);
+            println!("    fi");
+            println!("    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))");
+            println!("}}");
+            println!("complete -o default -F _minimal_models minimal_models");
+        }
+        Some("zsh") => {
+            println!("#compdef minimal_models");
+            println!("_arguments \\");
+            println!("    '1:: :({})' \\", subcommands);
+            for &(option, value, _) in OPTIONS {
+                match value {
+                    OptionValue::None => println!("    '*{}' \\", option),
+                    OptionValue::Any(_) => println!("    '*{}:value: ' \\", option),
+                    OptionValue::File => println!("    '*{}:file:_files' \\", option),
+                    OptionValue::Choice(choices) => {
+                        println!("    '*{}:value:({})' \\", option, choices.join(" "))
+                    }
+                }
+            }
+            println!("    '*:file:_files'");
+        }
+        Some("fish") => {
+            println!(
+                "complete -c minimal_models -n __fish_use_subcommand -a '{}'",
+                subcommands
+            );
+            for &(option, value, _) in OPTIONS {
+                let long = &option[2..];
+                match value {
+                    OptionValue::None => println!("complete -c minimal_models -l {}", long),
+                    OptionValue::Any(_) => println!("complete -c minimal_models -l {} -x", long),
+                    OptionValue::File => println!("complete -c minimal_models -l {} -r -F", long),
+                    OptionValue::Choice(choices) => println!(
+                        "complete -c minimal_models -l {} -x -a '{}'",
+                        long,
+                        choices.join(" ")
+                    ),
+                }
+            }
+        }
+        _ => anyhow::bail!("completions requires one of bash, zsh or fish"),
+    }
+    Ok(())
+}
+
+/// Flags accepted by all subcommands
+#[derive(Default)]
+pub struct GlobalOptions {
+    pub max_var: Option<usize>,
+}
+
+impl GlobalOptions {
+    /// Handles `arg` if it is a global flag, returning whether it was one
+    pub fn parse_arg(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> anyhow::Result<bool> {
+        match arg {
+            "--max-var" => {
+                let max_var = arg_value(args, arg)?.parse()?;
+                // Solver variable indices are 32-bit and we need some for auxiliary variables
+                if max_var >= (u32::MAX >> 1) as usize {
+                    anyhow::bail!("--max-var must be below {}", u32::MAX >> 1);
+                }
+                self.max_var = Some(max_var);
+            }
+            "--help" | "-h" => {
+                print_help();
+                std::process::exit(0);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Default locations of the configuration file, in the order they are searched
+fn default_config_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("minimal_models.toml")];
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(config_dir) = config_dir {
+        paths.push(config_dir.join("minimal_models/config.toml"));
+    }
+    paths
+}
+
+/// Returns the arguments from the configuration file
+///
+/// Removes `--config FILE` and `--no-config` from `args`. Without `--config` the first existing
+/// file of [`default_config_paths`] is used.
+pub fn config_args(args: &mut Vec<String>) -> anyhow::Result<Vec<String>> {
+    let mut path = None;
+    let mut search = true;
+    let mut index = 0;
+    while index < args.len() {
+        match &args[index][..] {
+            "--config" => {
+                args.remove(index);
+                if index == args.len() {
+                    anyhow::bail!("--config requires an argument");
+                }
+                path = Some(PathBuf::from(args.remove(index)));
+            }
+            "--no-config" => {
+                args.remove(index);
+                search = false;
+            }
+            _ => index += 1,
+        }
+    }
+    if path.is_none() && search {
+        path = default_config_paths()
+            .into_iter()
+            .find(|path| path.exists());
+    }
+    match path {
+        Some(path) => {
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("reading config {}", path.display()))?;
+            config::parse(&text, &path.display().to_string(), OPTIONS)
+        }
+        None => Ok(vec![]),
+    }
+}
@@ -0,0 +1,36 @@
+//! The `gen` subcommand
+use std::io::{self, Write};
+
+use minimal_models::Rng;
+
+/// Generates a random formula with `clauses` clauses of `length` distinct variables each
+pub fn generate(vars: usize, clauses: usize, length: usize, seed: u64) -> anyhow::Result<()> {
+    if length > vars {
+        anyhow::bail!(
+            "clauses of length {} need at least as many variables",
+            length
+        );
+    }
+    let mut rng = Rng(seed);
+    let mut vars_used = vec![];
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    for _ in 0..clauses {
+        vars_used.clear();
+        while vars_used.len() < length {
+            let var = 1 + rng.below(vars) as isize;
+            if !vars_used.contains(&var) {
+                vars_used.push(var);
+            }
+        }
+        for &var in &vars_used {
+            let lit = if rng.below(2) == 0 { var } else { -var };
+            write!(out, "{} ", lit)?;
+        }
+        writeln!(out, "0")?;
+    }
+    // Request solving when used as input for `solve`
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,179 @@
+mod analyze;
+mod check;
+mod cli;
+mod gen;
+mod maxsat;
+mod solve;
+
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use minimal_models::{config, input, remote};
+
+use crate::{
+    analyze::Analysis,
+    check::{check_assignment, diff_assignments, extend_assignment, reduce_model, verify_cert},
+    cli::{completions, config_args, GlobalOptions, OPTIONS, SUBCOMMANDS, USAGE},
+    gen::generate,
+    solve::SolveOptions,
+};
+
+/// Returns the value following the command line flag `arg`
+fn arg_value(args: &mut impl Iterator<Item = String>, arg: &str) -> anyhow::Result<String> {
+    args.next()
+        .ok_or_else(|| anyhow::anyhow!("{} requires an argument", arg))
+}
+
+/// Default for `--max-var`, bounding the memory used to map user variables
+const DEFAULT_MAX_VAR: usize = 10_000_000;
+
+/// Collects the positional argument `arg` into `positional`, which takes at most `max` arguments
+fn positional(arg: String, positional: &mut Vec<String>, max: usize) -> anyhow::Result<()> {
+    if arg.starts_with('-') || positional.len() == max {
+        anyhow::bail!("unknown argument {:?}\n\n{}", arg, USAGE);
+    }
+    positional.push(arg);
+    Ok(())
+}
+
+/// Opens the input file or URL, or stdin if there is none
+fn open_input(input: Option<&str>) -> anyhow::Result<Box<dyn BufRead>> {
+    Ok(match input {
+        Some(url) if remote::is_url(url) => Box::new(remote::open_url(url)?),
+        Some(path) => Box::new(io::BufReader::new(
+            fs::File::open(path).with_context(|| format!("opening {}", path))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1).peekable();
+    let command = args
+        .next_if(|arg| SUBCOMMANDS.contains(&&arg[..]))
+        .unwrap_or_else(|| "solve".to_owned());
+    let mut args: Vec<String> = args.collect();
+    if command == "solve" || command == "enumerate" {
+        // Later options take precedence, so the command line overrides the environment, which
+        // overrides the configuration file
+        let mut all_args = config_args(&mut args)?;
+        all_args.extend(config::env_args(
+            std::env::vars_os().filter_map(|(name, value)| {
+                Some((name.into_string().ok()?, value.into_string().ok()?))
+            }),
+            OPTIONS,
+        )?);
+        all_args.append(&mut args);
+        args = all_args;
+    }
+    let mut args = args.into_iter();
+
+    let mut global = GlobalOptions::default();
+    let mut solve_options = SolveOptions::default();
+    let (mut length, mut seed) = (3, 0);
+    let mut reduce = false;
+    let mut basis = None;
+    let mut cnf = None;
+    let mut positional_args = vec![];
+    let max_positional = match &command[..] {
+        "solve" | "enumerate" | "maxsat" | "completions" => 1,
+        _ => 2,
+    };
+    while let Some(arg) = args.next() {
+        if global.parse_arg(&arg, &mut args)? {
+            continue;
+        }
+        match &command[..] {
+            "solve" | "enumerate" if solve_options.parse_arg(&arg, &mut args)? => continue,
+            "gen" if arg == "--length" => length = arg_value(&mut args, &arg)?.parse()?,
+            "gen" if arg == "--seed" => seed = arg_value(&mut args, &arg)?.parse()?,
+            "analyze" if arg == "--reduce" => reduce = true,
+            "analyze" if arg == "--basis" => basis = Some(arg_value(&mut args, &arg)?),
+            "diff" if arg == "--cnf" => cnf = Some(PathBuf::from(arg_value(&mut args, &arg)?)),
+            _ => positional(arg, &mut positional_args, max_positional)?,
+        }
+    }
+    let input = positional_args.first().map(|arg| &arg[..]);
+
+    match &command[..] {
+        "solve" | "enumerate" => {
+            if command == "enumerate" {
+                solve_options.all = true;
+                solve_options.incremental = false;
+            }
+            let code = solve::solve(
+                solve_options,
+                input,
+                global.max_var.unwrap_or(DEFAULT_MAX_VAR),
+            )?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+            Ok(())
+        }
+        "analyze" => {
+            let (analysis, input) = match &positional_args[..] {
+                [mode, rest @ ..] if mode == "stats" => (Analysis::Stats, rest.first()),
+                [mode, rest @ ..] if mode == "communities" => {
+                    (Analysis::Communities { reduce }, rest.first())
+                }
+                [mode, rest @ ..] if mode == "autarky" => (Analysis::Autarky, rest.first()),
+                [mode, rest @ ..] if mode == "defined" => {
+                    let max_var = global.max_var.unwrap_or(DEFAULT_MAX_VAR);
+                    let basis = basis
+                        .take()
+                        .map(|vars| input::parse_clause(&vars.replace(',', " "), max_var))
+                        .transpose()
+                        .context("--basis")?;
+                    (Analysis::Defined { basis }, rest.first())
+                }
+                [_, extra] => anyhow::bail!("unknown argument {:?}\n\n{}", extra, USAGE),
+                _ => (Analysis::Summary, positional_args.first()),
+            };
+            if reduce && !matches!(analysis, Analysis::Communities { .. }) {
+                anyhow::bail!("--reduce requires analyze communities\n\n{}", USAGE);
+            }
+            if basis.is_some() {
+                anyhow::bail!("--basis requires analyze defined\n\n{}", USAGE);
+            }
+            // Reducing communities, finding autarkies and checking definability solve, which
+            // allocates memory for every variable
+            let max_var = match analysis {
+                Analysis::Communities { reduce: true }
+                | Analysis::Autarky
+                | Analysis::Defined { .. } => global.max_var.unwrap_or(DEFAULT_MAX_VAR),
+                _ => global.max_var.unwrap_or(usize::MAX),
+            };
+            let input = input.map(|arg| &arg[..]);
+            analyze::analyze(open_input(input)?, max_var, analysis)
+        }
+        "maxsat" => maxsat::maxsat(
+            open_input(input)?,
+            global.max_var.unwrap_or(DEFAULT_MAX_VAR),
+        ),
+        "completions" => completions(input),
+        _ => {
+            if positional_args.len() < 2 {
+                anyhow::bail!("{} requires two arguments\n\n{}", command, USAGE);
+            }
+            let (first, second) = (&positional_args[0], &positional_args[1]);
+            let max_var = global.max_var.unwrap_or(usize::MAX);
+            // Like solving, extending and reducing allocate memory for every variable
+            let solver_max_var = global.max_var.unwrap_or(DEFAULT_MAX_VAR);
+            match &command[..] {
+                "check" => check_assignment(first.as_ref(), second.as_ref(), max_var),
+                "diff" => {
+                    diff_assignments(first.as_ref(), second.as_ref(), cnf.as_deref(), max_var)
+                }
+                "extend" => extend_assignment(first.as_ref(), second.as_ref(), solver_max_var),
+                "reduce" => reduce_model(first.as_ref(), second.as_ref(), solver_max_var),
+                "verify-cert" => verify_cert(first.as_ref(), second.as_ref(), max_var),
+                _ => generate(first.parse()?, second.parse()?, length, seed),
+            }
+        }
+    }
+}
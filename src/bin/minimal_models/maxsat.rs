@@ -0,0 +1,47 @@
+//! The `maxsat` subcommand
+use std::{collections::HashSet, io::BufRead};
+
+use minimal_models::{
+    maxsat::{self, MaxSat},
+    Finder, Optimizer, SolverOptions,
+};
+
+/// Finds an optimum of the WCNF input and reduces it to a minimal model of the hard clauses
+pub fn maxsat(reader: impl BufRead, max_var: usize) -> anyhow::Result<()> {
+    let wcnf = maxsat::parse_wcnf(reader, max_var)?;
+    let (model, cost) = match maxsat::solve(&wcnf, &SolverOptions::default(), Optimizer::default())?
+    {
+        MaxSat::Optimum { model, cost } => (model, cost),
+        MaxSat::Unsat => {
+            println!("unsat");
+            return Ok(());
+        }
+        MaxSat::Unknown => {
+            println!("unknown");
+            return Ok(());
+        }
+    };
+    println!("cost: {}", cost);
+    print!("full model: ");
+    for lit in &model {
+        print!("{} ", lit);
+    }
+    println!();
+
+    let mut finder = Finder::builder().build()?;
+    for clause in &wcnf.hard {
+        finder.add_clause(clause);
+    }
+    // Variables only occurring in soft clauses don't affect the hard clauses
+    let hard_vars: HashSet<isize> = wcnf.hard.iter().flatten().map(|lit| lit.abs()).collect();
+    let model: Vec<isize> = model
+        .into_iter()
+        .filter(|lit| hard_vars.contains(&lit.abs()))
+        .collect();
+    print!("reduced model: ");
+    for lit in finder.reduce(&model).model {
+        print!("{} ", lit);
+    }
+    println!();
+    Ok(())
+}
@@ -0,0 +1,1930 @@
+//! The `solve` and `enumerate` subcommands, which share their options and the handling of the
+//! input
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Context;
+use minimal_models::{
+    certificate::CertificateWriter,
+    checkpoint::Checkpoint,
+    coverage::{self, Coverage},
+    dashboard::Dashboard,
+    fingerprint::Fingerprint,
+    importance::Importance,
+    input,
+    learned::Learned,
+    memory,
+    metrics::Metrics,
+    oracle::Oracle,
+    proof_check,
+    script::Script,
+    unsat_core::group_unsat_core,
+    verify, Backend, CandidateOrder, Check, CheckTrace, Checks, Consequence, Finder, Polarity,
+    Preference, ProofFormat, Solved, SolverOptions, Stats,
+};
+
+use crate::{
+    arg_value,
+    check::{read_assignment, read_models},
+    open_input,
+};
+
+/// Parses a `key=val` native solver option
+fn native_option(option: &str) -> anyhow::Result<(String, String)> {
+    let (key, value) = option
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("solver option {:?} is not of the form key=val", option))?;
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+/// Set by the SIGINT handler
+static INTERRUPT: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_signal: libc::c_int) {
+    if let Some(interrupt) = INTERRUPT.get() {
+        interrupt.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Installs a SIGINT handler setting [`INTERRUPT`]
+///
+/// The handler resets itself, so pressing Ctrl-C a second time terminates immediately.
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigint as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESETHAND;
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+fn install_interrupt_handler() {}
+
+/// Periodically saves the blocking clauses of the reported models
+struct Checkpointer {
+    path: PathBuf,
+    interval: Duration,
+    last_save: Instant,
+    checkpoint: Checkpoint,
+}
+
+impl Checkpointer {
+    /// Records the blocking clause of a reported model, saving the checkpoint if it is due
+    fn record(&mut self, blocking: &[isize]) -> anyhow::Result<()> {
+        self.checkpoint.blocked.push(blocking.to_vec());
+        if self.last_save.elapsed() >= self.interval {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&mut self) -> anyhow::Result<()> {
+        self.checkpoint.save(&self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+}
+
+/// What happened when handling a request to find a model
+enum Step {
+    /// A minimal model was reported and blocked
+    Blocked,
+    Unknown,
+    /// No further models will be reported, as the formula became unsatisfiable
+    Unsat,
+    /// No further models will be reported, as we were interrupted
+    Interrupted,
+    /// No further models agree with the assumptions
+    UnsatAssuming,
+}
+
+/// Answer for the formula as given by the input, for `--exit-codes`
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Outcome {
+    /// A minimal model was reported
+    Sat,
+    /// The formula has no models, not counting blocking clauses
+    Unsat,
+    Unknown,
+}
+
+/// Exit code conventions selected by `--exit-codes`
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum ExitCodes {
+    /// Exit with 0 unless there was an error
+    Posix,
+    /// Exit with 10 for [`Outcome::Sat`], 20 for [`Outcome::Unsat`] and 0 otherwise
+    SatCompetition,
+    /// Exit with 1 for [`Outcome::Unsat`] and 0 otherwise
+    FailOnUnsat,
+}
+
+impl std::str::FromStr for ExitCodes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s {
+            "posix" => ExitCodes::Posix,
+            "satcomp" => ExitCodes::SatCompetition,
+            "fail-on-unsat" => ExitCodes::FailOnUnsat,
+            _ => anyhow::bail!("unknown exit code convention {:?}", s),
+        })
+    }
+}
+
+impl ExitCodes {
+    fn code(self, outcome: Outcome) -> i32 {
+        match (self, outcome) {
+            (ExitCodes::SatCompetition, Outcome::Sat) => 10,
+            (ExitCodes::SatCompetition, Outcome::Unsat) => 20,
+            (ExitCodes::FailOnUnsat, Outcome::Unsat) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// A finder together with the state kept by the command line tool
+struct Session {
+    finder: Finder,
+    print_full_model: bool,
+    equivalences: bool,
+    checkpointer: Option<Checkpointer>,
+    /// For `--blocking-out`, opened for appending
+    blocking_out: Option<fs::File>,
+    certificate: Option<CertificateWriter>,
+    verify: bool,
+    /// Maximal number of variables for `--oracle`
+    oracle: Option<usize>,
+    /// All clauses added so far, retained for `--verify`, `--oracle`, `--certificate` and
+    /// `--unsat-core`
+    clauses: Option<Vec<Vec<isize>>>,
+    /// For `--unsat-core`, the input line number of each clause in `clauses`, or `None` for
+    /// blocking clauses
+    lines: Option<Vec<Option<usize>>>,
+    /// For `--unsat-core`, the index in `clause_group_names` of the group of each clause in
+    /// `clauses`, or `None` for clauses outside of groups
+    clause_groups: Option<Vec<Option<usize>>>,
+    /// Names of the groups declared by `group` lines
+    clause_group_names: Vec<String>,
+    /// The group of the following input clauses
+    current_group: Option<usize>,
+    /// Number of clauses read from the input so far
+    input_clauses: usize,
+    /// Of all reported reduced models, for `--fingerprint`
+    fingerprint: Option<Fingerprint>,
+    /// Of all reported reduced models, for `--importance`
+    importance: Option<Importance>,
+    /// For `--coverage` and `--coverage-target`
+    coverage: Option<Coverage>,
+    /// For `--backbone`, a finder with only the input clauses, as the blocking clauses of earlier
+    /// models would make more literals backbone literals
+    backbone: Option<Finder>,
+    /// The first definite answer, as later ones take the blocking clauses into account
+    outcome: Option<Outcome>,
+    /// Number of reported minimal models
+    models: usize,
+    /// Sets of literals assumed for every full model, by name
+    assumptions: BTreeMap<String, Vec<isize>>,
+    /// Exactly-one groups, rendered as `name=value` for each reduced model
+    groups: Vec<Group>,
+    /// Variables declared by `#show`, onto which reduced models are projected before blocking
+    show: Option<HashSet<isize>>,
+    /// Variables declared by `minimize` lines, which switch to reporting circumscribed models
+    minimized: Vec<isize>,
+    /// Variables declared by `fix` lines, kept while circumscribing
+    fixed: Vec<isize>,
+    /// Whether to print a statistics line for each reduced model
+    query_stats: bool,
+    /// Whether statistics lines include the time, which isn't deterministic
+    stats_time: bool,
+    /// Number of requests for a model so far
+    queries: usize,
+    dashboard: Option<Dashboard>,
+    metrics: Option<Metrics>,
+    /// Set by `--hint` until the first full model is found
+    hint: Option<Vec<isize>>,
+}
+
+/// Variables of which exactly one is true, e.g. the possible values of an option
+struct Group {
+    name: String,
+    /// Each value with the literal that is true when it is selected
+    values: Vec<(String, isize)>,
+}
+
+impl Session {
+    fn add_clause(&mut self, clause: &[isize]) {
+        self.add_clause_from(clause, None);
+    }
+
+    fn add_clause_from(&mut self, clause: &[isize], line: Option<usize>) {
+        self.finder.add_clause(clause);
+        self.record_clause(clause, line);
+    }
+
+    /// Adds the blocking clause of a reported model, returning that clause
+    fn block(&mut self, model: &[isize]) -> anyhow::Result<Vec<isize>> {
+        self.finder.block(model);
+        let blocking: Vec<isize> = model.iter().map(|&lit| -lit).collect();
+        self.record_clause(&blocking, None);
+        if let Some(file) = &mut self.blocking_out {
+            // A single write per clause, so a concurrent reader never sees a partial line
+            let mut line = String::new();
+            for lit in &blocking {
+                line.push_str(&format!("{} ", lit));
+            }
+            line.push_str("0\n");
+            file.write_all(line.as_bytes())
+                .context("writing the blocking clause")?;
+        }
+        Ok(blocking)
+    }
+
+    /// Retains a clause added to the finder, if needed
+    fn record_clause(&mut self, clause: &[isize], line: Option<usize>) {
+        if let Some(clauses) = &mut self.clauses {
+            clauses.push(clause.to_vec());
+        }
+        if let Some(lines) = &mut self.lines {
+            lines.push(line);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            // Clauses without a line are blocking clauses, possibly from a checkpoint
+            match line {
+                Some(_) => coverage.add_clause(clause),
+                None => coverage.add_blocking(clause),
+            }
+        }
+        if let (Some(backbone), Some(_)) = (&mut self.backbone, line) {
+            backbone.add_clause(clause);
+        }
+        if let Some(clause_groups) = &mut self.clause_groups {
+            // Only input clauses belong to groups, blocking clauses don't
+            clause_groups.push(line.and(self.current_group));
+        }
+    }
+
+    /// Returns an oracle for the current formula, if enabled and the formula is small enough
+    fn oracle(&self) -> Option<Oracle> {
+        Oracle::new(self.clauses.as_ref()?, self.oracle?)
+    }
+
+    /// Starts a new problem, keeping the configuration and, with `keep_vars`, the variables
+    fn reset(&mut self, keep_vars: bool) {
+        if let Some(metrics) = &self.metrics {
+            metrics.next_problem();
+        }
+        self.finder.reset(keep_vars);
+        if let Some(clauses) = &mut self.clauses {
+            clauses.clear();
+        }
+        if let Some(lines) = &mut self.lines {
+            lines.clear();
+        }
+        if let Some(clause_groups) = &mut self.clause_groups {
+            clause_groups.clear();
+        }
+        self.clause_group_names.clear();
+        self.current_group = None;
+        self.input_clauses = 0;
+        if let Some(fingerprint) = &mut self.fingerprint {
+            *fingerprint = Fingerprint::default();
+        }
+        if let Some(importance) = &mut self.importance {
+            *importance = Importance::default();
+        }
+        if let Some(coverage) = &mut self.coverage {
+            *coverage = Coverage::default();
+        }
+        if let Some(backbone) = &mut self.backbone {
+            backbone.reset(keep_vars);
+        }
+        self.outcome = None;
+        self.models = 0;
+        self.assumptions.clear();
+        self.groups.clear();
+        self.show = None;
+        self.minimized.clear();
+        self.fixed.clear();
+        self.queries = 0;
+        self.hint = None;
+        self.log(|| "next problem".to_owned());
+        self.update_dashboard();
+    }
+
+    /// Sets the union of all assumption sets as the finder's assumptions
+    fn update_assumptions(&mut self) {
+        let lits: Vec<isize> = self.assumptions.values().flatten().cloned().collect();
+        self.finder.set_assumptions(&lits);
+    }
+
+    fn add_input_clause(&mut self, clause: &[isize], line: usize) {
+        self.add_clause_from(clause, Some(line));
+        self.input_clauses += 1;
+    }
+
+    /// Adds an exactly-one group, encoded by input clauses for the given line
+    fn add_group(&mut self, group: Group, line: usize) -> anyhow::Result<()> {
+        if self.groups.iter().any(|other| other.name == group.name) {
+            anyhow::bail!("line {}: group {} is already defined", line, group.name);
+        }
+        let lits: Vec<isize> = group.values.iter().map(|&(_, lit)| lit).collect();
+        self.add_input_clause(&lits, line);
+        for (index, &first) in lits.iter().enumerate() {
+            for &second in &lits[index + 1..] {
+                self.add_input_clause(&[-first, -second], line);
+            }
+        }
+        self.groups.push(group);
+        Ok(())
+    }
+
+    /// Puts the following input clauses into the group `name`, which may already have clauses
+    fn start_clause_group(&mut self, name: &str) {
+        let index = match self
+            .clause_group_names
+            .iter()
+            .position(|other| other == name)
+        {
+            Some(index) => index,
+            None => {
+                self.clause_group_names.push(name.to_owned());
+                self.clause_group_names.len() - 1
+            }
+        };
+        self.current_group = Some(index);
+    }
+
+    /// Adds variables to the projection of the following models
+    fn add_show(&mut self, lits: &[isize], line: usize) -> anyhow::Result<()> {
+        if self.certificate.is_some() {
+            // The certificate's witnesses are for the blocking clauses of whole reduced models
+            anyhow::bail!("line {}: #show doesn't support --certificate", line);
+        }
+        self.show
+            .get_or_insert_with(HashSet::new)
+            .extend(lits.iter().map(|lit| lit.abs()));
+        Ok(())
+    }
+
+    /// Adds variables to the minimized or, with `fix`, the fixed variables of circumscription
+    fn add_circumscription(
+        &mut self,
+        lits: &[isize],
+        fix: bool,
+        line: usize,
+    ) -> anyhow::Result<()> {
+        if self.certificate.is_some() {
+            // The certificate's witnesses are for the blocking clauses of reduced models
+            anyhow::bail!(
+                "line {}: circumscription doesn't support --certificate",
+                line
+            );
+        }
+        let vars = if fix {
+            &mut self.fixed
+        } else {
+            &mut self.minimized
+        };
+        vars.extend(lits.iter().map(|lit| lit.abs()));
+        Ok(())
+    }
+
+    /// Reports and blocks the circumscription of the full model `model` instead of reducing it
+    fn circumscribe(&mut self, model: &[isize]) -> anyhow::Result<Step> {
+        let model = match self
+            .finder
+            .circumscribe(model, &self.minimized, &self.fixed)
+        {
+            Solved::Model(model) => model,
+            Solved::Unsat => unreachable!("circumscription starts from a model"),
+            Solved::Unknown => {
+                println!("unknown");
+                self.log(|| format!("query {}: unknown", self.queries));
+                return Ok(Step::Unknown);
+            }
+        };
+        // Blocking the fixed literals and the true minimized variables excludes exactly the
+        // models that aren't minimal due to this one
+        let circumscribed: Vec<isize> = model
+            .into_iter()
+            .filter(|&lit| {
+                self.fixed.contains(&lit.abs()) || (lit > 0 && self.minimized.contains(&lit))
+            })
+            .collect();
+        print!("circumscribed model: ");
+        for lit in &circumscribed {
+            print!("{} ", lit);
+        }
+        println!();
+        println!("blocking circumscribed model");
+
+        if let Some(fingerprint) = &mut self.fingerprint {
+            fingerprint.add(&circumscribed);
+        }
+        if let Some(importance) = &mut self.importance {
+            importance.add(&circumscribed);
+        }
+        self.outcome.get_or_insert(Outcome::Sat);
+        self.models += 1;
+        self.log(|| format!("query {}: circumscribed model", self.queries));
+        let blocking = self.block(&circumscribed)?;
+        if let Some(checkpointer) = &mut self.checkpointer {
+            checkpointer.record(&blocking)?;
+        }
+        Ok(Step::Blocked)
+    }
+
+    /// For `--backbone`, splits a reduced model into the literals true in all models of the input
+    /// clauses and the essential literals that were only chosen for this model
+    fn print_backbone(&mut self, model: &[isize]) {
+        let finder = match &mut self.backbone {
+            Some(finder) => finder,
+            None => return,
+        };
+        let backbone = match finder.backbone(model) {
+            Some(backbone) => backbone,
+            None => {
+                println!("backbone: unknown");
+                return;
+            }
+        };
+        print!("backbone: ");
+        for lit in &backbone {
+            print!("{} ", lit);
+        }
+        println!();
+        print!("essential: ");
+        for lit in model.iter().filter(|lit| !backbone.contains(lit)) {
+            print!("{} ", lit);
+        }
+        println!();
+    }
+
+    /// Prints the selected value of each group, or `?` if the model doesn't select one
+    fn print_configuration(&self, model: &[isize]) {
+        if self.groups.is_empty() {
+            return;
+        }
+        print!("configuration: ");
+        for group in &self.groups {
+            let value = group
+                .values
+                .iter()
+                .find(|(_, lit)| model.contains(lit))
+                .map_or("?", |(value, _)| value);
+            print!("{}={} ", group.name, value);
+        }
+        println!();
+    }
+
+    /// Prints the coverage of the models after every `interval` reported models, returning whether
+    /// it reached `target` percent
+    ///
+    /// Without an interval, the coverage is checked after every model, but not printed.
+    fn coverage_reached(
+        &mut self,
+        models: usize,
+        interval: Option<usize>,
+        target: Option<f64>,
+    ) -> bool {
+        let coverage = match &mut self.coverage {
+            Some(coverage) => coverage,
+            None => return false,
+        };
+        if !models.is_multiple_of(interval.unwrap_or(1)) {
+            return false;
+        }
+        let fraction = coverage.fraction(coverage::DEFAULT_BUDGET);
+        if interval.is_some() {
+            match fraction {
+                Some(fraction) => println!("coverage: {:.1}%", 100.0 * fraction),
+                None => println!("coverage: unknown"),
+            }
+        }
+        match (fraction, target) {
+            (Some(fraction), Some(target)) if 100.0 * fraction >= target => {
+                println!("coverage target reached");
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Answers whether `lit` is in some minimal model or, with `cautious`, in all of them
+    fn print_consequence(&mut self, lit: isize, cautious: bool) {
+        let (query, consequence) = if cautious {
+            ("cautious", self.finder.cautious(lit))
+        } else {
+            ("brave", self.finder.brave(lit))
+        };
+        let (answer, witness) = match consequence {
+            Consequence::Holds { witness } => ("yes", witness),
+            Consequence::Fails { witness } => ("no", witness),
+            Consequence::Unsat => ("unsat", None),
+            Consequence::Unknown => ("unknown", None),
+        };
+        println!("{} {}: {}", query, lit, answer);
+        if let Some(witness) = witness {
+            print!("witness: ");
+            for lit in &witness.model {
+                print!("{} ", lit);
+            }
+            println!();
+        }
+    }
+
+    /// Prints the input line numbers of a minimal unsatisfiable subset of the clauses
+    ///
+    /// The clauses of a group are kept or removed together, and the names of the groups in the
+    /// subset are printed instead of their lines.
+    fn print_unsat_core(&self) {
+        let (clauses, lines, clause_groups) =
+            match (&self.clauses, &self.lines, &self.clause_groups) {
+                (Some(clauses), Some(lines), Some(clause_groups)) => {
+                    (clauses, lines, clause_groups)
+                }
+                _ => return,
+            };
+        // Each clause outside of groups is a group of its own, numbered after the named ones
+        let named = self.clause_group_names.len();
+        let groups: Vec<usize> = clause_groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| group.unwrap_or(named + index))
+            .collect();
+        let core = match group_unsat_core(clauses, &groups) {
+            Some(core) => core,
+            None => return,
+        };
+        print!("unsat core: ");
+        let mut blocking = 0;
+        for &group in &core {
+            if group < named {
+                continue;
+            }
+            match lines[group - named] {
+                Some(line) => print!("{} ", line),
+                None => blocking += 1,
+            }
+        }
+        println!();
+        if core.first().is_some_and(|&group| group < named) {
+            print!("unsat core groups: ");
+            for &group in core.iter().take_while(|&&group| group < named) {
+                print!("{} ", self.clause_group_names[group]);
+            }
+            println!();
+        }
+        if blocking > 0 {
+            println!("unsat core blocking clauses: {}", blocking);
+        }
+    }
+
+    /// Prints the statistics accumulated since the start of the problem
+    fn print_stats(&self) {
+        let stats = self.finder.stats();
+        println!("queries: {}", self.queries);
+        println!("models: {}", self.models);
+        println!("blocking clauses: {}", stats.blocked);
+        println!("full model searches: {}", stats.solves);
+        println!("reductions: {}", stats.reductions);
+        println!(
+            "candidate checks: {} (sat {}, unsat {}, unknown {})",
+            stats.checks.total(),
+            stats.checks.sat,
+            stats.checks.unsat,
+            stats.checks.unknown
+        );
+        if self.stats_time {
+            println!(
+                "solve time: {:.3}ms",
+                stats.solve_time.as_secs_f64() * 1000.0
+            );
+            println!(
+                "reduce time: {:.3}ms",
+                stats.reduce_time.as_secs_f64() * 1000.0
+            );
+        }
+    }
+
+    /// Adds an event to the log of `--tui`
+    fn log(&self, line: impl FnOnce() -> String) {
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.log(line());
+        }
+    }
+
+    /// Updates the statistics shown by `--tui` and served by `--metrics`
+    fn update_dashboard(&self) {
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.update(self.queries, self.models, self.finder.stats());
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.update(self.queries, self.models, self.finder.stats());
+        }
+    }
+
+    /// Finds, reports and blocks the next minimal model
+    fn step(&mut self) -> anyhow::Result<Step> {
+        self.queries += 1;
+        let step = self.find_step();
+        self.update_dashboard();
+        step
+    }
+
+    fn find_step(&mut self) -> anyhow::Result<Step> {
+        let start = Instant::now();
+        let finder = &mut self.finder;
+        // First we find a full model, unless the hint is one
+        let hinted = self.hint.take().and_then(|hint| finder.use_hint(&hint));
+        if hinted.is_some() {
+            println!("using the hint as full model");
+        }
+        match hinted.map_or_else(|| finder.solve(), Solved::Model) {
+            Solved::Model(model) => {
+                if let (true, Some(clauses)) = (self.verify, &self.clauses) {
+                    verify::check_full_model(clauses, &model)?;
+                }
+
+                if self.print_full_model {
+                    print!("full model: ");
+                    for lit in &model {
+                        print!("{} ", lit);
+                    }
+                    println!();
+                }
+
+                if self.equivalences {
+                    for class in finder.equivalence_classes() {
+                        print!("equivalent: ");
+                        for lit in &class {
+                            print!("{} ", lit);
+                        }
+                        println!();
+                    }
+                }
+
+                if !self.minimized.is_empty() {
+                    return self.circumscribe(&model);
+                }
+
+                let mut checks = Checks::default();
+                let reduced = if finder.clause_count() > 0 {
+                    let reduced = finder.reduce(&model);
+                    checks = reduced.checks;
+
+                    print!("reduced model: ");
+                    for lit in &reduced.model {
+                        print!("{} ", lit);
+                    }
+                    println!();
+                    self.print_backbone(&reduced.model);
+                    self.print_configuration(&reduced.model);
+
+                    if reduced.interrupted {
+                        print!("interrupted, unverified: ");
+                        for lit in &reduced.unverified {
+                            print!("{} ", lit);
+                        }
+                        println!();
+                        self.log(|| format!("query {}: interrupted", self.queries));
+                        return Ok(Step::Interrupted);
+                    } else if !reduced.is_minimal() {
+                        print!("possibly non-minimal, unverified: ");
+                        for lit in &reduced.unverified {
+                            print!("{} ", lit);
+                        }
+                        println!();
+                    }
+
+                    if let (true, Some(clauses)) = (self.verify, &self.clauses) {
+                        verify::check_entailment(clauses, &reduced.model)?;
+                        if reduced.is_minimal() {
+                            verify::check_minimality(clauses, &reduced.model)?;
+                        }
+                    }
+                    if let Some(oracle) = self.oracle() {
+                        let agrees = if reduced.is_minimal() {
+                            oracle.is_minimal(&reduced.model)
+                        } else {
+                            oracle.entails(&reduced.model)
+                        };
+                        if !agrees {
+                            anyhow::bail!("oracle disagrees with the reduced model");
+                        }
+                    }
+
+                    match &self.show {
+                        Some(show) => {
+                            let projected: Vec<isize> = reduced
+                                .model
+                                .into_iter()
+                                .filter(|lit| show.contains(&lit.abs()))
+                                .collect();
+                            print!("projected model: ");
+                            for lit in &projected {
+                                print!("{} ", lit);
+                            }
+                            println!();
+                            println!("blocking projected model");
+                            projected
+                        }
+                        None => {
+                            println!("blocking reduced model");
+                            reduced.model
+                        }
+                    }
+                } else {
+                    println!("no clauses");
+                    // The empty assignment is the only minimal model, blocking it leaves no models
+                    vec![]
+                };
+                if self.query_stats {
+                    print!(
+                        "stats: query {}, checks {} (sat {}, unsat {}, unknown {})",
+                        self.queries,
+                        checks.total(),
+                        checks.sat,
+                        checks.unsat,
+                        checks.unknown
+                    );
+                    if self.stats_time {
+                        print!(", time {:.3}ms", start.elapsed().as_secs_f64() * 1000.0);
+                    }
+                    println!();
+                }
+                if let (Some(certificate), Some(clauses)) = (&mut self.certificate, &self.clauses) {
+                    certificate.model(self.input_clauses, clauses, &reduced)?;
+                }
+                if let Some(fingerprint) = &mut self.fingerprint {
+                    fingerprint.add(&reduced);
+                }
+                if let Some(importance) = &mut self.importance {
+                    importance.add(&reduced);
+                }
+                self.outcome.get_or_insert(Outcome::Sat);
+                self.models += 1;
+                self.log(|| {
+                    format!(
+                        "query {}: minimal model of {} literals, {} checks",
+                        self.queries,
+                        reduced.len(),
+                        checks.total()
+                    )
+                });
+                let blocking = self.block(&reduced)?;
+                if let Some(checkpointer) = &mut self.checkpointer {
+                    checkpointer.record(&blocking)?;
+                }
+                Ok(Step::Blocked)
+            }
+            Solved::Unsat if finder.has_assumptions() => {
+                println!("unsat under assumptions");
+                self.log(|| format!("query {}: unsat under assumptions", self.queries));
+                Ok(Step::UnsatAssuming)
+            }
+            Solved::Unsat => {
+                if let Some(oracle) = self.oracle() {
+                    if oracle.is_satisfiable() {
+                        anyhow::bail!("oracle disagrees with unsat");
+                    }
+                }
+                println!("unsat");
+                self.log(|| format!("query {}: unsat", self.queries));
+                self.outcome.get_or_insert(Outcome::Unsat);
+                self.print_unsat_core();
+                Ok(Step::Unsat)
+            }
+            Solved::Unknown => {
+                println!("unknown");
+                self.log(|| format!("query {}: unknown", self.queries));
+                Ok(Step::Unknown)
+            }
+        }
+    }
+}
+
+/// Default for `--oracle-max-vars`
+const DEFAULT_ORACLE_MAX_VARS: usize = 20;
+
+/// Options of the `solve` and `enumerate` subcommands
+pub struct SolveOptions {
+    order: CandidateOrder,
+    shuffle: bool,
+    seed: u64,
+    portfolio: usize,
+    speculate: usize,
+    preprocess: bool,
+    equivalences: bool,
+    pure_literals: bool,
+    warm_start: bool,
+    mem_limit: Option<u64>,
+    time_limit: Option<f64>,
+    deterministic: bool,
+    print_core: bool,
+    fingerprint: bool,
+    importance: bool,
+    backbone: bool,
+    pub all: bool,
+    /// Whether empty lines request solving, otherwise they are ignored
+    pub incremental: bool,
+    verify: bool,
+    oracle: Option<usize>,
+    certificate_path: Option<PathBuf>,
+    /// Proofs of the positive solver and of the candidate checks
+    proof_path: Option<PathBuf>,
+    check_proof_path: Option<PathBuf>,
+    proof_format: ProofFormat,
+    /// Whether to check the proofs once they are complete
+    check_proofs: bool,
+    checkpoint_path: Option<PathBuf>,
+    blocking_out_path: Option<PathBuf>,
+    blocked_in_path: Option<PathBuf>,
+    /// Read from `blocked_in_path` by [`solve`]
+    blocked_in: Vec<Vec<isize>>,
+    learned_out_path: Option<PathBuf>,
+    learned_in_path: Option<PathBuf>,
+    /// Read from `learned_in_path` by [`solve`]
+    learned_in: Option<Learned>,
+    checkpoint_interval: f64,
+    resume: Option<PathBuf>,
+    watch: Option<PathBuf>,
+    /// Maximal number of models reported by `--all`
+    max_models: Option<usize>,
+    /// Number of reported models between coverage lines
+    coverage_interval: Option<usize>,
+    /// Coverage in percent at which `--all` stops
+    coverage_target: Option<f64>,
+    no_full_model: bool,
+    query_stats: bool,
+    trace: bool,
+    timing: bool,
+    memory: bool,
+    check_histogram: bool,
+    framed: bool,
+    /// Set by `--tui`
+    dashboard: Option<Dashboard>,
+    /// Address of `--metrics`
+    metrics_addr: Option<String>,
+    hint_path: Option<PathBuf>,
+    /// Read from `hint_path` by [`solve`]
+    hint: Option<Vec<isize>>,
+    /// Set by [`solve`] once the metrics are served
+    metrics: Option<Metrics>,
+    script: Option<Script>,
+    exit_codes: ExitCodes,
+    pos_options: SolverOptions,
+    neg_options: SolverOptions,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            order: CandidateOrder::Arbitrary,
+            shuffle: false,
+            seed: 0,
+            portfolio: 1,
+            speculate: 0,
+            preprocess: false,
+            equivalences: false,
+            pure_literals: false,
+            warm_start: false,
+            mem_limit: None,
+            time_limit: None,
+            deterministic: false,
+            print_core: false,
+            fingerprint: false,
+            importance: false,
+            backbone: false,
+            all: false,
+            incremental: true,
+            verify: false,
+            oracle: None,
+            certificate_path: None,
+            proof_path: None,
+            check_proof_path: None,
+            proof_format: ProofFormat::Lrat,
+            check_proofs: false,
+            checkpoint_path: None,
+            blocking_out_path: None,
+            blocked_in_path: None,
+            blocked_in: vec![],
+            learned_out_path: None,
+            learned_in_path: None,
+            learned_in: None,
+            checkpoint_interval: 60.0,
+            resume: None,
+            watch: None,
+            max_models: None,
+            coverage_interval: None,
+            coverage_target: None,
+            no_full_model: false,
+            query_stats: false,
+            trace: false,
+            timing: false,
+            memory: false,
+            check_histogram: false,
+            framed: false,
+            dashboard: None,
+            metrics_addr: None,
+            hint_path: None,
+            hint: None,
+            metrics: None,
+            script: None,
+            exit_codes: ExitCodes::Posix,
+            pos_options: SolverOptions::default(),
+            neg_options: SolverOptions::default(),
+        }
+    }
+}
+
+impl SolveOptions {
+    /// Handles `arg` if it is an option of `solve` and `enumerate`, returning whether it was one
+    pub fn parse_arg(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> anyhow::Result<bool> {
+        match arg {
+            "--order" => {
+                self.order = arg_value(args, arg)?.parse()?;
+            }
+            "--shuffle-candidates" => self.shuffle = true,
+            "--seed" => {
+                self.seed = arg_value(args, arg)?.parse()?;
+            }
+            "--portfolio" => {
+                self.portfolio = arg_value(args, arg)?.parse()?;
+            }
+            "--speculate" => {
+                self.speculate = arg_value(args, arg)?.parse()?;
+            }
+            "--preprocess" => self.preprocess = true,
+            "--equivalences" => self.equivalences = true,
+            "--pure-literals" => self.pure_literals = true,
+            "--warm-start" => self.warm_start = true,
+            "--mem-limit" => {
+                self.mem_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--time-limit" => {
+                self.time_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--all" => self.all = true,
+            "--max-models" => {
+                self.max_models = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--coverage" => {
+                let interval = arg_value(args, arg)?.parse()?;
+                if interval == 0 {
+                    anyhow::bail!("--coverage must be positive");
+                }
+                self.coverage_interval = Some(interval);
+            }
+            "--coverage-target" => {
+                self.coverage_target = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--verify" => self.verify = true,
+            "--oracle" => self.oracle = Some(DEFAULT_ORACLE_MAX_VARS),
+            "--oracle-max-vars" => {
+                self.oracle = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--certificate" => {
+                self.certificate_path = Some(arg_value(args, arg)?.into());
+            }
+            "--proof" => self.proof_path = Some(arg_value(args, arg)?.into()),
+            "--check-proof" => self.check_proof_path = Some(arg_value(args, arg)?.into()),
+            "--proof-format" => self.proof_format = arg_value(args, arg)?.parse()?,
+            "--check-proofs" => self.check_proofs = true,
+            "--checkpoint" => {
+                self.checkpoint_path = Some(arg_value(args, arg)?.into());
+            }
+            "--blocking-out" => self.blocking_out_path = Some(arg_value(args, arg)?.into()),
+            "--blocked-in" => self.blocked_in_path = Some(arg_value(args, arg)?.into()),
+            "--learned-out" => self.learned_out_path = Some(arg_value(args, arg)?.into()),
+            "--learned-in" => self.learned_in_path = Some(arg_value(args, arg)?.into()),
+            "--checkpoint-interval" => {
+                self.checkpoint_interval = arg_value(args, arg)?.parse()?;
+            }
+            "--resume" => {
+                self.resume = Some(arg_value(args, arg)?.into());
+            }
+            "--watch" => {
+                self.watch = Some(arg_value(args, arg)?.into());
+            }
+            "--deterministic" => self.deterministic = true,
+            "--unsat-core" => self.print_core = true,
+            "--fingerprint" => self.fingerprint = true,
+            "--importance" => self.importance = true,
+            "--backbone" => self.backbone = true,
+            "--no-full-model" => self.no_full_model = true,
+            "--query-stats" => self.query_stats = true,
+            "--trace" => self.trace = true,
+            "--timing" => self.timing = true,
+            "--memory" => self.memory = true,
+            "--check-histogram" => self.check_histogram = true,
+            "--framed" => self.framed = true,
+            "--tui" => self.dashboard = Some(Dashboard::default()),
+            "--metrics" => self.metrics_addr = Some(arg_value(args, arg)?),
+            "--hint" => self.hint_path = Some(arg_value(args, arg)?.into()),
+            "--script" => {
+                let path = arg_value(args, arg)?;
+                let text =
+                    fs::read_to_string(&path).with_context(|| format!("reading {}", path))?;
+                self.script =
+                    Some(Script::parse(&text).with_context(|| format!("script {}", path))?);
+            }
+            "--prefer-false" => self.pos_options.polarity = Some(Polarity::False),
+            "--exit-codes" => {
+                self.exit_codes = arg_value(args, arg)?.parse()?;
+            }
+            "--backend" => {
+                let backend = arg_value(args, arg)?.parse()?;
+                self.pos_options.backend = backend;
+                self.neg_options.backend = backend;
+            }
+            "--pos-backend" => {
+                self.pos_options.backend = arg_value(args, arg)?.parse()?;
+            }
+            "--neg-backend" => {
+                self.neg_options.backend = arg_value(args, arg)?.parse()?;
+            }
+            "--first-model-backend" => {
+                self.pos_options.initial_backend = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--ipasir" => {
+                let path = PathBuf::from(arg_value(args, arg)?);
+                for options in [&mut self.pos_options, &mut self.neg_options] {
+                    options.backend = Backend::Ipasir;
+                    options.ipasir = Some(path.clone());
+                }
+            }
+            "--pos-ipasir" => {
+                self.pos_options.backend = Backend::Ipasir;
+                self.pos_options.ipasir = Some(arg_value(args, arg)?.into());
+            }
+            "--neg-ipasir" => {
+                self.neg_options.backend = Backend::Ipasir;
+                self.neg_options.ipasir = Some(arg_value(args, arg)?.into());
+            }
+            "--verbosity" => {
+                let option = ("verbosity".to_owned(), arg_value(args, arg)?);
+                self.pos_options.native.push(option.clone());
+                self.neg_options.native.push(option);
+            }
+            "--solver-threads" => {
+                let threads = arg_value(args, arg)?.parse()?;
+                self.pos_options.threads = Some(threads);
+                self.neg_options.threads = Some(threads);
+            }
+            "--pos-solver-threads" => {
+                self.pos_options.threads = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--neg-solver-threads" => {
+                self.neg_options.threads = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--solve-time-limit" => {
+                self.pos_options.time_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--check-time-limit" => {
+                self.neg_options.time_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--solve-conflict-limit" => {
+                self.pos_options.conflict_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--check-conflict-limit" => {
+                self.neg_options.conflict_limit = Some(arg_value(args, arg)?.parse()?);
+            }
+            "--solver-opt" => {
+                let option = native_option(&arg_value(args, arg)?)?;
+                self.pos_options.native.push(option.clone());
+                self.neg_options.native.push(option);
+            }
+            "--pos-solver-opt" => {
+                let option = native_option(&arg_value(args, arg)?)?;
+                self.pos_options.native.push(option);
+            }
+            "--neg-solver-opt" => {
+                let option = native_option(&arg_value(args, arg)?)?;
+                self.neg_options.native.push(option);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Splits a line of the input into its first word and the rest
+fn split_command(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    line.split_once(char::is_whitespace).unwrap_or((line, ""))
+}
+
+/// Parses the name and `value=lit` pairs of an `exactly-one` line, optionally terminated by a `0`
+fn parse_group(rest: &str, max_var: usize) -> anyhow::Result<Group> {
+    let (name, pairs) = split_command(rest);
+    if name.is_empty() || name.contains('=') {
+        anyhow::bail!("exactly-one requires a name");
+    }
+    let mut values = vec![];
+    let mut pairs = pairs.split_ascii_whitespace();
+    while let Some(pair) = pairs.next() {
+        if pair == "0" {
+            if let Some(junk) = pairs.next() {
+                anyhow::bail!("unexpected {:?} after the terminating 0", junk);
+            }
+            break;
+        }
+        let (value, lit) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("value {:?} is not of the form value=lit", pair))?;
+        match input::parse_clause(lit, max_var)?[..] {
+            [lit] => values.push((value.to_owned(), lit)),
+            _ => anyhow::bail!("value {:?} is not of the form value=lit", pair),
+        }
+    }
+    if values.is_empty() {
+        anyhow::bail!("group {} has no values", name);
+    }
+    Ok(Group {
+        name: name.to_owned(),
+        values,
+    })
+}
+
+/// Splits a `keep`, `drop` or `neutral` line of the input into the preference and its literals
+fn parse_preference(line: &str) -> Option<(Option<Preference>, &str)> {
+    let (word, lits) = split_command(line);
+    let preference = match word {
+        "keep" => Some(Preference::Keep),
+        "drop" => Some(Preference::Drop),
+        "neutral" => None,
+        _ => return None,
+    };
+    Some((preference, lits))
+}
+
+/// Runs the `solve` and `enumerate` subcommands
+///
+/// Returns the exit code selected by `--exit-codes`.
+pub fn solve(
+    mut options: SolveOptions,
+    input: Option<&str>,
+    max_var: usize,
+) -> anyhow::Result<i32> {
+    if options.deterministic {
+        if options.mem_limit.is_some() {
+            anyhow::bail!("--mem-limit isn't deterministic");
+        }
+        if options.time_limit.is_some() {
+            anyhow::bail!("--time-limit isn't deterministic");
+        }
+        if options.timing {
+            anyhow::bail!("--timing isn't deterministic");
+        }
+        if options.dashboard.is_some() {
+            anyhow::bail!("--tui isn't deterministic");
+        }
+        if options.metrics_addr.is_some() {
+            anyhow::bail!("--metrics isn't deterministic");
+        }
+        options.pos_options.deterministic = true;
+        options.neg_options.deterministic = true;
+    }
+    if options.watch.is_some() && input.is_some() {
+        anyhow::bail!("--watch reads its own input file");
+    }
+    if cfg!(target_os = "wasi") {
+        let threaded = [
+            ("--portfolio", options.portfolio > 1),
+            ("--speculate", options.speculate > 0),
+            ("--tui", options.dashboard.is_some()),
+            ("--metrics", options.metrics_addr.is_some()),
+            ("--mem-limit", options.mem_limit.is_some()),
+            ("--time-limit", options.time_limit.is_some()),
+        ];
+        if let Some((option, _)) = threaded.iter().find(|(_, used)| *used) {
+            anyhow::bail!("{} requires threads, which WASI doesn't support", option);
+        }
+    }
+    if options.check_proofs && options.proof_path.is_none() && options.check_proof_path.is_none() {
+        anyhow::bail!("--check-proofs requires --proof or --check-proof");
+    }
+
+    if let Some(path) = &options.hint_path {
+        options.hint = Some(read_assignment(path, max_var)?);
+    }
+    if let Some(path) = &options.blocked_in_path {
+        options.blocked_in = read_models(path, max_var)?;
+    }
+    if let Some(path) = &options.learned_in_path {
+        options.learned_in = Some(Learned::load(path)?);
+    }
+    if let Some(addr) = &options.metrics_addr {
+        let metrics = Metrics::default();
+        metrics
+            .serve(addr)
+            .with_context(|| format!("serving metrics on {}", addr))?;
+        options.metrics = Some(metrics);
+    }
+
+    let interrupt = INTERRUPT.get_or_init(Default::default).clone();
+    install_interrupt_handler();
+
+    let mem_exceeded = Arc::new(AtomicBool::new(false));
+    if let Some(mem_limit) = options.mem_limit {
+        memory::watch_limit(mem_limit << 20, mem_exceeded.clone(), interrupt.clone());
+    }
+
+    let time_exceeded = Arc::new(AtomicBool::new(false));
+    if let Some(time_limit) = options.time_limit {
+        let (time_exceeded, interrupt) = (time_exceeded.clone(), interrupt.clone());
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs_f64(time_limit));
+            time_exceeded.store(true, Ordering::SeqCst);
+            interrupt.store(true, Ordering::SeqCst);
+        });
+    }
+
+    let renderer = options
+        .dashboard
+        .as_ref()
+        .map(|dashboard| dashboard.spawn(Duration::from_millis(250)));
+    let outcome = if let Some(path) = &options.watch {
+        watch(&options, path, max_var, &interrupt)?
+    } else {
+        run(&options, open_input(input)?, max_var, &interrupt)?
+    };
+    drop(renderer);
+
+    if mem_exceeded.load(Ordering::SeqCst) {
+        anyhow::bail!(
+            "memory limit of {} MiB exceeded",
+            options.mem_limit.unwrap()
+        );
+    }
+    if time_exceeded.load(Ordering::SeqCst) {
+        anyhow::bail!(
+            "time limit of {} seconds exceeded",
+            options.time_limit.unwrap()
+        );
+    }
+
+    Ok(options.exit_codes.code(outcome))
+}
+
+/// Modification time and size of a file, to detect changes
+fn file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Runs the input of `path` every time the file changes, until interrupted
+///
+/// Errors of a run, e.g. while the file is being edited, are reported and the next change is
+/// awaited. Returns the outcome of the last run.
+fn watch(
+    options: &SolveOptions,
+    path: &Path,
+    max_var: usize,
+    interrupt: &Arc<AtomicBool>,
+) -> anyhow::Result<Outcome> {
+    loop {
+        let version = file_version(path);
+        println!("reading {}", path.display());
+        let result = fs::File::open(path)
+            .with_context(|| format!("opening {}", path.display()))
+            .and_then(|file| run(options, io::BufReader::new(file), max_var, interrupt));
+        let outcome = result.unwrap_or_else(|error| {
+            eprintln!("Error: {:?}", error);
+            Outcome::Unknown
+        });
+
+        // Polling avoids a dependency for file system notifications
+        while file_version(path) == version {
+            if interrupt.load(Ordering::SeqCst) {
+                return Ok(outcome);
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+/// Prints a line for a candidate check, for `--trace`
+fn print_trace(trace: &CheckTrace) {
+    let join = |lits: &[isize]| {
+        lits.iter()
+            .map(|lit| lit.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match trace.result {
+        Check::Sat => println!(
+            "check {}, keeping {}: sat",
+            trace.candidate,
+            join(&trace.assumptions)
+        ),
+        Check::Unsat => println!(
+            "check {}, keeping {}: unsat, core {}",
+            trace.candidate,
+            join(&trace.assumptions),
+            join(&trace.core)
+        ),
+        Check::Unknown => println!(
+            "check {}, keeping {}: unknown",
+            trace.candidate,
+            join(&trace.assumptions)
+        ),
+    }
+}
+
+/// Creates a session for a new problem
+fn new_session(options: &SolveOptions, interrupt: &Arc<AtomicBool>) -> anyhow::Result<Session> {
+    let mut builder = Finder::builder()
+        .order(options.order)
+        .portfolio(options.portfolio)
+        .speculate(options.speculate)
+        .pos_solver_options(options.pos_options.clone())
+        .neg_solver_options(options.neg_options.clone())
+        .preprocess(options.preprocess)
+        .equivalences(options.equivalences)
+        .pure_literals(options.pure_literals)
+        .warm_start(options.warm_start)
+        .progress(options.dashboard.is_none())
+        .interrupt(interrupt.clone());
+    if let Some(dashboard) = &options.dashboard {
+        let dashboard = dashboard.clone();
+        builder = builder.on_progress(move |progress| dashboard.progress(progress));
+    }
+    if options.trace {
+        builder = builder.on_check(print_trace);
+    }
+    if options.shuffle {
+        builder = builder.shuffle_candidates(options.seed);
+    }
+    if let Some(script) = &options.script {
+        builder = builder.script(script.clone());
+    }
+    if let Some(path) = &options.proof_path {
+        builder = builder.pos_proof(path, options.proof_format);
+    }
+    if let Some(path) = &options.check_proof_path {
+        builder = builder.neg_proof(path, options.proof_format);
+    }
+    let checkpoint_path = options
+        .checkpoint_path
+        .clone()
+        .or_else(|| options.resume.clone());
+    let certificate_path = options.certificate_path.as_deref();
+    let mut session = Session {
+        finder: builder.build()?,
+        print_full_model: !options.no_full_model,
+        equivalences: options.equivalences,
+        checkpointer: checkpoint_path.map(|path| Checkpointer {
+            path,
+            interval: Duration::from_secs_f64(options.checkpoint_interval),
+            last_save: Instant::now(),
+            checkpoint: Checkpoint::default(),
+        }),
+        blocking_out: match &options.blocking_out_path {
+            Some(path) => Some(
+                fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("opening {}", path.display()))?,
+            ),
+            None => None,
+        },
+        certificate: certificate_path
+            .map(CertificateWriter::create)
+            .transpose()?,
+        verify: options.verify,
+        oracle: options.oracle,
+        clauses: (options.verify
+            || options.oracle.is_some()
+            || certificate_path.is_some()
+            || options.print_core)
+            .then(Vec::new),
+        lines: options.print_core.then(Vec::new),
+        clause_groups: options.print_core.then(Vec::new),
+        clause_group_names: vec![],
+        current_group: None,
+        input_clauses: 0,
+        fingerprint: options.fingerprint.then(Fingerprint::default),
+        importance: options.importance.then(Importance::default),
+        backbone: if options.backbone {
+            Some(
+                Finder::builder()
+                    .solver_options(options.pos_options.clone())
+                    .build()?,
+            )
+        } else {
+            None
+        },
+        coverage: (options.coverage_interval.is_some() || options.coverage_target.is_some())
+            .then(Coverage::default),
+        outcome: None,
+        models: 0,
+        assumptions: BTreeMap::new(),
+        groups: vec![],
+        show: None,
+        minimized: vec![],
+        fixed: vec![],
+        query_stats: options.query_stats,
+        stats_time: !options.deterministic,
+        queries: 0,
+        dashboard: options.dashboard.clone(),
+        metrics: options.metrics.clone(),
+        hint: options.hint.clone(),
+    };
+    if let Some(metrics) = &session.metrics {
+        metrics.next_problem();
+    }
+    if let Some(learned) = &options.learned_in {
+        session.finder.import_learned(learned);
+    }
+
+    if let Some(resume) = &options.resume {
+        let checkpoint = Checkpoint::load(resume)?;
+        for clause in &checkpoint.blocked {
+            session.add_clause(clause);
+            if let Some(certificate) = &mut session.certificate {
+                certificate.blocked(clause)?;
+            }
+        }
+        println!("resuming after {} models", checkpoint.models());
+        if checkpoint.models() > 0 {
+            session.outcome = Some(Outcome::Sat);
+        }
+        if let Some(checkpointer) = &mut session.checkpointer {
+            checkpointer.checkpoint = checkpoint;
+        }
+    }
+    if let Some(path) = &options.blocked_in_path {
+        for model in &options.blocked_in {
+            let blocking = session.block(model)?;
+            if let Some(certificate) = &mut session.certificate {
+                certificate.blocked(&blocking)?;
+            }
+            if let Some(checkpointer) = &mut session.checkpointer {
+                checkpointer.record(&blocking)?;
+            }
+        }
+        println!(
+            "blocked {} models of {}",
+            options.blocked_in.len(),
+            path.display()
+        );
+        if !options.blocked_in.is_empty() {
+            session.outcome = Some(Outcome::Sat);
+        }
+    }
+    Ok(session)
+}
+
+/// Finishes the problem of `session` once its input ended, returning its outcome
+///
+/// Unless `done`, this first handles `--all`.
+fn finish_problem(
+    options: &SolveOptions,
+    session: &mut Session,
+    done: bool,
+    interrupt: &AtomicBool,
+) -> anyhow::Result<Outcome> {
+    // With `--all` we keep requesting models after the end of the input
+    if options.all && !done {
+        let mut models = 0;
+        let exhaustive = loop {
+            if interrupt.load(Ordering::SeqCst) {
+                println!("interrupted");
+                break false;
+            }
+            if options.max_models == Some(models) {
+                // Only a full model is needed to tell whether there are further minimal models
+                break session.finder.solve() == Solved::Unsat;
+            }
+            match session.step()? {
+                Step::Blocked => {
+                    models += 1;
+                    if session.coverage_reached(
+                        models,
+                        options.coverage_interval,
+                        options.coverage_target,
+                    ) {
+                        break false;
+                    }
+                }
+                Step::Unsat | Step::UnsatAssuming => break true,
+                // Retrying would just run into the same time limit again
+                Step::Unknown | Step::Interrupted => break false,
+            }
+        };
+        if options.max_models.is_some() {
+            println!("exhaustive: {}", if exhaustive { "yes" } else { "no" });
+        }
+    }
+
+    if let Some(checkpointer) = &mut session.checkpointer {
+        checkpointer.save()?;
+    }
+
+    if let Some(fingerprint) = &session.fingerprint {
+        println!("fingerprint: {}", fingerprint);
+    }
+    if let Some(importance) = &session.importance {
+        println!("{}", importance);
+    }
+
+    Ok(session.outcome.unwrap_or(Outcome::Unknown))
+}
+
+/// If `line` separates two independent problems of the input, returns whether the variables of
+/// the previous problem are kept
+fn parse_separator(line: &str) -> Option<bool> {
+    let line = line.trim();
+    match line {
+        "reset" => Some(false),
+        "reset keep-vars" => Some(true),
+        _ => line.starts_with("---").then_some(false),
+    }
+}
+
+/// Prints how the time `total` of a run was spent, for `--timing`
+///
+/// `parse` is the time spent reading and parsing the input, `stats` those of all problems.
+fn print_timing(parse: Duration, stats: &Stats, total: Duration) {
+    let line = |phase: &str, time: Duration| {
+        println!(
+            "time {}: {:.3}ms ({:.1}%)",
+            phase,
+            time.as_secs_f64() * 1000.0,
+            100.0 * time.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE)
+        );
+    };
+    let checks = &stats.check_times;
+    line("parsing", parse);
+    line("full model search", stats.solve_time);
+    line("sat checks", checks.sat);
+    line("unsat checks", checks.unsat);
+    line("unknown checks", checks.unknown);
+    line(
+        "other reduction",
+        stats.reduce_time.saturating_sub(checks.total()),
+    );
+    line(
+        "output and other",
+        total.saturating_sub(parse + stats.solve_time + stats.reduce_time),
+    );
+    line("total", total);
+}
+
+/// Prints the distributions of the candidate checks of `stats`, for `--check-histogram`
+fn print_histograms(stats: &Stats) {
+    let checks = &stats.checks;
+    println!(
+        "checks: sat {}, unsat {}, unknown {}",
+        checks.sat, checks.unsat, checks.unknown
+    );
+    let histograms = &stats.histograms;
+    println!("unsat core sizes: {}", histograms.core_sizes);
+    let conflicts = [
+        ("sat", &histograms.sat_conflicts, checks.sat),
+        ("unsat", &histograms.unsat_conflicts, checks.unsat),
+    ];
+    for (result, histogram, count) in conflicts {
+        // Without any recorded check, the backend doesn't report conflicts
+        if histogram.is_empty() && count > 0 {
+            println!("conflicts of {} checks: unknown", result);
+        } else {
+            println!("conflicts of {} checks: {}", result, histogram);
+        }
+    }
+}
+
+/// Prints the peak memory usage, with `peak`, and the estimated size of the solvers of `finder`
+fn print_memory(finder: &Finder, peak: bool) {
+    if peak {
+        match memory::peak_resident_bytes() {
+            Some(bytes) => println!("peak memory: {:.1} MiB", bytes as f64 / (1 << 20) as f64),
+            None => println!("peak memory: unknown"),
+        }
+    }
+    let sizes = finder.solver_sizes();
+    for (name, size) in [("positive", sizes.pos), ("negative", sizes.neg)] {
+        println!(
+            "{} solver: ~{} KiB ({} variables, {} clauses, {} literals)",
+            name,
+            size.estimated_bytes().div_ceil(1 << 10),
+            size.vars,
+            size.clauses,
+            size.lits
+        );
+    }
+    if sizes.neg_copies > 0 {
+        println!("negative solver copies: {}", sizes.neg_copies);
+    }
+}
+
+/// Finds minimal models for the clauses of `reader`, as described in the README
+///
+/// For multiple problems, the outcome is unsat if any problem is unsat, otherwise unknown if any
+/// problem is unknown.
+fn run(
+    options: &SolveOptions,
+    reader: impl BufRead,
+    max_var: usize,
+    interrupt: &Arc<AtomicBool>,
+) -> anyhow::Result<Outcome> {
+    let start = Instant::now();
+    let mut parse_time = Duration::ZERO;
+    let mut stats = Stats::default();
+    let mut read_time = Duration::ZERO;
+    let mut lines = reader.lines();
+    let lines = std::iter::from_fn(|| {
+        let start = Instant::now();
+        let line = lines.next();
+        read_time += start.elapsed();
+        line
+    });
+
+    let mut session = new_session(options, interrupt)?;
+    let mut outcomes = vec![];
+    // Whether no further models will be reported for the current problem
+    let mut done = false;
+    let mut interrupted = false;
+    for (index, line) in lines.enumerate() {
+        if interrupt.load(Ordering::SeqCst) {
+            println!("interrupted");
+            interrupted = true;
+            break;
+        }
+
+        let line = line?;
+        // Ends the response when this iteration ends, however it does
+        let _frame = if options.framed && is_request(&line) {
+            Some(FrameEnd(Some(index + 1)))
+        } else {
+            None
+        };
+        if let Some(keep_vars) = parse_separator(&line) {
+            if options.checkpoint_path.is_some()
+                || options.resume.is_some()
+                || options.blocked_in_path.is_some()
+                || options.learned_out_path.is_some()
+                || options.certificate_path.is_some()
+            {
+                anyhow::bail!(
+                    "line {}: --checkpoint, --resume, --blocked-in, --learned-out and \
+                     --certificate only support a single problem",
+                    index + 1
+                );
+            }
+            if options.check_proofs {
+                anyhow::bail!(
+                    "line {}: --check-proofs only supports a single problem, as each problem \
+                     overwrites the proofs",
+                    index + 1
+                );
+            }
+            let outcome = finish_problem(options, &mut session, done, interrupt)?;
+            print_summary(outcomes.len() + 1, session.models, outcome);
+            outcomes.push(outcome);
+            if interrupt.load(Ordering::SeqCst) {
+                interrupted = true;
+                break;
+            }
+            stats += session.finder.stats();
+            session.reset(keep_vars);
+            done = false;
+            continue;
+        }
+        if done {
+            // Skip the rest of the problem
+            continue;
+        }
+
+        if let Some((preference, lits)) = parse_preference(&line) {
+            let lits = input::parse_clause(lits, max_var)
+                .with_context(|| format!("line {}", index + 1))?;
+            for lit in lits {
+                session.finder.set_preference(lit, preference);
+            }
+            continue;
+        }
+
+        match split_command(&line) {
+            ("prefer", lits) => {
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                session.finder.prefer(&lits);
+                continue;
+            }
+            ("block", lits) => {
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                let blocking: Vec<isize> = lits.iter().map(|&lit| -lit).collect();
+                session.add_input_clause(&blocking, index + 1);
+                continue;
+            }
+            ("assume-always", rest) => {
+                let (name, lits) = split_command(rest);
+                if name.is_empty() {
+                    anyhow::bail!("line {}: assume-always requires a name", index + 1);
+                }
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                session.assumptions.insert(name.to_owned(), lits);
+                session.update_assumptions();
+                continue;
+            }
+            ("#show", lits) => {
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                session.add_show(&lits, index + 1)?;
+                continue;
+            }
+            (command @ ("minimize" | "fix"), lits) => {
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                session.add_circumscription(&lits, command == "fix", index + 1)?;
+                continue;
+            }
+            ("group", name) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    anyhow::bail!("line {}: group requires a name", index + 1);
+                }
+                session.start_clause_group(name);
+                continue;
+            }
+            ("end-group", rest) if rest.trim().is_empty() => {
+                session.current_group = None;
+                continue;
+            }
+            ("exactly-one", rest) => {
+                let group =
+                    parse_group(rest, max_var).with_context(|| format!("line {}", index + 1))?;
+                session.add_group(group, index + 1)?;
+                continue;
+            }
+            (query @ ("brave" | "cautious"), lits) => {
+                let lits = input::parse_clause(lits, max_var)
+                    .with_context(|| format!("line {}", index + 1))?;
+                match lits[..] {
+                    [lit] => session.print_consequence(lit, query == "cautious"),
+                    _ => anyhow::bail!("line {}: {} requires a single literal", index + 1, query),
+                }
+                continue;
+            }
+            ("show", "stats") => {
+                session.print_stats();
+                continue;
+            }
+            ("clear-assumptions", name) => {
+                match name.trim() {
+                    "" => session.assumptions.clear(),
+                    name => {
+                        session.assumptions.remove(name);
+                    }
+                }
+                session.update_assumptions();
+                continue;
+            }
+            _ => (),
+        }
+
+        let parse_start = Instant::now();
+        let clause =
+            input::parse_clause(&line, max_var).with_context(|| format!("line {}", index + 1))?;
+        parse_time += parse_start.elapsed();
+
+        // We use an emtpy clause to request solving
+        if clause.is_empty() {
+            if !options.incremental {
+                continue;
+            }
+            match session.step()? {
+                Step::Unsat => done = true,
+                Step::Interrupted => {
+                    interrupted = true;
+                    break;
+                }
+                Step::Blocked | Step::Unknown | Step::UnsatAssuming => (),
+            }
+        } else {
+            session.add_input_clause(&clause, index + 1);
+        }
+    }
+
+    let outcome = finish_problem(options, &mut session, done || interrupted, interrupt)?;
+    if !outcomes.is_empty() {
+        print_summary(outcomes.len() + 1, session.models, outcome);
+    }
+    outcomes.push(outcome);
+
+    stats += session.finder.stats();
+    if options.timing {
+        print_timing(parse_time + read_time, &stats, start.elapsed());
+    }
+    if options.check_histogram {
+        print_histograms(&stats);
+    }
+    if options.memory {
+        print_memory(&session.finder, !options.deterministic);
+    }
+    if let Some(path) = &options.learned_out_path {
+        session.finder.learned().save(path)?;
+    }
+    if options.check_proofs {
+        // The proofs are complete once the finder is dropped
+        drop(session);
+        check_proofs(options)?;
+    }
+    if options.framed {
+        drop(FrameEnd(None));
+    }
+
+    Ok(if outcomes.contains(&Outcome::Unsat) {
+        Outcome::Unsat
+    } else if outcomes.contains(&Outcome::Unknown) {
+        Outcome::Unknown
+    } else {
+        Outcome::Sat
+    })
+}
+
+/// Checks the complete proofs of `--proof` and `--check-proof`, for `--check-proofs`
+fn check_proofs(options: &SolveOptions) -> anyhow::Result<()> {
+    let proofs = [
+        ("proof", &options.proof_path),
+        ("check proof", &options.check_proof_path),
+    ];
+    for (name, path) in proofs {
+        let path = match path {
+            Some(path) => path,
+            None => continue,
+        };
+        let checked = proof_check::check_file(path, options.proof_format)
+            .with_context(|| format!("{} {} is invalid", name, path.display()))?;
+        println!(
+            "{} verified: {} derived clauses{}",
+            name,
+            checked.derived,
+            if checked.refutation {
+                ", including the empty clause"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Prints the line ending a response with `--framed` when dropped
+///
+/// The line is `%end N` for the request on line `N` and `%end eof` for the output following the
+/// end of the input.
+struct FrameEnd(Option<usize>);
+
+impl Drop for FrameEnd {
+    fn drop(&mut self) {
+        match self.0 {
+            Some(line) => println!("%end {}", line),
+            None => println!("%end eof"),
+        }
+    }
+}
+
+/// Whether `line` is answered by a response with `--framed`, unlike clauses and declarations
+fn is_request(line: &str) -> bool {
+    line.trim().is_empty()
+        || parse_separator(line).is_some()
+        || matches!(
+            split_command(line),
+            ("brave" | "cautious", _) | ("show", "stats")
+        )
+}
+
+/// Prints the summary line of a problem of a multi-problem input
+fn print_summary(problem: usize, models: usize, outcome: Outcome) {
+    let outcome = match outcome {
+        Outcome::Sat => "sat",
+        Outcome::Unsat => "unsat",
+        Outcome::Unknown => "unknown",
+    };
+    println!("problem {}: {} models, {}", problem, models, outcome);
+}
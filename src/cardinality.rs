@@ -0,0 +1,179 @@
+//! Encodings of cardinality constraints
+//!
+//! Every encoding computes unary counter outputs for the input literals, where the `i`-th output
+//! is true exactly when at least `i + 1` of the inputs are true. For an at most `k` constraint,
+//! counting beyond `k + 1` isn't needed, so only that many outputs are produced.
+//!
+//! The clauses define the outputs in both directions, so they can be added to the positive solver
+//! as well as to the negative solver, where the output for `k + 1` indicates that the constraint
+//! is violated.
+use crate::backend::Lit;
+
+/// How a cardinality constraint is encoded into clauses
+///
+/// All encodings are equivalent, but differ in size and propagation strength, which affects the
+/// performance of the reduction.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum CardinalityEncoding {
+    /// A tree of unary adders, with `O(n k)` clauses for `n` literals and bound `k`
+    #[default]
+    Totalizer,
+    /// A chain of unary counters over growing prefixes of the literals, with `O(n k)` clauses
+    SequentialCounter,
+    /// Batcher's odd-even merge sort, with `O(n log^2 n)` clauses independent of the bound
+    SortingNetwork,
+}
+
+/// Encodes the number of true literals in `lits`, counting up to `limit`
+///
+/// Returns the outputs, whose `i`-th element is true iff at least `i + 1` literals are true.
+/// `new_var` allocates auxiliary variables and `add_clause` receives the defining clauses.
+pub fn encode(
+    encoding: CardinalityEncoding,
+    lits: &[Lit],
+    limit: usize,
+    new_var: &mut impl FnMut() -> Lit,
+    add_clause: &mut impl FnMut(&[Lit]),
+) -> Vec<Lit> {
+    match encoding {
+        CardinalityEncoding::Totalizer => totalizer(lits, limit, new_var, add_clause),
+        CardinalityEncoding::SequentialCounter => {
+            sequential_counter(lits, limit, new_var, add_clause)
+        }
+        CardinalityEncoding::SortingNetwork => sorting_network(lits, limit, new_var, add_clause),
+    }
+}
+
+/// A binary tree whose leaves are the input literals, where every node counts its leaves
+fn totalizer(
+    lits: &[Lit],
+    limit: usize,
+    new_var: &mut impl FnMut() -> Lit,
+    add_clause: &mut impl FnMut(&[Lit]),
+) -> Vec<Lit> {
+    if lits.len() <= 1 {
+        return lits.to_vec();
+    }
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let left = totalizer(left, limit, new_var, add_clause);
+    let right = totalizer(right, limit, new_var, add_clause);
+
+    let outputs: Vec<Lit> = (0..limit.min(left.len() + right.len()))
+        .map(|_| new_var())
+        .collect();
+
+    // Writing `a_i` for "at least `i` of the left inputs", where `a_0` is true, and `b_j` for the
+    // right inputs, the outputs are defined by `a_i & b_j -> r_(i + j)` and
+    // `!a_(i + 1) & !b_(j + 1) -> !r_(i + j + 1)`. A child's output beyond its inputs is false, so
+    // it is left out. Only a child with more inputs than `limit` lacks outputs that aren't false,
+    // but those would only occur in clauses for `r` beyond `limit`.
+    for i in 0..=left.len() {
+        for j in 0..=right.len() {
+            let sum = i + j;
+            if (1..=outputs.len()).contains(&sum) {
+                let mut clause = vec![outputs[sum - 1]];
+                clause.extend(i.checked_sub(1).map(|i| !left[i]));
+                clause.extend(j.checked_sub(1).map(|j| !right[j]));
+                add_clause(&clause);
+            }
+            if sum < outputs.len() {
+                let mut clause = vec![!outputs[sum]];
+                clause.extend(left.get(i));
+                clause.extend(right.get(j));
+                add_clause(&clause);
+            }
+        }
+    }
+    outputs
+}
+
+/// Counts the true literals of each prefix, based on the count of the previous prefix
+fn sequential_counter(
+    lits: &[Lit],
+    limit: usize,
+    new_var: &mut impl FnMut() -> Lit,
+    add_clause: &mut impl FnMut(&[Lit]),
+) -> Vec<Lit> {
+    let mut counter: Vec<Lit> = lits.first().into_iter().copied().take(limit).collect();
+    for &lit in lits.iter().skip(1) {
+        let next: Vec<Lit> = (0..limit.min(counter.len() + 1))
+            .map(|_| new_var())
+            .collect();
+        // Writing `s_j` for the previous counter, where `s_0` is true and `s_j` beyond the
+        // previous prefix is false, the next counter is `s_j | (s_(j - 1) & lit)`
+        for (j, &output) in next.iter().enumerate() {
+            let previous = counter.get(j);
+            let below = j.checked_sub(1).map(|j| counter[j]);
+
+            let mut clause = vec![!lit, output];
+            clause.extend(below.map(|below| !below));
+            add_clause(&clause);
+            if let Some(&previous) = previous {
+                add_clause(&[!previous, output]);
+            }
+
+            let mut clause = vec![!output, lit];
+            clause.extend(previous);
+            add_clause(&clause);
+            if let Some(below) = below {
+                let mut clause = vec![!output, below];
+                clause.extend(previous);
+                add_clause(&clause);
+            }
+        }
+        counter = next;
+    }
+    counter
+}
+
+/// Sorts the literals in descending order using Batcher's odd-even merge sort
+///
+/// The input is padded to a power of two with false, which comparators just pass through.
+fn sorting_network(
+    lits: &[Lit],
+    limit: usize,
+    new_var: &mut impl FnMut() -> Lit,
+    add_clause: &mut impl FnMut(&[Lit]),
+) -> Vec<Lit> {
+    let size = lits.len().next_power_of_two();
+    let mut wires: Vec<Option<Lit>> = lits.iter().copied().map(Some).collect();
+    wires.resize(size, None);
+
+    let mut compare = |wires: &mut Vec<Option<Lit>>, high: usize, low: usize| {
+        if let (Some(a), Some(b)) = (wires[high], wires[low]) {
+            let (max, min) = (new_var(), new_var());
+            // max = a | b
+            add_clause(&[!a, max]);
+            add_clause(&[!b, max]);
+            add_clause(&[!max, a, b]);
+            // min = a & b
+            add_clause(&[!min, a]);
+            add_clause(&[!min, b]);
+            add_clause(&[!a, !b, min]);
+            wires[high] = Some(max);
+            wires[low] = Some(min);
+        } else if wires[high].is_none() {
+            wires.swap(high, low);
+        }
+    };
+
+    let mut merge_size = 1;
+    while merge_size < size {
+        let mut step = merge_size;
+        while step >= 1 {
+            let mut start = step % merge_size;
+            while start + step < size {
+                for i in 0..step.min(size - start - step) {
+                    if (i + start) / (2 * merge_size) == (i + start + step) / (2 * merge_size) {
+                        compare(&mut wires, i + start, i + start + step);
+                    }
+                }
+                start += 2 * step;
+            }
+            step /= 2;
+        }
+        merge_size *= 2;
+    }
+
+    wires.into_iter().flatten().take(limit).collect()
+}
@@ -0,0 +1,246 @@
+//! Certificates for the minimality of reported models
+//!
+//! A certificate replays an enumeration. It is a text file starting with a header line, followed
+//! by these lines, each terminated by a `0`:
+//!
+//! * `b <clause>`: A blocking clause added from a checkpoint.
+//! * `m <n> <model>`: A reported model of the first `n` input clauses together with all blocking
+//!   clauses added so far. Its blocking clause is added afterwards.
+//! * `e <lit> <clause>`: A witness for the literal `lit` of the preceding model being essential,
+//!   i.e. a clause of the formula in which `lit` is the only literal assigned by the model.
+//! * `u <lit>`: The literal `lit` of the preceding model is unverified, so it may not be minimal.
+//!
+//! Literals not in the reported model need no individual justification, as the model entailing
+//! the formula, which is checked clause by clause, justifies dropping all of them.
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+
+use crate::verify::{assignment, check_entailment, is_tautology, only_assigned, witnesses};
+
+const HEADER: &str = "c minimal_models certificate";
+
+/// Writes a certificate while models are reported
+pub struct CertificateWriter {
+    file: io::BufWriter<fs::File>,
+}
+
+impl CertificateWriter {
+    pub fn create(path: &Path) -> anyhow::Result<CertificateWriter> {
+        let file = fs::File::create(path)
+            .with_context(|| format!("creating certificate {}", path.display()))?;
+        let mut writer = CertificateWriter {
+            file: io::BufWriter::new(file),
+        };
+        writeln!(writer.file, "{}", HEADER)?;
+        Ok(writer)
+    }
+
+    /// Records a blocking clause added from a checkpoint
+    pub fn blocked(&mut self, clause: &[isize]) -> anyhow::Result<()> {
+        write!(self.file, "b ")?;
+        write_lits(&mut self.file, clause)?;
+        Ok(())
+    }
+
+    /// Records a reported model, with witnesses found in `clauses`, the formula it is a model of
+    ///
+    /// The formula consists of the first `input_clauses` input clauses and the blocking clauses in
+    /// the order they were added.
+    pub fn model(
+        &mut self,
+        input_clauses: usize,
+        clauses: &[Vec<isize>],
+        model: &[isize],
+    ) -> anyhow::Result<()> {
+        write!(self.file, "m {} ", input_clauses)?;
+        write_lits(&mut self.file, model)?;
+        for (&lit, witness) in model.iter().zip(witnesses(clauses, model)?) {
+            if let Some(witness) = witness {
+                write!(self.file, "e {} ", lit)?;
+                write_lits(&mut self.file, witness)?;
+            } else {
+                writeln!(self.file, "u {} 0", lit)?;
+            }
+        }
+        // Make sure everything reported so far is covered in case we crash later
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+fn write_lits(file: &mut impl Write, lits: &[isize]) -> io::Result<()> {
+    for lit in lits {
+        write!(file, "{} ", lit)?;
+    }
+    writeln!(file, "0")
+}
+
+/// Result of checking a certificate
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Checked {
+    /// Number of models proven to be minimal
+    pub minimal: usize,
+    /// Number of models proven to be models, but which have unverified literals
+    pub unverified: usize,
+}
+
+/// Formula as it was at some point of the enumeration
+#[derive(Default)]
+struct Formula {
+    clauses: Vec<Vec<isize>>,
+    /// Sorted copies of `clauses`, to look up witnesses
+    sorted: HashSet<Vec<isize>>,
+}
+
+impl Formula {
+    fn add_clause(&mut self, clause: &[isize]) {
+        let mut sorted = clause.to_vec();
+        sorted.sort_unstable();
+        self.sorted.insert(sorted);
+        self.clauses.push(clause.to_vec());
+    }
+
+    fn contains(&self, clause: &[isize]) -> bool {
+        let mut sorted = clause.to_vec();
+        sorted.sort_unstable();
+        self.sorted.contains(&sorted)
+    }
+}
+
+/// A model of the certificate and what we've checked about its literals so far
+struct PendingModel {
+    line: usize,
+    model: Vec<isize>,
+    justified: HashSet<isize>,
+    unverified: bool,
+}
+
+/// Checks a certificate for the enumeration of models of `input`
+pub fn check(input: &[Vec<isize>], certificate: &str) -> anyhow::Result<Checked> {
+    let mut lines = certificate.lines().enumerate();
+    if lines.next().map(|(_, line)| line) != Some(HEADER) {
+        anyhow::bail!("not a certificate");
+    }
+
+    let mut formula = Formula::default();
+    let mut input_clauses = 0;
+    let mut pending: Option<PendingModel> = None;
+    let mut checked = Checked::default();
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let mut tokens = line.split_ascii_whitespace();
+        let kind = match tokens.next() {
+            Some(kind) => kind,
+            None => continue,
+        };
+        if kind == "c" {
+            continue;
+        }
+        let mut numbers = vec![];
+        for token in tokens {
+            let number = token
+                .parse::<isize>()
+                .with_context(|| format!("line {} of certificate", line_number))?;
+            if number == 0 {
+                break;
+            }
+            numbers.push(number);
+        }
+
+        match kind {
+            "m" | "b" => {
+                if let Some(model) = pending.take() {
+                    finish_model(&mut formula, model, &mut checked)?;
+                }
+            }
+            _ => (),
+        }
+
+        match kind {
+            "b" => formula.add_clause(&numbers),
+            "m" => {
+                if numbers.is_empty() || numbers[0] < input_clauses as isize {
+                    anyhow::bail!("line {}: invalid number of input clauses", line_number);
+                }
+                let count = numbers[0] as usize;
+                if count > input.len() {
+                    anyhow::bail!("line {}: input has fewer clauses", line_number);
+                }
+                for clause in &input[input_clauses..count] {
+                    formula.add_clause(clause);
+                }
+                input_clauses = count;
+
+                let model = numbers[1..].to_vec();
+                check_entailment(&formula.clauses, &model)
+                    .with_context(|| format!("line {} of certificate", line_number))?;
+                pending = Some(PendingModel {
+                    line: line_number,
+                    model,
+                    justified: HashSet::new(),
+                    unverified: false,
+                });
+            }
+            "e" | "u" => {
+                let model = pending.as_mut().ok_or_else(|| {
+                    anyhow::anyhow!("line {}: literal without a model", line_number)
+                })?;
+                let lit = *numbers
+                    .first()
+                    .ok_or_else(|| anyhow::anyhow!("line {}: missing literal", line_number))?;
+                if !model.model.contains(&lit) {
+                    anyhow::bail!("line {}: {} is not part of the model", line_number, lit);
+                }
+                if kind == "e" {
+                    let witness = &numbers[1..];
+                    let assignment = assignment(&model.model)?;
+                    if !formula.contains(witness)
+                        || is_tautology(witness)
+                        || only_assigned(&assignment, witness) != Some(lit)
+                    {
+                        anyhow::bail!("line {}: invalid witness for {}", line_number, lit);
+                    }
+                } else {
+                    model.unverified = true;
+                }
+                model.justified.insert(lit);
+            }
+            _ => anyhow::bail!("line {}: unknown line type {:?}", line_number, kind),
+        }
+    }
+
+    if let Some(model) = pending.take() {
+        finish_model(&mut formula, model, &mut checked)?;
+    }
+
+    Ok(checked)
+}
+
+fn finish_model(
+    formula: &mut Formula,
+    model: PendingModel,
+    checked: &mut Checked,
+) -> anyhow::Result<()> {
+    if let Some(lit) = model
+        .model
+        .iter()
+        .find(|lit| !model.justified.contains(lit))
+    {
+        anyhow::bail!("line {}: no witness for {}", model.line, lit);
+    }
+    if model.unverified {
+        checked.unverified += 1;
+    } else {
+        checked.minimal += 1;
+    }
+    let blocking: Vec<isize> = model.model.iter().map(|&lit| -lit).collect();
+    formula.add_clause(&blocking);
+    Ok(())
+}
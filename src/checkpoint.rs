@@ -0,0 +1,85 @@
+//! Checkpoints of an enumeration, allowing to resume it without reporting models again
+//!
+//! A checkpoint is stored in DIMACS like format, listing the blocking clauses added so far.
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+const HEADER: &str = "c minimal_models checkpoint";
+
+/// Blocking clauses of all models reported so far
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Checkpoint {
+    pub blocked: Vec<Vec<isize>>,
+}
+
+impl Checkpoint {
+    /// Number of models reported so far
+    pub fn models(&self) -> usize {
+        self.blocked.len()
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Checkpoint> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("reading checkpoint {}", path.display()))?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            anyhow::bail!("{} is not a checkpoint", path.display());
+        }
+
+        let mut checkpoint = Checkpoint::default();
+        for line in lines {
+            if line.starts_with('c') {
+                continue;
+            }
+            let mut clause = vec![];
+            let mut terminated = false;
+            for lit_str in line.split_ascii_whitespace() {
+                let lit_val = str::parse::<isize>(lit_str)?;
+                if lit_val == 0 {
+                    terminated = true;
+                    break;
+                }
+                clause.push(lit_val);
+            }
+            // A truncated last line can only stem from a crash during a non-atomic copy, as we
+            // always replace the whole file
+            if !terminated {
+                anyhow::bail!("truncated checkpoint {}", path.display());
+            }
+            checkpoint.blocked.push(clause);
+        }
+        Ok(checkpoint)
+    }
+
+    /// Writes the checkpoint, atomically replacing any existing file at `path`
+    ///
+    /// The checkpoint is written to a temporary file next to `path` which is renamed after being
+    /// synced, so a crash leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let write = || -> io::Result<()> {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            writeln!(file, "{}", HEADER)?;
+            writeln!(file, "c models {}", self.models())?;
+            for clause in &self.blocked {
+                for lit in clause {
+                    write!(file, "{} ", lit)?;
+                }
+                writeln!(file, "0")?;
+            }
+            file.into_inner()
+                .map_err(|err| err.into_error())?
+                .sync_all()?;
+            fs::rename(&tmp_path, path)
+        };
+        write().with_context(|| format!("writing checkpoint {}", path.display()))
+    }
+}
@@ -0,0 +1,214 @@
+//! Configuration files and environment variables for the command line tool
+//!
+//! A configuration file uses a subset of TOML: each line is empty, a `#` comment or a `key =
+//! value` pair, where the value is a string, a number, a boolean or a single line array of these.
+//! Tables aren't supported. Every key is the name of a command line option without the leading
+//! `--`, so the file translates into command line arguments: `true` becomes a flag, `false` is
+//! left out, arrays repeat the option for each element and anything else is passed as the option's
+//! value. Only flags take booleans, and only other options take strings and numbers.
+//!
+//! Environment variables named `MINIMAL_MODELS_` followed by the name of an option in upper case,
+//! with `-` replaced by `_`, are translated in the same way, except that their value is never an
+//! array and isn't quoted, and flags also accept `1`, `0`, `yes` and `no`.
+
+/// Prefix of the environment variables setting options
+pub const ENV_PREFIX: &str = "MINIMAL_MODELS_";
+
+/// What follows an option on the command line
+#[derive(Copy, Clone)]
+pub enum OptionValue {
+    /// The option is a flag
+    None,
+    /// Any value, with the name shown in the usage
+    Any(&'static str),
+    File,
+    Choice(&'static [&'static str]),
+}
+
+/// Looks up what follows `option` in `options`, which lists each option with what follows it and
+/// its help
+fn lookup(options: &[(&str, OptionValue, &str)], option: &str) -> Option<OptionValue> {
+    options
+        .iter()
+        .find(|(name, _, _)| *name == option)
+        .map(|(_, value, _)| *value)
+}
+
+/// Translates the environment variables starting with [`ENV_PREFIX`] into command line arguments
+///
+/// Each variable has to name one of `options`. Flags are set by `true`, `1` or `yes` and left out
+/// for `false`, `0` or `no`. The arguments are sorted by option, so they don't depend on the order
+/// of `vars`.
+pub fn env_args(
+    vars: impl Iterator<Item = (String, String)>,
+    options: &[(&str, OptionValue, &str)],
+) -> anyhow::Result<Vec<String>> {
+    let mut vars: Vec<(String, String, String)> = vars
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?;
+            let option = format!("--{}", key.to_ascii_lowercase().replace('_', "-"));
+            Some((option, value, name))
+        })
+        .collect();
+    vars.sort();
+
+    let mut args = vec![];
+    for (option, value, name) in vars {
+        let kind = match lookup(options, &option) {
+            Some(kind) => kind,
+            None => anyhow::bail!("environment variable {}: unknown option {}", name, option),
+        };
+        match kind {
+            OptionValue::None => match &value.to_ascii_lowercase()[..] {
+                "true" | "1" | "yes" => args.push(option),
+                "false" | "0" | "no" => (),
+                _ => anyhow::bail!(
+                    "environment variable {}: {} is a flag, expected true, false, 1, 0, yes or no, not {:?}",
+                    name,
+                    option,
+                    value
+                ),
+            },
+            _ => {
+                args.push(option);
+                args.push(value);
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Translates the contents of a configuration file into command line arguments
+///
+/// Each key has to name one of `options`, with a boolean value exactly for flags. Errors start
+/// with `name` and the line number.
+pub fn parse(
+    text: &str,
+    name: &str,
+    options: &[(&str, OptionValue, &str)],
+) -> anyhow::Result<Vec<String>> {
+    let mut args = vec![];
+    for (index, line) in text.lines().enumerate() {
+        if let Err(err) = parse_line(line, options, &mut args) {
+            anyhow::bail!("{}:{}: {:#}", name, index + 1, err);
+        }
+    }
+    Ok(args)
+}
+
+fn parse_line(
+    line: &str,
+    options: &[(&str, OptionValue, &str)],
+    args: &mut Vec<String>,
+) -> anyhow::Result<()> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+    if line.starts_with('[') {
+        anyhow::bail!("tables aren't supported");
+    }
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected key = value"))?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("invalid key {:?}", key);
+    }
+    let option = format!("--{}", key);
+    let kind = match lookup(options, &option) {
+        Some(kind) => kind,
+        None => anyhow::bail!("unknown option {}", key),
+    };
+
+    let mut rest = value.trim_start();
+    let values = if let Some(array) = rest.strip_prefix('[') {
+        rest = array;
+        let mut values = vec![];
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                rest = after;
+                break;
+            }
+            let (value, after) = parse_value(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                anyhow::bail!("expected , or ] in array");
+            }
+        }
+        values
+    } else {
+        let (value, after) = parse_value(rest)?;
+        rest = after;
+        vec![value]
+    };
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        anyhow::bail!("unexpected {:?} after the value", rest);
+    }
+
+    for value in values {
+        match (kind, value) {
+            (OptionValue::None, Value::Bool(true)) => args.push(option.clone()),
+            (OptionValue::None, Value::Bool(false)) => (),
+            (OptionValue::None, Value::Other(value)) => {
+                anyhow::bail!("{} is a flag, expected true or false, not {}", key, value)
+            }
+            (_, Value::Bool(_)) => anyhow::bail!("{} takes a value, not a boolean", key),
+            (_, Value::Other(value)) => {
+                args.push(option.clone());
+                args.push(value);
+            }
+        }
+    }
+    Ok(())
+}
+
+enum Value {
+    Bool(bool),
+    /// A string or a number, which is passed on as is
+    Other(String),
+}
+
+/// Parses a value at the start of `text`, returning it together with the remaining text
+fn parse_value(text: &str) -> anyhow::Result<(Value, &str)> {
+    if let Some(string) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = string.char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::Other(value), &string[index + 1..])),
+                '\\' => match chars.next() {
+                    Some((_, '"')) => value.push('"'),
+                    Some((_, '\\')) => value.push('\\'),
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    _ => anyhow::bail!("unsupported escape sequence in string"),
+                },
+                _ => value.push(c),
+            }
+        }
+        anyhow::bail!("unterminated string");
+    }
+
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == ',' || c == ']' || c == '#')
+        .unwrap_or(text.len());
+    let (token, rest) = text.split_at(end);
+    let value = match token {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        // TOML allows underscores between digits
+        _ if token.replace('_', "").parse::<f64>().is_ok() => Value::Other(token.replace('_', "")),
+        _ => anyhow::bail!("invalid value {:?}", token),
+    };
+    Ok((value, rest))
+}
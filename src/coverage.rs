@@ -0,0 +1,195 @@
+//! The fraction of the models of a formula covered by the reported minimal models
+//!
+//! A full model is covered when it extends a reported model, i.e. when it violates that model's
+//! blocking clause. So the coverage is one minus the ratio of the models of the formula together
+//! with the blocking clauses to the models of the formula alone. Both are counted exactly by a
+//! DPLL style model counter, which splits the clauses into independent components and caches the
+//! count of each component. As model counting is much harder than finding a model, the counter
+//! gives up after a fixed number of decisions.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Decisions after which [`count_models`] gives up
+pub const DEFAULT_BUDGET: usize = 100_000;
+
+/// Tracks the clauses of a formula and the blocking clauses of its reported models
+#[derive(Clone, Default, Debug)]
+pub struct Coverage {
+    clauses: Vec<Vec<isize>>,
+    blocking: Vec<Vec<isize>>,
+    /// The number of models of `clauses`, once counted, or `None` if the counter gave up
+    models: Option<Option<f64>>,
+}
+
+impl Coverage {
+    /// Adds a clause of the formula
+    pub fn add_clause(&mut self, clause: &[isize]) {
+        self.clauses.push(clause.to_vec());
+        self.models = None;
+    }
+
+    /// Adds the blocking clause of a reported model
+    pub fn add_blocking(&mut self, clause: &[isize]) {
+        self.blocking.push(clause.to_vec());
+    }
+
+    /// The fraction of the models of the formula, over its variables, that extend a reported
+    /// model
+    ///
+    /// Returns `None` if counting the models exceeds `budget` decisions or if the formula has no
+    /// models.
+    pub fn fraction(&mut self, budget: usize) -> Option<f64> {
+        let clauses = &self.clauses;
+        let models = (*self
+            .models
+            .get_or_insert_with(|| count_models(clauses, budget)))?;
+        if models == 0.0 {
+            return None;
+        }
+        let mut all = self.clauses.clone();
+        all.extend(self.blocking.iter().cloned());
+        // Blocking clauses may mention variables the formula doesn't, which the formula's models
+        // leave free
+        let extra = vars(&all).len() - vars(&self.clauses).len();
+        let uncovered = count_models(&all, budget)? / 2f64.powi(extra as i32);
+        Some(1.0 - uncovered / models)
+    }
+}
+
+/// Counts the assignments to the variables of `clauses` that satisfy them
+///
+/// Returns `None` if this takes more than `budget` decisions. The count is exact as long as it is
+/// below `2^53`.
+pub fn count_models(clauses: &[Vec<isize>], budget: usize) -> Option<f64> {
+    let mut normalized = vec![];
+    for clause in clauses {
+        let mut clause = clause.clone();
+        clause.sort_unstable();
+        clause.dedup();
+        if !clause.iter().any(|lit| clause.contains(&-lit)) {
+            normalized.push(clause);
+        }
+    }
+    // Variables only occurring in tautologies are free
+    let free = vars(clauses).len() - vars(&normalized).len();
+    let mut counter = Counter {
+        budget,
+        cache: HashMap::new(),
+    };
+    Some(counter.count(normalized)? * 2f64.powi(free as i32))
+}
+
+fn vars(clauses: &[Vec<isize>]) -> BTreeSet<isize> {
+    clauses.iter().flatten().map(|lit| lit.abs()).collect()
+}
+
+struct Counter {
+    budget: usize,
+    /// Counts of components by their sorted clauses
+    cache: HashMap<Vec<Vec<isize>>, f64>,
+}
+
+impl Counter {
+    /// Counts the assignments to the variables of `clauses`, which contain no tautologies or
+    /// duplicate literals
+    fn count(&mut self, mut clauses: Vec<Vec<isize>>) -> Option<f64> {
+        if clauses.is_empty() {
+            return Some(1.0);
+        }
+        if clauses.iter().any(Vec::is_empty) {
+            return Some(0.0);
+        }
+        clauses.sort_unstable();
+        clauses.dedup();
+        if let Some(&count) = self.cache.get(&clauses) {
+            return Some(count);
+        }
+
+        let components = components(&clauses);
+        let count = if components.len() > 1 {
+            let mut count = 1.0;
+            for component in components {
+                count *= self.count(component)?;
+                if count == 0.0 {
+                    break;
+                }
+            }
+            count
+        } else {
+            if self.budget == 0 {
+                return None;
+            }
+            self.budget -= 1;
+            let var = branching_var(&clauses);
+            let vars = vars(&clauses).len();
+            let mut count = 0.0;
+            for lit in [var, -var] {
+                let assigned = assign(&clauses, lit);
+                // Variables only occurring in satisfied clauses are free
+                let free = vars - 1 - self::vars(&assigned).len();
+                count += self.count(assigned)? * 2f64.powi(free as i32);
+            }
+            count
+        };
+        self.cache.insert(clauses, count);
+        Some(count)
+    }
+}
+
+/// Splits `clauses` into groups without common variables
+fn components(clauses: &[Vec<isize>]) -> Vec<Vec<Vec<isize>>> {
+    let mut component_of_var: HashMap<isize, usize> = HashMap::new();
+    // Union-find over the clauses, joining those with a common variable
+    let mut parent: Vec<usize> = (0..clauses.len()).collect();
+    fn root(parent: &mut [usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+    for (index, clause) in clauses.iter().enumerate() {
+        for lit in clause {
+            let other = *component_of_var.entry(lit.abs()).or_insert(index);
+            let (a, b) = (root(&mut parent, index), root(&mut parent, other));
+            parent[a] = b;
+        }
+    }
+    let mut components: BTreeMap<usize, Vec<Vec<isize>>> = BTreeMap::new();
+    for (index, clause) in clauses.iter().enumerate() {
+        let root = root(&mut parent, index);
+        components.entry(root).or_default().push(clause.clone());
+    }
+    components.into_values().collect()
+}
+
+/// The variable of a unit clause if there is one, as its other branch fails immediately, and
+/// otherwise the variable occurring most often
+fn branching_var(clauses: &[Vec<isize>]) -> isize {
+    if let Some(unit) = clauses.iter().find(|clause| clause.len() == 1) {
+        return unit[0].abs();
+    }
+    let mut occurrences: HashMap<isize, usize> = HashMap::new();
+    for lit in clauses.iter().flatten() {
+        *occurrences.entry(lit.abs()).or_default() += 1;
+    }
+    occurrences
+        .into_iter()
+        .max_by_key(|&(var, count)| (count, -var))
+        .unwrap()
+        .0
+}
+
+/// The clauses remaining when `lit` is true
+fn assign(clauses: &[Vec<isize>], lit: isize) -> Vec<Vec<isize>> {
+    clauses
+        .iter()
+        .filter(|clause| !clause.contains(&lit))
+        .map(|clause| {
+            clause
+                .iter()
+                .copied()
+                .filter(|&other| other != -lit)
+                .collect()
+        })
+        .collect()
+}
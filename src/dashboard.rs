@@ -0,0 +1,220 @@
+//! Live terminal dashboard of the command line tool's `--tui`
+//!
+//! The dashboard is redrawn periodically on a separate thread, showing the accumulated statistics,
+//! the progress of the running reduction and the most recent events. It uses plain ANSI escape
+//! sequences, so it works on any terminal without additional dependencies.
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{Progress, Stats};
+
+/// Width of the dashboard in columns
+const WIDTH: usize = 80;
+
+/// Number of lines of the essential and candidate panels
+const LIT_LINES: usize = 3;
+
+/// Number of events shown in the log panel
+const LOG_LINES: usize = 8;
+
+#[derive(Default)]
+struct State {
+    queries: usize,
+    models: usize,
+    stats: Stats,
+    /// Of the running reduction
+    progress: Option<Progress>,
+    log: VecDeque<String>,
+}
+
+/// Shared state of a dashboard, cheap to clone
+#[derive(Clone)]
+pub struct Dashboard {
+    start: Instant,
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Dashboard {
+            start: Instant::now(),
+            state: Arc::default(),
+        }
+    }
+}
+
+impl Dashboard {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        // The state stays consistent even if a panic occurred while it was locked
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Updates the running reduction, to be passed to
+    /// [`Builder::on_progress`](crate::Builder::on_progress)
+    pub fn progress(&self, progress: &Progress) {
+        self.state().progress = Some(progress.clone());
+    }
+
+    /// Updates the statistics, ending the running reduction
+    pub fn update(&self, queries: usize, models: usize, stats: &Stats) {
+        let mut state = self.state();
+        state.queries = queries;
+        state.models = models;
+        state.stats = stats.clone();
+        state.progress = None;
+    }
+
+    /// Adds an event to the log panel
+    pub fn log(&self, line: impl Into<String>) {
+        let mut state = self.state();
+        if state.log.len() == LOG_LINES {
+            state.log.pop_front();
+        }
+        state.log.push_back(line.into());
+    }
+
+    /// Writes the dashboard to `out`, starting at the top left of a cleared screen
+    pub fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        let elapsed = self.start.elapsed().as_secs();
+        let state = self.state();
+        let mut lines = vec![format!(
+            "minimal_models, running for {}:{:02}:{:02}",
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60
+        )];
+
+        lines.push(header("stats"));
+        let stats = &state.stats;
+        lines.push(format!(
+            "queries {}, models {}, full model searches {}, reductions {}",
+            state.queries, state.models, stats.solves, stats.reductions
+        ));
+        lines.push(format!(
+            "checks {} (sat {}, unsat {}, unknown {})",
+            stats.checks.total(),
+            stats.checks.sat,
+            stats.checks.unsat,
+            stats.checks.unknown
+        ));
+        lines.push(format!(
+            "solve time {:.1}s, reduce time {:.1}s",
+            stats.solve_time.as_secs_f64(),
+            stats.reduce_time.as_secs_f64()
+        ));
+
+        lines.push(header("reduction"));
+        match &state.progress {
+            Some(progress) => {
+                let checks = &progress.checks;
+                lines.push(format!(
+                    "essential {}, candidates {}, checks {} (sat {}, unsat {}, unknown {})",
+                    progress.essential.len(),
+                    progress.candidates.len(),
+                    checks.total(),
+                    checks.sat,
+                    checks.unsat,
+                    checks.unknown
+                ));
+                lines.push(header("candidates, next first"));
+                lits(&mut lines, &progress.candidates);
+                lines.push(header("essential"));
+                lits(&mut lines, &progress.essential);
+            }
+            None => lines.push("idle".to_owned()),
+        }
+
+        lines.push(header("log"));
+        lines.extend(state.log.iter().cloned());
+        drop(state);
+
+        // Move to the top left and clear the screen
+        write!(out, "\x1b[H\x1b[2J")?;
+        for line in &lines {
+            writeln!(out, "{}", truncate(line, WIDTH))?;
+        }
+        out.flush()
+    }
+
+    /// Spawns a thread redrawing the dashboard on stderr every `interval`, until the returned
+    /// [`Renderer`] is dropped
+    pub fn spawn(&self, interval: Duration) -> Renderer {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (dashboard, stop) = (self.clone(), stop.clone());
+            thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    // The dashboard is purely informational, so write errors are ignored
+                    let _ = dashboard.render(&mut io::stderr().lock());
+                    thread::sleep(interval);
+                }
+            })
+        };
+        Renderer {
+            dashboard: self.clone(),
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Stops redrawing the dashboard when dropped, after drawing its final state
+pub struct Renderer {
+    dashboard: Dashboard,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let _ = self.dashboard.render(&mut io::stderr().lock());
+    }
+}
+
+fn header(title: &str) -> String {
+    format!(
+        "-- {} {}",
+        title,
+        "-".repeat(WIDTH.saturating_sub(title.len() + 4))
+    )
+}
+
+/// Adds `lits` to `lines`, wrapped to the width and ending in `...` if they don't fit
+fn lits(lines: &mut Vec<String>, lits: &[isize]) {
+    let mut line = String::new();
+    let mut count = 0;
+    for lit in lits {
+        let lit = lit.to_string();
+        // Leaving room for the `...`
+        if line.len() + lit.len() + 4 > WIDTH {
+            if count + 1 == LIT_LINES {
+                line.push_str("...");
+                break;
+            }
+            lines.push(std::mem::take(&mut line));
+            count += 1;
+        }
+        line.push_str(&lit);
+        line.push(' ');
+    }
+    lines.push(line);
+}
+
+fn truncate(line: &str, width: usize) -> &str {
+    match line.char_indices().nth(width) {
+        Some((end, _)) => &line[..end],
+        None => line,
+    }
+}
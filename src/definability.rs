@@ -0,0 +1,126 @@
+//! Variables implicitly defined by other variables, for `analyze defined`
+//!
+//! A variable `y` is defined by a basis of variables when all models agreeing on the basis agree
+//! on `y`. Padoa's method checks this with two copies of the formula sharing nothing: `y` is
+//! defined iff no two models, one of each copy, agree on the basis but not on `y`. One solver
+//! holds both copies, with a selector for each variable that makes it equal in both copies when
+//! assumed, so every check is a single call under assumptions, like the checks of the negative
+//! solver.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    backend::{Lbool, Lit, SatBackend},
+    solver_config::SolverConfig,
+    SolverOptions,
+};
+
+/// Both copies of a formula with the selectors, checking definability
+struct Padoa {
+    solver: Box<dyn SatBackend>,
+    /// Index of each variable, whose copies and selector are `3 * index`, `3 * index + 1` and
+    /// `3 * index + 2`
+    vars: BTreeMap<isize, u32>,
+}
+
+impl Padoa {
+    fn new(clauses: &[Vec<isize>], options: &SolverOptions) -> anyhow::Result<Padoa> {
+        let mut solver = SolverConfig::new(options)?.new_solver();
+        let vars: BTreeMap<isize, u32> = clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.abs())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .zip(0..)
+            .collect();
+        // cryptominisat can't solve without variables
+        solver.new_vars(3 * vars.len().max(1));
+        let mut padoa = Padoa { solver, vars };
+        for copy in 0..2 {
+            for clause in clauses {
+                let clause: Vec<Lit> = clause.iter().map(|&lit| padoa.lit(lit, copy)).collect();
+                padoa.solver.add_clause(&clause);
+            }
+        }
+        let vars: Vec<isize> = padoa.vars.keys().copied().collect();
+        for var in vars {
+            let (a, b, selector) = (padoa.lit(var, 0), padoa.lit(var, 1), padoa.selector(var));
+            padoa.solver.add_clause(&[!selector, !a, b]);
+            padoa.solver.add_clause(&[!selector, a, !b]);
+        }
+        Ok(padoa)
+    }
+
+    /// The literal `lit` in the first (`0`) or second (`1`) copy
+    fn lit(&self, lit: isize, copy: u32) -> Lit {
+        Lit::new(3 * self.vars[&lit.abs()] + copy, lit < 0).unwrap()
+    }
+
+    fn selector(&self, var: isize) -> Lit {
+        Lit::new(3 * self.vars[&var] + 2, false).unwrap()
+    }
+
+    /// Whether `var` is defined by the variables of `basis`, or `None` if the solver gave up
+    fn is_defined(&mut self, var: isize, basis: &BTreeSet<isize>) -> Option<bool> {
+        let mut assumptions: Vec<Lit> = basis.iter().map(|&other| self.selector(other)).collect();
+        assumptions.extend([self.lit(var, 0), self.lit(-var, 1)]);
+        match self.solver.solve_with_assumptions(&assumptions) {
+            Lbool::True => Some(false),
+            Lbool::False => Some(true),
+            Lbool::Undef => None,
+        }
+    }
+}
+
+/// The variables of `clauses` outside of `basis` that are defined by the variables of `basis`
+///
+/// Variables of `basis` not occurring in `clauses` are ignored. Returns `None` if the solver gave
+/// up.
+pub fn defined_vars(
+    clauses: &[Vec<isize>],
+    basis: &[isize],
+    options: &SolverOptions,
+) -> anyhow::Result<Option<Vec<isize>>> {
+    let mut padoa = Padoa::new(clauses, options)?;
+    let basis: BTreeSet<isize> = basis
+        .iter()
+        .map(|lit| lit.abs())
+        .filter(|var| padoa.vars.contains_key(var))
+        .collect();
+    let mut defined = vec![];
+    for var in padoa.vars.keys().copied().collect::<Vec<_>>() {
+        if basis.contains(&var) {
+            continue;
+        }
+        match padoa.is_defined(var, &basis) {
+            Some(true) => defined.push(var),
+            Some(false) => (),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(defined))
+}
+
+/// A basis defining all variables of `clauses`, from which no variable can be removed
+///
+/// Starting with all variables, each variable, from the last to the first, is removed if the
+/// remaining ones define it. So earlier variables are preferred for the basis. Returns `None` if
+/// the solver gave up.
+pub fn independent_support(
+    clauses: &[Vec<isize>],
+    options: &SolverOptions,
+) -> anyhow::Result<Option<Vec<isize>>> {
+    let mut padoa = Padoa::new(clauses, options)?;
+    let mut basis: BTreeSet<isize> = padoa.vars.keys().copied().collect();
+    for var in basis.clone().into_iter().rev() {
+        basis.remove(&var);
+        match padoa.is_defined(var, &basis) {
+            Some(true) => (),
+            Some(false) => {
+                basis.insert(var);
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(basis.into_iter().collect()))
+}
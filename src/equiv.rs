@@ -0,0 +1,89 @@
+use crate::{backend::Lit, order::lit_index};
+
+fn index_lit(index: usize) -> Lit {
+    Lit::new((index >> 1) as u32, index & 1 != 0).unwrap()
+}
+
+/// Finds the classes of equivalent literals implied by the binary clauses
+///
+/// These are the strongly connected components of the binary implication graph with at least two
+/// literals. As every component has a complementary component containing the negated literals,
+/// only the one containing a positive literal of its smallest variable is returned.
+pub fn equivalence_classes(binary_clauses: &[[Lit; 2]]) -> Vec<Vec<Lit>> {
+    let nodes = binary_clauses
+        .iter()
+        .flatten()
+        .map(|&lit| (lit_index(lit) | 1) + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut edges = vec![vec![]; nodes];
+    for &[a, b] in binary_clauses {
+        edges[lit_index(!a)].push(lit_index(b));
+        edges[lit_index(!b)].push(lit_index(a));
+    }
+
+    // Iterative version of Tarjan's algorithm
+    const UNVISITED: usize = usize::MAX;
+    let mut preorder = vec![UNVISITED; nodes];
+    let mut lowlink = vec![0; nodes];
+    let mut on_stack = vec![false; nodes];
+    let mut stack = vec![];
+    let mut counter = 0;
+    let mut classes = vec![];
+
+    for root in 0..nodes {
+        if preorder[root] != UNVISITED {
+            continue;
+        }
+        // Pairs of node and the position of the next edge to follow
+        let mut dfs = vec![(root, 0)];
+        preorder[root] = counter;
+        lowlink[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(node, edge)) = dfs.last() {
+            if let Some(&next) = edges[node].get(edge) {
+                dfs.last_mut().unwrap().1 += 1;
+                if preorder[next] == UNVISITED {
+                    preorder[next] = counter;
+                    lowlink[next] = counter;
+                    counter += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    dfs.push((next, 0));
+                } else if on_stack[next] {
+                    lowlink[node] = lowlink[node].min(preorder[next]);
+                }
+            } else {
+                dfs.pop();
+                if let Some(&(parent, _)) = dfs.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                }
+                if lowlink[node] == preorder[node] {
+                    let mut class = vec![];
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        class.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    let smallest = *class.iter().min().unwrap();
+                    // A class containing both polarities of a variable means the formula is
+                    // unsatisfiable, which isn't our business here
+                    let contradictory = class.iter().any(|&member| class.contains(&(member ^ 1)));
+                    if class.len() > 1 && smallest & 1 == 0 && !contradictory {
+                        class.sort_unstable();
+                        classes.push(class.into_iter().map(index_lit).collect());
+                    }
+                }
+            }
+        }
+    }
+
+    classes
+}
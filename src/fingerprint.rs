@@ -0,0 +1,45 @@
+//! Order independent hashes of the reported models, to detect changes in behavior
+use std::fmt;
+
+/// Hash of a multiset of partial assignments
+///
+/// The hash doesn't depend on the order of the assignments or of their literals, nor on the
+/// platform or the version of Rust, so it can be compared across versions of this tool.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Fingerprint {
+    count: u64,
+    sum: u64,
+}
+
+impl Fingerprint {
+    pub fn add(&mut self, model: &[isize]) {
+        let mut lits = model.to_vec();
+        lits.sort_unstable();
+        // FNV-1a over the literals as 64-bit little endian integers
+        let mut hash = 0xcbf29ce484222325u64;
+        for lit in lits {
+            for byte in (lit as i64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        // The splitmix64 finalizer makes the sum sensitive to all bits of each hash
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+        hash ^= hash >> 31;
+
+        self.count += 1;
+        self.sum = self.sum.wrapping_add(hash);
+    }
+
+    /// Number of assignments added so far
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:016x}", self.count, self.sum)
+    }
+}
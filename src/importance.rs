@@ -0,0 +1,50 @@
+//! How often each literal occurs in the reported models, as a simple sensitivity analysis
+use std::{collections::BTreeMap, fmt};
+
+/// Counts the occurrences of each literal in a sequence of partial assignments
+///
+/// A literal of a minimal model is essential for it, so the literals occurring in most minimal
+/// models are those the formula depends on most.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Importance {
+    models: usize,
+    counts: BTreeMap<isize, usize>,
+}
+
+impl Importance {
+    pub fn add(&mut self, model: &[isize]) {
+        self.models += 1;
+        for &lit in model {
+            *self.counts.entry(lit).or_default() += 1;
+        }
+    }
+
+    /// The literals occurring in any assignment with their number of occurrences, most frequent
+    /// first and otherwise ordered by variable, with positive literals first
+    pub fn ranking(&self) -> Vec<(isize, usize)> {
+        let mut ranking: Vec<(isize, usize)> = self
+            .counts
+            .iter()
+            .map(|(&lit, &count)| (lit, count))
+            .collect();
+        ranking.sort_by_key(|&(lit, count)| (std::cmp::Reverse(count), lit.abs(), lit < 0));
+        ranking
+    }
+}
+
+/// A line with the number of assignments, followed by one line for each literal of the ranking
+impl fmt::Display for Importance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "importance of {} models:", self.models)?;
+        for (lit, count) in self.ranking() {
+            write!(
+                f,
+                "\n{}: {} ({:.1}%)",
+                lit,
+                count,
+                100.0 * count as f64 / self.models as f64
+            )?;
+        }
+        Ok(())
+    }
+}
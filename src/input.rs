@@ -0,0 +1,30 @@
+//! Parsing of the line based input format
+use anyhow::Context;
+
+/// Parses a line of input consisting of literals, optionally terminated by a `0`
+///
+/// Rejects anything following a terminating `0` and variables above `max_var`. Variables are
+/// always at most `isize::MAX`, so that every literal can be negated.
+pub fn parse_clause(line: &str, max_var: usize) -> anyhow::Result<Vec<isize>> {
+    let mut clause = vec![];
+    let mut lit_strs = line.split_ascii_whitespace();
+    while let Some(lit_str) = lit_strs.next() {
+        let lit_val = str::parse::<isize>(lit_str)
+            .with_context(|| format!("invalid literal {:?}", lit_str))?;
+        if lit_val == 0 {
+            if let Some(junk) = lit_strs.next() {
+                anyhow::bail!("unexpected {:?} after the terminating 0", junk);
+            }
+            break;
+        }
+        if lit_val.unsigned_abs() > max_var.min(isize::MAX as usize) {
+            anyhow::bail!(
+                "variable {} exceeds the maximum of {}",
+                lit_val.unsigned_abs(),
+                max_var.min(isize::MAX as usize)
+            );
+        }
+        clause.push(lit_val);
+    }
+    Ok(clause)
+}
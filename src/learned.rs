@@ -0,0 +1,129 @@
+//! Learned clauses of a run, allowing a later run on the same formula to skip some of the search
+//!
+//! The clauses are stored in DIMACS like format over the solvers' variables, each solver's clauses
+//! following a line naming the solver and the hash of the clauses it was given.
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+const HEADER: &str = "c minimal_models learned clauses";
+
+/// Learned clauses of one solver, see [`Finder::learned`](crate::Finder::learned)
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct SolverClauses {
+    /// Hash of the clauses the solver was given, which imply the learned clauses
+    pub formula: u64,
+    /// Clauses of non-zero DIMACS style literals over the solver's variables
+    pub clauses: Vec<Vec<isize>>,
+}
+
+/// Learned clauses of both solvers, `None` for solvers that can't export them
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Learned {
+    pub pos: Option<SolverClauses>,
+    pub neg: Option<SolverClauses>,
+}
+
+impl Learned {
+    /// Number of clauses of both solvers
+    pub fn len(&self) -> usize {
+        [&self.pos, &self.neg]
+            .iter()
+            .filter_map(|solver| solver.as_ref())
+            .map(|solver| solver.clauses.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn solver(&mut self, name: &str) -> &mut Option<SolverClauses> {
+        if name == "pos" {
+            &mut self.pos
+        } else {
+            &mut self.neg
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Learned> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("reading learned clauses {}", path.display()))?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            anyhow::bail!("{} doesn't contain learned clauses", path.display());
+        }
+
+        let mut learned = Learned::default();
+        // Name of the solver whose clauses follow
+        let mut current = None;
+        for (index, line) in lines.enumerate() {
+            let context = || format!("line {} of {}", index + 2, path.display());
+            if line.starts_with('c') {
+                continue;
+            }
+            if let Some((name @ ("pos" | "neg"), formula)) = line.split_once(' ') {
+                let formula = u64::from_str_radix(formula, 16).with_context(context)?;
+                *learned.solver(name) = Some(SolverClauses {
+                    formula,
+                    clauses: vec![],
+                });
+                current = Some(name);
+                continue;
+            }
+            let solver = match current {
+                Some(name) => learned.solver(name).as_mut().unwrap(),
+                None => anyhow::bail!("{}: clause before naming the solver", context()),
+            };
+            let mut clause = vec![];
+            let mut terminated = false;
+            for lit_str in line.split_ascii_whitespace() {
+                let lit_val = str::parse::<isize>(lit_str).with_context(context)?;
+                if lit_val == 0 {
+                    terminated = true;
+                    break;
+                }
+                clause.push(lit_val);
+            }
+            if !terminated {
+                anyhow::bail!("{}: unterminated clause", context());
+            }
+            solver.clauses.push(clause);
+        }
+        Ok(learned)
+    }
+
+    /// Writes the learned clauses, atomically replacing any existing file at `path`
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let write = || -> io::Result<()> {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            writeln!(file, "{}", HEADER)?;
+            for (name, solver) in [("pos", &self.pos), ("neg", &self.neg)] {
+                let solver = match solver {
+                    Some(solver) => solver,
+                    None => continue,
+                };
+                writeln!(file, "{} {:016x}", name, solver.formula)?;
+                for clause in &solver.clauses {
+                    for lit in clause {
+                        write!(file, "{} ", lit)?;
+                    }
+                    writeln!(file, "0")?;
+                }
+            }
+            file.into_inner()
+                .map_err(|err| err.into_error())?
+                .sync_all()?;
+            fs::rename(&tmp_path, path)
+        };
+        write().with_context(|| format!("writing learned clauses {}", path.display()))
+    }
+}
@@ -0,0 +1,1822 @@
+//! Finds minimal partial assignments that are models of an incrementally extended CNF formula.
+//!
+//! See the README for a description of the technique.
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
+};
+
+pub mod autarky;
+pub mod certificate;
+pub mod checkpoint;
+pub mod config;
+pub mod coverage;
+pub mod dashboard;
+pub mod definability;
+pub mod fingerprint;
+pub mod importance;
+pub mod input;
+pub mod learned;
+pub mod maxsat;
+pub mod memory;
+pub mod metrics;
+pub mod oracle;
+pub mod proof_check;
+pub mod remote;
+pub mod script;
+pub mod structure;
+pub mod unsat_core;
+pub mod verify;
+
+mod backend;
+mod cardinality;
+mod equiv;
+mod optimize;
+mod order;
+mod pb;
+mod preprocess;
+mod reduce;
+mod solver_config;
+mod speculate;
+mod var_map;
+
+pub use backend::SolverSize;
+pub use cardinality::CardinalityEncoding;
+pub use optimize::{Optimized, Optimizer};
+/// Shared with the binary, tests and benchmarks, but not part of the API
+#[doc(hidden)]
+pub use order::Rng;
+pub use order::{CandidateOrder, Preference};
+pub use pb::PbEncoding;
+pub use reduce::{
+    Check, CheckHistograms, CheckTimes, CheckTrace, Checks, Histogram, OnCheck, OnProgress,
+    Progress,
+};
+pub use solver_config::{Backend, Polarity, ProofFormat, SolverOptions};
+
+use backend::{InterruptForwarder, Lbool, Lit, Measured, SatBackend};
+use learned::{Learned, SolverClauses};
+use optimize::Verdict;
+use order::{count, lit_index};
+use pb::Signal;
+use reduce::{Portfolio, Reducer};
+use script::Script;
+use solver_config::SolverConfig;
+use speculate::Speculation;
+use var_map::{VarMap, VarName};
+
+/// Result of [`Finder::solve`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Solved {
+    /// A full model, assigning every variable seen so far
+    Model(Vec<isize>),
+    Unsat,
+    /// The solver gave up, e.g. due to a time limit
+    Unknown,
+}
+
+/// Result of [`Finder::solve_under_assumptions`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Assumed {
+    /// The reduced model of a full model agreeing with the assumptions
+    Model(Reduced),
+    /// No model agrees with the assumptions
+    Unsat {
+        /// Assumed literals that suffice for this, empty if the formula itself is unsatisfiable
+        core: Vec<isize>,
+    },
+    /// The solver gave up
+    Unknown,
+}
+
+/// Result of [`Finder::brave`] and [`Finder::cautious`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Consequence {
+    /// The literal holds in some minimal model, for [`Finder::brave`], or in all of them, for
+    /// [`Finder::cautious`]
+    Holds {
+        /// For [`Finder::brave`], a minimal model containing the literal
+        witness: Option<Reduced>,
+    },
+    /// The literal holds in no minimal model, for [`Finder::brave`], or not in all of them, for
+    /// [`Finder::cautious`]
+    Fails {
+        /// For [`Finder::cautious`], a minimal model not containing the literal
+        witness: Option<Reduced>,
+    },
+    /// The formula has no models
+    Unsat,
+    /// A solver gave up
+    Unknown,
+}
+
+/// Handle of a clause added by [`Finder::add_retractable_clause`]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ClauseId(usize);
+
+/// Result of [`Finder::reduce`]
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Reduced {
+    /// The reduced model
+    pub model: Vec<isize>,
+    /// Literals of `model` that were kept because the solver gave up on checking them or because
+    /// the reduction was interrupted before checking them
+    ///
+    /// If this is non-empty, the reduced model may not be minimal.
+    pub unverified: Vec<isize>,
+    /// Whether the reduction was stopped early by [`Builder::interrupt`]
+    pub interrupted: bool,
+    /// Candidate checks performed, for a portfolio those of the attempt giving the result
+    pub checks: Checks,
+}
+
+impl Reduced {
+    pub fn is_minimal(&self) -> bool {
+        self.unverified.is_empty()
+    }
+}
+
+/// The largest variable accepted by [`minimal_model`] and [`all_minimal_models`]
+///
+/// The memory used for mapping variables grows with the largest variable, not their count.
+pub const ONE_SHOT_MAX_VAR: usize = 10_000_000;
+
+/// Finds a minimal model of `clauses`, or `None` if they are unsatisfiable
+///
+/// Uses a [`Finder`] with the default configuration. Fails for the literal `0`, for variables
+/// above [`ONE_SHOT_MAX_VAR`] and if a solver gives up.
+pub fn minimal_model(clauses: &[Vec<i32>]) -> anyhow::Result<Option<Vec<i32>>> {
+    let mut finder = one_shot_finder(clauses)?;
+    next_minimal_model(&mut finder)
+}
+
+/// Finds all minimal models of `clauses`, in the order a [`Finder`] with the default
+/// configuration reports them
+///
+/// Every minimal model is blocked once found, until no models are left. So the number of models
+/// can be exponential in the number of variables. Fails like [`minimal_model`].
+pub fn all_minimal_models(clauses: &[Vec<i32>]) -> anyhow::Result<Vec<Vec<i32>>> {
+    let mut finder = one_shot_finder(clauses)?;
+    let mut models = vec![];
+    while let Some(model) = next_minimal_model(&mut finder)? {
+        let blocking: Vec<isize> = model.iter().map(|&lit| -(lit as isize)).collect();
+        models.push(model);
+        if blocking.is_empty() {
+            // The empty assignment is the only minimal model
+            break;
+        }
+        finder.add_clause(&blocking);
+    }
+    Ok(models)
+}
+
+fn one_shot_finder(clauses: &[Vec<i32>]) -> anyhow::Result<Finder> {
+    let mut finder = Finder::builder().build()?;
+    for clause in clauses {
+        if clause.contains(&0) {
+            anyhow::bail!("invalid literal 0 in clause {:?}", clause);
+        }
+        if let Some(lit) = clause
+            .iter()
+            .find(|lit| lit.unsigned_abs() as usize > ONE_SHOT_MAX_VAR)
+        {
+            anyhow::bail!(
+                "variable {} exceeds the maximum of {}",
+                lit.unsigned_abs(),
+                ONE_SHOT_MAX_VAR
+            );
+        }
+        let clause: Vec<isize> = clause.iter().map(|&lit| lit as isize).collect();
+        finder.add_clause(&clause);
+    }
+    Ok(finder)
+}
+
+fn next_minimal_model(finder: &mut Finder) -> anyhow::Result<Option<Vec<i32>>> {
+    let model = match finder.solve() {
+        Solved::Model(model) => model,
+        Solved::Unsat => return Ok(None),
+        Solved::Unknown => anyhow::bail!("the solver gave up"),
+    };
+    // Without clauses the empty assignment is the only minimal model
+    let reduced = if finder.clause_count() > 0 {
+        finder.reduce(&model)
+    } else {
+        Reduced::default()
+    };
+    if !reduced.is_minimal() {
+        anyhow::bail!("the solver gave up on checking {:?}", reduced.unverified);
+    }
+    Ok(Some(reduced.model.iter().map(|&lit| lit as i32).collect()))
+}
+
+/// Maps a solver literal to a DIMACS style literal of the solver's variables
+fn dimacs_lit(lit: Lit) -> isize {
+    let var = lit.var() as isize + 1;
+    if lit.isneg() {
+        -var
+    } else {
+        var
+    }
+}
+
+/// Adds learned clauses of DIMACS style literals to `solver`, dropping invalid ones
+fn import_learned(solver: &mut Measured, clauses: &SolverClauses) {
+    let lits = clauses
+        .clauses
+        .iter()
+        .filter_map(|clause| {
+            clause
+                .iter()
+                .map(|&lit| {
+                    let var = u32::try_from(lit.unsigned_abs().checked_sub(1)?).ok()?;
+                    Lit::new(var, lit < 0)
+                })
+                .collect()
+        })
+        .collect();
+    solver.import(clauses.formula, lits);
+}
+
+/// Statistics accumulated since a [`Finder`] was built or reset, see [`Finder::stats`]
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Stats {
+    /// Full model searches by [`Finder::solve`], [`Finder::solve_under_assumptions`] and
+    /// [`Finder::use_hint`]
+    pub solves: usize,
+    /// Time spent finding full models, mostly in the positive solver
+    pub solve_time: Duration,
+    /// Calls of [`Finder::reduce`] with at least one clause
+    pub reductions: usize,
+    /// Time spent reducing, mostly checking candidates in the negative solver(s)
+    pub reduce_time: Duration,
+    /// Candidate checks of all reductions
+    pub checks: Checks,
+    /// Time spent in the candidate checks of all reductions, part of `reduce_time`
+    pub check_times: CheckTimes,
+    /// Distributions of the candidate checks of all reductions
+    pub histograms: CheckHistograms,
+    /// Clauses added by [`Finder::block`]
+    pub blocked: usize,
+}
+
+impl std::ops::AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        self.solves += other.solves;
+        self.solve_time += other.solve_time;
+        self.reductions += other.reductions;
+        self.reduce_time += other.reduce_time;
+        self.checks += other.checks;
+        self.check_times += other.check_times;
+        self.histograms += &other.histograms;
+        self.blocked += other.blocked;
+    }
+}
+
+/// Result of [`Finder::solver_sizes`]
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct SolverSizes {
+    /// The solver finding full models
+    pub pos: SolverSize,
+    /// The solver checking candidates
+    pub neg: SolverSize,
+    /// Further negative solvers with the same clauses, one per speculation worker and, during a
+    /// reduction, per additional portfolio attempt
+    pub neg_copies: usize,
+}
+
+/// Configures and creates a [`Finder`]
+#[derive(Clone, Debug)]
+pub struct Builder {
+    order: CandidateOrder,
+    shuffle_seed: Option<u64>,
+    portfolio: usize,
+    speculate: usize,
+    pos_options: SolverOptions,
+    neg_options: SolverOptions,
+    progress: bool,
+    on_progress: Option<OnProgress>,
+    on_check: Option<OnCheck>,
+    interrupt: Option<Arc<AtomicBool>>,
+    preprocess: bool,
+    equivalences: bool,
+    pure_literals: bool,
+    warm_start: bool,
+    script: Option<Arc<Script>>,
+    optimizer: Optimizer,
+    pos_proof: Option<(PathBuf, ProofFormat)>,
+    neg_proof: Option<(PathBuf, ProofFormat)>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder {
+            order: CandidateOrder::Arbitrary,
+            shuffle_seed: None,
+            portfolio: 1,
+            speculate: 0,
+            pos_options: SolverOptions::default(),
+            neg_options: SolverOptions::default(),
+            progress: false,
+            on_progress: None,
+            on_check: None,
+            interrupt: None,
+            preprocess: false,
+            equivalences: false,
+            pure_literals: false,
+            warm_start: false,
+            script: None,
+            optimizer: Optimizer::default(),
+            pos_proof: None,
+            neg_proof: None,
+        }
+    }
+}
+
+impl Builder {
+    /// Order in which candidate literals are tested for being essential
+    pub fn order(mut self, order: CandidateOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Shuffle the candidates using the given seed before applying the order
+    pub fn shuffle_candidates(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Number of reductions with different candidate orders to run in parallel
+    pub fn portfolio(mut self, attempts: usize) -> Self {
+        self.portfolio = attempts;
+        self
+    }
+
+    /// Number of additional candidates to check concurrently
+    pub fn speculate(mut self, workers: usize) -> Self {
+        self.speculate = workers;
+        self
+    }
+
+    /// Options for both solvers
+    pub fn solver_options(mut self, options: SolverOptions) -> Self {
+        self.pos_options = options.clone();
+        self.neg_options = options;
+        self
+    }
+
+    /// Options for the solver maintaining the formula
+    pub fn pos_solver_options(mut self, options: SolverOptions) -> Self {
+        self.pos_options = options;
+        self
+    }
+
+    /// Options for the solver(s) maintaining the negated formula
+    pub fn neg_solver_options(mut self, options: SolverOptions) -> Self {
+        self.neg_options = options;
+        self
+    }
+
+    /// Print progress messages to stdout while reducing
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Call `callback` before each candidate check of a reduction
+    ///
+    /// This allows displaying the progress of long reductions, e.g. in a dashboard.
+    pub fn on_progress(mut self, callback: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(OnProgress(Arc::new(callback)));
+        self
+    }
+
+    /// Call `callback` after each candidate check of a reduction with its assumptions, outcome
+    /// and core
+    ///
+    /// This records how each reduced model was derived, e.g. for debugging or teaching. The
+    /// speculative checks of [`Builder::speculate`] are passed in the order of their candidates.
+    /// Only the first attempt of [`Builder::portfolio`] is passed, so the reduced model may come
+    /// from another attempt.
+    pub fn on_check(mut self, callback: impl Fn(&CheckTrace) + Send + Sync + 'static) -> Self {
+        self.on_check = Some(OnCheck(Arc::new(callback)));
+        self
+    }
+
+    /// Stop reducing when `interrupt` is set
+    ///
+    /// The flag is checked before each candidate check, as cryptominisat can't be interrupted while
+    /// running. The reduction then returns the current, possibly non-minimal, model.
+    pub fn interrupt(mut self, interrupt: Arc<AtomicBool>) -> Self {
+        self.interrupt = Some(interrupt);
+        self
+    }
+
+    /// Simplify clauses before loading them into the solvers
+    ///
+    /// This buffers clauses until the next call to [`Finder::solve`], simplifying each batch
+    /// together with the unit clauses of previous batches.
+    pub fn preprocess(mut self, preprocess: bool) -> Self {
+        self.preprocess = preprocess;
+        self
+    }
+
+    /// Don't test literals with equivalent literals for being essential
+    ///
+    /// Every model of the formula (partial or not) has to assign all variables of a class of
+    /// equivalent literals, so these are essential. Equivalences are detected as strongly
+    /// connected components of the binary implication graph, see [`Finder::equivalence_classes`].
+    pub fn equivalences(mut self, equivalences: bool) -> Self {
+        self.equivalences = equivalences;
+        self
+    }
+
+    /// Don't test literals occurring in no clause for being essential, but drop them directly
+    ///
+    /// Such a literal satisfies no clause, so it is never essential. Its negation is a pure
+    /// literal, which is an autarky on its own. The literals of larger autarkies, see
+    /// [`autarky::maximal_autarky`], don't allow such a shortcut: e.g. `x` and `z` form an autarky
+    /// of the clauses `x y` and `-x z`, but `-x` is essential for the model `-x y -z`.
+    pub fn pure_literals(mut self, pure_literals: bool) -> Self {
+        self.pure_literals = pure_literals;
+        self
+    }
+
+    /// Use the full model of each reduction without the reduced model as phase hints for the next
+    /// call to [`Finder::solve`]
+    ///
+    /// As the reduced model is usually blocked afterwards, this steers towards similar models.
+    pub fn warm_start(mut self, warm_start: bool) -> Self {
+        self.warm_start = warm_start;
+        self
+    }
+
+    /// Rank the candidates using `script`, after applying the order and before the preferences
+    pub fn script(mut self, script: Script) -> Self {
+        self.script = Some(Arc::new(script));
+        self
+    }
+
+    /// How [`Finder::minimum_model`] searches for a minimum
+    pub fn optimizer(mut self, optimizer: Optimizer) -> Self {
+        self.optimizer = optimizer;
+        self
+    }
+
+    /// Write a proof of each call of the positive solver without a model to `path`, e.g. once
+    /// all models were blocked
+    ///
+    /// Only the builtin solver can write proofs. The proof is complete once the finder is dropped
+    /// and [`Finder::reset`] starts a new one.
+    pub fn pos_proof(mut self, path: impl Into<PathBuf>, format: ProofFormat) -> Self {
+        self.pos_proof = Some((path.into(), format));
+        self
+    }
+
+    /// Write a proof of each candidate check without a counterexample, i.e. of each literal that
+    /// isn't essential, to `path`
+    ///
+    /// The checks are proven in the encoding of the negative solver, whose variables aren't those
+    /// of the formula. This requires checking the candidates on a single solver, without a
+    /// portfolio or speculation. Otherwise like [`Builder::pos_proof`].
+    pub fn neg_proof(mut self, path: impl Into<PathBuf>, format: ProofFormat) -> Self {
+        self.neg_proof = Some((path.into(), format));
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<Finder> {
+        let builder = self.clone();
+        let pos_config = SolverConfig::new(&self.pos_options)?;
+        let neg_config = SolverConfig::new(&self.neg_options)?;
+        let seed = self.shuffle_seed.unwrap_or(0);
+        let pos_solver = match &self.pos_proof {
+            Some((path, format)) => pos_config.new_proving_solver(path, *format)?,
+            None => pos_config.new_solver(),
+        };
+        let pos_solver = Measured::new(pos_solver);
+        let neg_solver = match &self.neg_proof {
+            Some(_) if self.portfolio > 1 || self.speculate > 0 => {
+                anyhow::bail!("proofs of candidate checks require a single negative solver")
+            }
+            Some((path, format)) => neg_config.new_proving_solver(path, *format)?,
+            None => neg_config.new_solver(),
+        };
+        let neg_solver = Measured::new(neg_solver);
+        let interrupt_forwarder = self.interrupt.as_ref().map(|interrupt| {
+            let interrupters = [&pos_solver, &neg_solver]
+                .iter()
+                .filter_map(|solver| solver.interrupter())
+                .collect();
+            InterruptForwarder::new(interrupt.clone(), interrupters)
+        });
+
+        Ok(Finder {
+            pos_solver,
+            neg_solver,
+            _interrupt_forwarder: interrupt_forwarder,
+            var_map: VarMap::default(),
+            clause_counter: 0,
+            chain: None,
+            reducer: Reducer::new(
+                self.order,
+                self.shuffle_seed.map(Rng),
+                self.script.clone(),
+                self.progress,
+                self.on_progress.clone(),
+                self.on_check.clone(),
+                self.interrupt.clone(),
+            ),
+            // Make sure the additional attempts don't reuse the order of the first attempt
+            portfolio: (self.portfolio > 1)
+                .then(|| Portfolio::new(self.portfolio, Rng(!seed), &neg_config)),
+            speculation: (self.speculate > 0)
+                .then(|| Speculation::new(self.speculate, &neg_config)),
+            preprocess: self.preprocess,
+            pending: vec![],
+            units: HashSet::default(),
+            equivalences: self.equivalences,
+            pure_literals: self.pure_literals,
+            constraint_vars: HashSet::default(),
+            binary_clauses: vec![],
+            warm_start: self.warm_start,
+            hints: vec![],
+            phases: vec![],
+            assumptions: vec![],
+            retractable: vec![],
+            scopes: vec![],
+            stats: Stats::default(),
+            builder,
+        })
+    }
+}
+
+/// Maintains a formula and finds minimal models for it
+pub struct Finder {
+    /// Maintains conjunction of clauses
+    pos_solver: Measured,
+    /// Maintains disjunction of negated clauses
+    neg_solver: Measured,
+    /// Forwards [`Builder::interrupt`] to the solvers until the finder is dropped
+    _interrupt_forwarder: Option<InterruptForwarder>,
+
+    /// Map user variables into internal variables, so we have space for auxiliary variables
+    var_map: VarMap,
+
+    clause_counter: usize,
+
+    /// Literal used to incrementally extend the disjunction in `neg_solver`
+    chain: Option<Lit>,
+
+    reducer: Reducer,
+    /// Only used when reducing with more than one attempt
+    portfolio: Option<Portfolio>,
+    /// Only used when checking candidates concurrently
+    speculation: Option<Speculation>,
+
+    preprocess: bool,
+    /// Clauses waiting to be preprocessed
+    pending: Vec<Vec<isize>>,
+    /// Unit clauses found by preprocessing previous batches
+    units: HashSet<isize>,
+
+    equivalences: bool,
+    pure_literals: bool,
+    /// Variables of cardinality and pseudo-Boolean constraints, whose literals aren't counted as
+    /// occurrences but may be essential anyway
+    constraint_vars: HashSet<u32>,
+    /// Binary clauses over user variables, forming the binary implication graph
+    binary_clauses: Vec<[Lit; 2]>,
+
+    warm_start: bool,
+    /// Phase hints for `pos_solver`, see [`Finder::set_hints`]
+    hints: Vec<Lit>,
+    /// Preferred phases for `pos_solver`, see [`Finder::prefer`]
+    phases: Vec<Lit>,
+    /// Literals assumed by every call of `pos_solver`, see [`Finder::set_assumptions`]
+    assumptions: Vec<Lit>,
+    /// Clauses added by [`Finder::add_retractable_clause`], indexed by their [`ClauseId`]
+    retractable: Vec<Retractable>,
+    /// Scopes opened by [`Finder::push`], innermost last
+    scopes: Vec<Scope>,
+
+    stats: Stats,
+
+    /// Configuration this was built with, for [`Finder::reset`]
+    builder: Builder,
+}
+
+/// A clause that can be deactivated and activated again
+///
+/// In `pos_solver` the clause is extended by `!activation`, which is assumed while the clause is
+/// active. In `neg_solver`, `indicator` is true iff the clause is falsified, and the active clause
+/// extends `chain` by `gate`, which implies `indicator`.
+struct Retractable {
+    activation: Lit,
+    indicator: Lit,
+    /// `None` while inactive
+    gate: Option<Lit>,
+    /// Whether the clause was added in a scope closed by [`Finder::pop`]
+    popped: bool,
+}
+
+/// A scope opened by [`Finder::push`]
+///
+/// Clauses and constraints added in the scope are extended by `!activation` in `pos_solver`,
+/// which is assumed while the scope is open. In `neg_solver` they extend `chain` by a gate that
+/// implies `activation`. Closing the scope makes `activation` false in both solvers.
+struct Scope {
+    activation: Lit,
+    /// What to restore when the scope is closed
+    vars: usize,
+    clause_counter: usize,
+    binary_clauses: usize,
+    retractable: usize,
+    units: HashSet<isize>,
+}
+
+impl Finder {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    fn user_lit(&self, lit: Lit) -> isize {
+        if let Some(VarName::UserVar(user_var)) = self.var_map.get(lit.var() as usize) {
+            if lit.isneg() {
+                -user_var
+            } else {
+                user_var
+            }
+        } else {
+            panic!("not a user var");
+        }
+    }
+
+    fn internal_lit(&mut self, user_lit: isize) -> Lit {
+        let index = self.var_map.user_var(user_lit.abs());
+
+        let var = Lit::new(index as u32, false).unwrap();
+        if user_lit < 0 {
+            !var
+        } else {
+            var
+        }
+    }
+
+    /// Adds a clause to `neg_solver` and all of its copies
+    ///
+    /// The workers of a portfolio or speculation keep their copies up to date.
+    fn add_neg_clause(&mut self, clause: &[Lit]) {
+        self.neg_solver.add_clause(clause);
+        if let Some(portfolio) = &mut self.portfolio {
+            portfolio.add_clause(clause);
+        }
+        if let Some(speculation) = &mut self.speculation {
+            speculation.add_clause(clause);
+        }
+    }
+
+    fn ensure_vars(&mut self) {
+        for solver in [&mut self.pos_solver, &mut self.neg_solver] {
+            // Since when did cryptominisat require declaring variables with new_var?
+            while (solver.nvars() as usize) <= self.var_map.len() + 2 {
+                solver.new_var();
+            }
+        }
+    }
+
+    /// Number of clauses loaded into the solvers so far, including blocking clauses
+    ///
+    /// With preprocessing this excludes clauses that were simplified away or are still pending.
+    pub fn clause_count(&self) -> usize {
+        self.clause_counter
+    }
+
+    /// Adds a clause given as non-zero DIMACS style literals
+    ///
+    /// Panics if a literal is `0` or `isize::MIN`.
+    pub fn add_clause(&mut self, clause: &[isize]) {
+        for &lit in clause {
+            assert!(lit != 0 && lit != isize::MIN, "invalid literal {}", lit);
+        }
+        // Variables are mapped right away, so that variables of removed clauses still appear in
+        // full models
+        for &lit in clause {
+            self.internal_lit(lit);
+        }
+        if self.preprocess {
+            self.pending.push(clause.to_vec());
+        } else if let Some(clause) = preprocess::normalize(clause) {
+            // Tautologies are always satisfied, so dropping them doesn't change the models
+            self.load_clause(&clause);
+        }
+    }
+
+    fn flush_pending(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        for clause in preprocess::simplify(pending, &mut self.units) {
+            self.load_clause(&clause);
+        }
+    }
+
+    fn load_clause(&mut self, clause: &[isize]) {
+        let clause: Vec<Lit> = clause.iter().map(|&lit| self.internal_lit(lit)).collect();
+
+        self.ensure_vars();
+
+        if let [a, b] = clause[..] {
+            self.binary_clauses.push([a, b]);
+        }
+
+        // We can directly add the clause to `pos_solver`, only limited to the current scope
+
+        let mut pos_clause = clause.clone();
+        pos_clause.extend(self.scopes.last().map(|scope| !scope.activation));
+        self.pos_solver.add_clause(&pos_clause);
+        self.clause_counter += 1;
+
+        for &lit in &clause {
+            count(&mut self.reducer.occurrences, lit);
+        }
+
+        // For `neg_solver` we add an auxiliary variable that will be true when the clause is
+        // falsified.
+        let index = self.var_map.push(VarName::Clause(self.clause_counter));
+        let clause_indicator = Lit::new(index as u32, false).unwrap();
+        self.extend_scoped_chain(clause_indicator);
+        self.add_clause_indicator(clause, clause_indicator);
+    }
+
+    /// Adds the clauses to `neg_solver` making `clause_indicator` true iff `clause` is falsified
+    fn add_clause_indicator(&mut self, mut clause: Vec<Lit>, clause_indicator: Lit) {
+        // clause_indicator = !lit_0 & ... & lit_n
+        for lit in &mut clause {
+            self.add_neg_clause(&[*lit, !clause_indicator]);
+            *lit = !*lit;
+        }
+        clause.push(clause_indicator);
+        self.add_neg_clause(&clause);
+    }
+
+    /// Updates the `chain` variable of `neg_solver` such that it is true when the formula so far
+    /// is falsified, given the `indicator` of the newly added clause or constraint being falsified
+    fn extend_chain(&mut self, indicator: Lit) {
+        if let Some(prev_chain) = self.chain {
+            let index = self.var_map.push(VarName::Chain(self.clause_counter));
+            let next_chain = Lit::new(index as u32, false).unwrap();
+
+            // next_chain = prev_chain | indicator
+            self.add_neg_clause(&[!prev_chain, next_chain]);
+            self.add_neg_clause(&[!indicator, next_chain]);
+            self.add_neg_clause(&[indicator, prev_chain, !next_chain]);
+            self.chain = Some(next_chain);
+        } else {
+            self.chain = Some(indicator);
+        }
+    }
+
+    /// Like [`Finder::extend_chain`], but only while the current scope is open
+    fn extend_scoped_chain(&mut self, indicator: Lit) {
+        let activation = match self.scopes.last() {
+            Some(scope) => scope.activation,
+            None => return self.extend_chain(indicator),
+        };
+        let index = self.var_map.push(VarName::Aux);
+        let gate = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+        self.add_neg_clause(&[!gate, indicator]);
+        self.add_neg_clause(&[!gate, activation]);
+        self.extend_chain(gate);
+    }
+
+    /// Adds the constraint that at most `k` of the given non-zero DIMACS style literals are true
+    ///
+    /// Repeated literals count once per occurrence. The constraint is encoded using a totalizer,
+    /// whose auxiliary variables don't appear in models, in both solvers, so the reduced models
+    /// are minimal models of the clauses together with all constraints. Unlike clauses, the
+    /// constraint isn't preprocessed and doesn't count towards [`Finder::clause_count`].
+    ///
+    /// Panics if a literal is `0` or `isize::MIN`.
+    pub fn add_at_most_k(&mut self, lits: &[isize], k: usize) {
+        self.add_at_most_k_with(lits, k, CardinalityEncoding::default());
+    }
+
+    /// Like [`Finder::add_at_most_k`], using the given encoding
+    pub fn add_at_most_k_with(&mut self, lits: &[isize], k: usize, encoding: CardinalityEncoding) {
+        for &lit in lits {
+            assert!(lit != 0 && lit != isize::MIN, "invalid literal {}", lit);
+        }
+        let lits: Vec<Lit> = lits.iter().map(|&lit| self.internal_lit(lit)).collect();
+        if lits.len() <= k {
+            return;
+        }
+
+        let mut clauses = vec![];
+        let outputs = cardinality::encode(
+            encoding,
+            &lits,
+            k + 1,
+            &mut || {
+                let index = self.var_map.push(VarName::Aux);
+                Lit::new(index as u32, false).unwrap()
+            },
+            &mut |clause: &[Lit]| clauses.push(clause.to_vec()),
+        );
+        // True iff more than `k` literals are true
+        let violated = outputs[k];
+        let user_vars = lits.iter().map(|lit| lit.var()).collect();
+        self.add_constraint(user_vars, clauses, violated);
+    }
+
+    /// Adds the constraint that the coefficients of the true literals among the given non-zero
+    /// DIMACS style literals sum to at most `bound`
+    ///
+    /// Coefficients may be negative, and repeated literals count once per occurrence. A constraint
+    /// with `>=`, as in OPB files, is added by negating its coefficients and bound. The constraint is
+    /// encoded using a decision diagram, whose auxiliary variables don't appear in models, in both
+    /// solvers, like [`Finder::add_at_most_k`].
+    ///
+    /// Panics if a literal is `0` or `isize::MIN`.
+    pub fn add_pb(&mut self, terms: &[(i64, isize)], bound: i64) {
+        self.add_pb_with(terms, bound, PbEncoding::default());
+    }
+
+    /// Like [`Finder::add_pb`], using the given encoding
+    pub fn add_pb_with(&mut self, terms: &[(i64, isize)], bound: i64, encoding: PbEncoding) {
+        // Normalized to positive coefficients, using `c l = |c| !l - |c|` for negative ones
+        let mut bound = bound as i128;
+        let mut normalized = vec![];
+        for &(coefficient, lit) in terms {
+            assert!(lit != 0 && lit != isize::MIN, "invalid literal {}", lit);
+            let lit = self.internal_lit(lit);
+            if coefficient < 0 {
+                bound -= coefficient as i128;
+                normalized.push((coefficient.unsigned_abs() as u128, !lit));
+            } else if coefficient > 0 {
+                normalized.push((coefficient as u128, lit));
+            }
+        }
+        let total: i128 = normalized
+            .iter()
+            .map(|&(coefficient, _)| coefficient as i128)
+            .sum();
+        if bound >= total {
+            return;
+        }
+
+        let mut clauses = vec![];
+        let mut new_var = || {
+            let index = self.var_map.push(VarName::Aux);
+            Lit::new(index as u32, false).unwrap()
+        };
+        let violated = if bound < 0 {
+            Signal::True
+        } else {
+            pb::encode(
+                encoding,
+                &normalized,
+                bound as u128,
+                &mut new_var,
+                &mut |clause: &[Lit]| clauses.push(clause.to_vec()),
+            )
+        };
+        let violated = match violated {
+            Signal::False => return,
+            Signal::True => {
+                // No assignment satisfies the constraint
+                let violated = new_var();
+                clauses.push(vec![violated]);
+                violated
+            }
+            Signal::Lit(lit) => lit,
+        };
+        let user_vars = normalized.iter().map(|(_, lit)| lit.var()).collect();
+        self.add_constraint(user_vars, clauses, violated);
+    }
+
+    /// Adds the encoding of a constraint, given the variables of its literals, the `clauses`
+    /// defining auxiliary variables and `violated`, which is true iff the constraint is violated
+    fn add_constraint(
+        &mut self,
+        user_vars: HashSet<u32>,
+        mut clauses: Vec<Vec<Lit>>,
+        violated: Lit,
+    ) {
+        self.constraint_vars.extend(&user_vars);
+        // The indicator has to be a fresh variable, as the first one becomes `chain`
+        let index = self.var_map.push(VarName::Aux);
+        let indicator = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+
+        // In `neg_solver` the user variables are negated, while the auxiliary variables keep
+        // their meaning
+        let negate_user_vars = |lit: Lit| {
+            if user_vars.contains(&lit.var()) {
+                !lit
+            } else {
+                lit
+            }
+        };
+        for clause in &mut clauses {
+            self.pos_solver.add_clause(clause);
+            for lit in clause.iter_mut() {
+                *lit = negate_user_vars(*lit);
+            }
+            self.add_neg_clause(clause);
+        }
+        let mut clause = vec![!violated];
+        clause.extend(self.scopes.last().map(|scope| !scope.activation));
+        self.pos_solver.add_clause(&clause);
+
+        let violated = negate_user_vars(violated);
+        self.add_neg_clause(&[!indicator, violated]);
+        self.add_neg_clause(&[indicator, !violated]);
+        self.extend_scoped_chain(indicator);
+    }
+
+    /// Adds a clause given as non-zero DIMACS style literals, which can be retracted later
+    ///
+    /// The clause is active until it is passed to [`Finder::deactivate`], and can be activated
+    /// again using [`Finder::activate`]. Only the active clauses are part of the formula, both for
+    /// finding and for reducing models. The clause isn't preprocessed and isn't used to find
+    /// equivalent literals, but counts towards [`Finder::clause_count`].
+    ///
+    /// Panics if a literal is `0` or `isize::MIN`.
+    pub fn add_retractable_clause(&mut self, clause: &[isize]) -> ClauseId {
+        for &lit in clause {
+            assert!(lit != 0 && lit != isize::MIN, "invalid literal {}", lit);
+        }
+        let mut clause: Vec<Lit> = clause.iter().map(|&lit| self.internal_lit(lit)).collect();
+        self.clause_counter += 1;
+        let index = self.var_map.push(VarName::Aux);
+        let activation = Lit::new(index as u32, false).unwrap();
+        let index = self.var_map.push(VarName::Clause(self.clause_counter));
+        let indicator = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+
+        for &lit in &clause {
+            count(&mut self.reducer.occurrences, lit);
+        }
+        self.add_clause_indicator(clause.clone(), indicator);
+        clause.push(!activation);
+        self.pos_solver.add_clause(&clause);
+
+        let id = ClauseId(self.retractable.len());
+        self.retractable.push(Retractable {
+            activation,
+            indicator,
+            gate: None,
+            popped: false,
+        });
+        self.activate(id);
+        id
+    }
+
+    /// Activates the retractable clause `id` again, doing nothing if it is active
+    ///
+    /// Panics if `id` wasn't returned by [`Finder::add_retractable_clause`] since the last
+    /// [`Finder::reset`], or if it was added in a scope closed by [`Finder::pop`].
+    pub fn activate(&mut self, id: ClauseId) {
+        assert!(!self.retractable[id.0].popped, "clause was removed by pop");
+        if self.retractable[id.0].gate.is_some() {
+            return;
+        }
+        // A gate is disabled for good when deactivating, so each activation needs a fresh one
+        let index = self.var_map.push(VarName::Aux);
+        let gate = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+        self.add_neg_clause(&[!gate, self.retractable[id.0].indicator]);
+        self.extend_chain(gate);
+        self.retractable[id.0].gate = Some(gate);
+    }
+
+    /// Removes the retractable clause `id` from the formula, doing nothing if it is inactive
+    ///
+    /// Panics like [`Finder::activate`].
+    pub fn deactivate(&mut self, id: ClauseId) {
+        if let Some(gate) = self.retractable[id.0].gate.take() {
+            self.add_neg_clause(&[!gate]);
+        }
+    }
+
+    /// Whether the retractable clause `id` is part of the formula
+    ///
+    /// Panics like [`Finder::activate`].
+    pub fn is_active(&self, id: ClauseId) -> bool {
+        self.retractable[id.0].gate.is_some()
+    }
+
+    /// Opens a scope, so that the clauses and constraints added until the matching
+    /// [`Finder::pop`] can be removed at once
+    ///
+    /// Scopes nest, allowing to explore a tree of alternatives by backtracking. This includes
+    /// blocking clauses and retractable clauses, but activating and deactivating retractable
+    /// clauses added before isn't undone.
+    pub fn push(&mut self) {
+        // Pending clauses belong to the enclosing scope
+        self.flush_pending();
+        let index = self.var_map.push(VarName::Aux);
+        let activation = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+        self.scopes.push(Scope {
+            activation,
+            vars: self.var_map.len(),
+            clause_counter: self.clause_counter,
+            binary_clauses: self.binary_clauses.len(),
+            retractable: self.retractable.len(),
+            units: self.units.clone(),
+        });
+    }
+
+    /// Closes the innermost scope, removing the clauses and constraints added since the matching
+    /// [`Finder::push`]
+    ///
+    /// User variables first seen in the scope are forgotten, so they no longer appear in full
+    /// models. The removed clauses no longer count towards [`Finder::clause_count`].
+    ///
+    /// Panics if there is no open scope.
+    pub fn pop(&mut self) {
+        let scope = self.scopes.pop().expect("no open scope");
+        self.pending.clear();
+        self.pos_solver.add_clause(&[!scope.activation]);
+        self.add_neg_clause(&[!scope.activation]);
+        let gates: Vec<Lit> = self.retractable[scope.retractable..]
+            .iter_mut()
+            .filter_map(|clause| {
+                clause.popped = true;
+                clause.gate.take()
+            })
+            .collect();
+        for gate in gates {
+            self.add_neg_clause(&[!gate]);
+        }
+
+        self.var_map.forget_user_vars(scope.vars);
+        self.reducer.user_vars.truncate(scope.vars);
+        self.clause_counter = scope.clause_counter;
+        self.binary_clauses.truncate(scope.binary_clauses);
+        self.units = scope.units;
+    }
+
+    /// The activation literals of the open scopes and of the active retractable clauses
+    fn activations(&self) -> Vec<Lit> {
+        let scopes = self.scopes.iter().map(|scope| scope.activation);
+        let retractable = self
+            .retractable
+            .iter()
+            .filter(|clause| clause.gate.is_some())
+            .map(|clause| clause.activation);
+        scopes.chain(retractable).collect()
+    }
+
+    /// The assumptions set by [`Finder::set_assumptions`] together with the activation literals,
+    /// which every call of `pos_solver` assumes
+    fn pos_assumptions(&self) -> Vec<Lit> {
+        let mut assumptions = self.assumptions.clone();
+        assumptions.extend(self.activations());
+        assumptions
+    }
+
+    /// Adds a clause excluding the given (partial) assignment
+    pub fn block(&mut self, model: &[isize]) {
+        let clause: Vec<isize> = model.iter().map(|&lit| -lit).collect();
+        self.add_clause(&clause);
+        self.stats.blocked += 1;
+    }
+
+    /// Statistics since this was built or last reset
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Sizes of the solvers, for estimating their memory usage
+    pub fn solver_sizes(&self) -> SolverSizes {
+        let workers = self
+            .speculation
+            .as_ref()
+            .map_or(0, |speculation| speculation.workers());
+        let attempts = self.portfolio.as_ref().map_or(1, Portfolio::attempts);
+        SolverSizes {
+            pos: self.pos_solver.size(),
+            neg: self.neg_solver.size(),
+            neg_copies: workers + attempts - 1,
+        }
+    }
+
+    /// Learned clauses of both solvers, to be given to [`Finder::import_learned`] in a later run
+    ///
+    /// Only solvers whose backend can export its learned clauses, currently only the builtin
+    /// backend, contribute clauses.
+    pub fn learned(&self) -> Learned {
+        let export = |solver: &Measured| {
+            let clauses = solver.learned_clauses()?;
+            Some(SolverClauses {
+                formula: solver.solved_formula(),
+                clauses: clauses
+                    .iter()
+                    .map(|clause| clause.iter().map(|&lit| dimacs_lit(lit)).collect())
+                    .collect(),
+            })
+        };
+        Learned {
+            pos: export(&self.pos_solver),
+            neg: export(&self.neg_solver),
+        }
+    }
+
+    /// Adds the learned clauses of an earlier run to the solvers
+    ///
+    /// The clauses are over the solvers' internal variables, so each solver only adds them once
+    /// it was given exactly the same clauses as the solver that learned them, i.e. when the same
+    /// formula is added in the same way with the same configuration. Until then, and for solvers
+    /// writing a proof, they are ignored, so they can never change the results.
+    pub fn import_learned(&mut self, learned: &Learned) {
+        if let (Some(clauses), None) = (&learned.pos, &self.builder.pos_proof) {
+            import_learned(&mut self.pos_solver, clauses);
+        }
+        if let (Some(clauses), None) = (&learned.neg, &self.builder.neg_proof) {
+            import_learned(&mut self.neg_solver, clauses);
+        }
+    }
+
+    /// Removes all clauses, keeping the configuration
+    ///
+    /// This replaces the solvers, dropping all auxiliary variables, as well as the preferences,
+    /// preferred phases, phase hints, assumptions and statistics. With `keep_vars`, the user
+    /// variables seen so far remain known, so they still appear in full models, in the same order.
+    pub fn reset(&mut self, keep_vars: bool) {
+        let user_vars: Vec<isize> = if keep_vars {
+            self.var_map
+                .iter()
+                .filter_map(|name| match name {
+                    VarName::UserVar(user_var) => Some(user_var),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        *self = self
+            .builder
+            .clone()
+            .build()
+            .expect("configuration was accepted before");
+        for user_var in user_vars {
+            self.internal_lit(user_var);
+        }
+    }
+
+    /// Finds a full model of the formula, assigning every variable seen so far
+    ///
+    /// The model agrees with the assumptions set by [`Finder::set_assumptions`], if there is no
+    /// such model, this returns [`Solved::Unsat`].
+    pub fn solve(&mut self) -> Solved {
+        let start = Instant::now();
+        let solved = self.find_model();
+        self.stats.solves += 1;
+        self.stats.solve_time += start.elapsed();
+        solved
+    }
+
+    fn find_model(&mut self) -> Solved {
+        self.flush_pending();
+        self.ensure_vars();
+
+        // Cryptominisat doesn't allow setting the polarity of individual variables, so we emulate
+        // phase hints by assuming them, with preferred phases overriding the hints
+        let required = self.pos_assumptions();
+        let mut assumptions = required.clone();
+        for &lit in self.phases.iter().chain(&self.hints) {
+            if !assumptions.iter().any(|assumed| assumed.var() == lit.var()) {
+                assumptions.push(lit);
+            }
+        }
+        if assumptions.len() > required.len() {
+            match self.pos_solver.solve_with_assumptions(&assumptions) {
+                Lbool::True => return Solved::Model(self.full_model()),
+                // We can't drop just the hints in the conflict, as the bindings can't handle the
+                // empty conflict of a formula that is unsatisfiable independent of the hints, so
+                // we ignore all hints instead
+                Lbool::False => (),
+                Lbool::Undef => return Solved::Unknown,
+            }
+        }
+
+        let solved = if required.is_empty() {
+            self.pos_solver.solve()
+        } else {
+            self.pos_solver.solve_with_assumptions(&required)
+        };
+        match solved {
+            Lbool::True => Solved::Model(self.full_model()),
+            Lbool::False => Solved::Unsat,
+            Lbool::Undef => Solved::Unknown,
+        }
+    }
+
+    fn full_model(&self) -> Vec<isize> {
+        let mut model = vec![];
+        self.pos_solver.model(&mut model);
+        let mut full_model = vec![];
+        for (index, var_name) in self.var_map.iter().enumerate() {
+            if let VarName::UserVar(user_var) = var_name {
+                full_model.push(if model[index] == Lbool::True {
+                    user_var
+                } else {
+                    -user_var
+                });
+            }
+        }
+        full_model
+    }
+
+    /// Finds and reduces a model agreeing with the given literals, in addition to the assumptions
+    /// set by [`Finder::set_assumptions`]
+    ///
+    /// This allows asking what-if questions without changing the formula. Like with
+    /// [`Finder::set_assumptions`], the reduced model is a minimal model of the formula that
+    /// doesn't necessarily contain the assumptions. If there is no such model, this returns the
+    /// assumed literals responsible, which aren't necessarily a minimal subset.
+    pub fn solve_under_assumptions(&mut self, lits: &[isize]) -> Assumed {
+        self.flush_pending();
+        let required = self.pos_assumptions();
+        let mut assumptions = required.clone();
+        assumptions.extend(lits.iter().map(|&lit| self.internal_lit(lit)));
+        self.ensure_vars();
+
+        let start = Instant::now();
+        let solved = self.pos_solver.solve_with_assumptions(&assumptions);
+        self.stats.solves += 1;
+        self.stats.solve_time += start.elapsed();
+        match solved {
+            Lbool::True => {
+                let model = self.full_model();
+                Assumed::Model(self.reduce(&model))
+            }
+            Lbool::False => {
+                // The bindings can't handle an empty conflict, so we check for it separately and
+                // repeat the call for the conflict otherwise
+                match self.pos_solver.solve_with_assumptions(&required) {
+                    Lbool::True => (),
+                    Lbool::False => return Assumed::Unsat { core: vec![] },
+                    Lbool::Undef => return Assumed::Unknown,
+                }
+                if self.pos_solver.solve_with_assumptions(&assumptions) != Lbool::False {
+                    return Assumed::Unknown;
+                }
+                let mut conflict = vec![];
+                self.pos_solver.conflict(&mut conflict);
+                // Activation literals aren't user literals
+                conflict.retain(|lit| {
+                    matches!(
+                        self.var_map.get(lit.var() as usize),
+                        Some(VarName::UserVar(_))
+                    )
+                });
+                Assumed::Unsat {
+                    core: conflict.iter().map(|&lit| self.user_lit(!lit)).collect(),
+                }
+            }
+            Lbool::Undef => Assumed::Unknown,
+        }
+    }
+
+    /// Sets phase hints for the following calls to [`Finder::solve`]
+    ///
+    /// The solver first tries to find a model agreeing with all hinted literals, and if there is
+    /// none, any model.
+    pub fn set_hints(&mut self, hints: &[isize]) {
+        self.hints = hints.iter().map(|&lit| self.internal_lit(lit)).collect();
+        self.ensure_vars();
+    }
+
+    /// Sets the literals of `model` as phase hints, like [`Finder::set_hints`], and returns the
+    /// full model given by `model` if it is one
+    ///
+    /// Literals of variables not seen so far are ignored. If `model` assigns every variable seen
+    /// so far, it is checked together with the assumptions set by [`Finder::set_assumptions`] by
+    /// a call of the positive solver that needs no search over these variables, only over
+    /// auxiliary variables of constraints. Otherwise, or if it isn't a model, this returns `None`
+    /// and a full model has to be found by [`Finder::solve`].
+    pub fn use_hint(&mut self, model: &[isize]) -> Option<Vec<isize>> {
+        self.flush_pending();
+        self.ensure_vars();
+        let start = Instant::now();
+        let lits: HashSet<isize> = model.iter().copied().collect();
+        let mut hints = vec![];
+        let mut complete = true;
+        for (index, name) in self.var_map.iter().enumerate() {
+            if let VarName::UserVar(user_var) = name {
+                let var = Lit::new(index as u32, false).unwrap();
+                if lits.contains(&user_var) {
+                    hints.push(var);
+                } else if lits.contains(&-user_var) {
+                    hints.push(!var);
+                } else {
+                    complete = false;
+                }
+            }
+        }
+        self.hints = hints;
+        if !complete {
+            return None;
+        }
+
+        let mut assumptions = self.pos_assumptions();
+        assumptions.extend(&self.hints);
+        let solved = self.pos_solver.solve_with_assumptions(&assumptions);
+        self.stats.solves += 1;
+        self.stats.solve_time += start.elapsed();
+        (solved == Lbool::True).then(|| self.full_model())
+    }
+
+    /// Restricts the following calls to [`Finder::solve`] to full models containing the given
+    /// literals, replacing previous assumptions
+    ///
+    /// The formula itself is unchanged, so the reduced models are minimal models of the formula
+    /// that agree with the assumptions, without necessarily containing them.
+    pub fn set_assumptions(&mut self, lits: &[isize]) {
+        self.assumptions = lits.iter().map(|&lit| self.internal_lit(lit)).collect();
+        self.ensure_vars();
+    }
+
+    /// Whether [`Finder::set_assumptions`] restricts the full models
+    pub fn has_assumptions(&self) -> bool {
+        !self.assumptions.is_empty()
+    }
+
+    /// Prefers full models containing the given literals in the following calls to
+    /// [`Finder::solve`]
+    ///
+    /// Unlike [`Finder::set_hints`], these accumulate, replacing earlier preferred phases of the
+    /// same variables, and take precedence over the hints. If there is no model agreeing with all
+    /// preferred phases and hints, any model is found.
+    pub fn prefer(&mut self, lits: &[isize]) {
+        for &lit in lits {
+            let lit = self.internal_lit(lit);
+            self.phases.retain(|phase| phase.var() != lit.var());
+            self.phases.push(lit);
+        }
+        self.ensure_vars();
+    }
+
+    /// Sets the preference for keeping or dropping the literal `lit` in the following reductions
+    ///
+    /// `None` removes a previously set preference. The reduced models are minimal regardless of
+    /// the preferences, which only change which literals are tested first.
+    pub fn set_preference(&mut self, lit: isize, preference: Option<Preference>) {
+        // The reduction assumes the negated literals of the model
+        let index = lit_index(!self.internal_lit(lit));
+        self.ensure_vars();
+        let preferences = &mut self.reducer.preferences;
+        if preferences.len() <= index {
+            preferences.resize(index + 1, Preference::key(None));
+        }
+        preferences[index] = Preference::key(preference);
+    }
+
+    /// Classes of equivalent literals implied by the binary clauses loaded so far
+    ///
+    /// Each class is sorted by variable index and its first literal, the representative, is
+    /// positive.
+    pub fn equivalence_classes(&self) -> Vec<Vec<isize>> {
+        equiv::equivalence_classes(&self.binary_clauses)
+            .into_iter()
+            .map(|class| class.into_iter().map(|lit| self.user_lit(lit)).collect())
+            .collect()
+    }
+
+    /// Reduces a model of the formula to a minimal partial assignment that is still a model
+    ///
+    /// Without any clauses, this is the empty assignment.
+    pub fn reduce(&mut self, model: &[isize]) -> Reduced {
+        let chain = match self.chain {
+            Some(chain) => chain,
+            None => return Reduced::default(),
+        };
+        let start = Instant::now();
+
+        if self.reducer.script.is_some()
+            || self.reducer.on_progress.is_some()
+            || self.reducer.on_check.is_some()
+        {
+            let known = self.reducer.user_vars.len();
+            for name in self.var_map.iter().skip(known) {
+                self.reducer.user_vars.push(match name {
+                    VarName::UserVar(user_var) => user_var as usize,
+                    _ => 0,
+                });
+            }
+        }
+
+        // We initialize our assumptions with the negated model, matching the negated encoding of
+        // the clauses in `neg_solver`
+        let mut assumptions: Vec<Lit> = model.iter().map(|&lit| !self.internal_lit(lit)).collect();
+
+        if self.pure_literals {
+            // Occurrences are never decremented, so this is conservative after popping a scope or
+            // deactivating a retractable clause
+            let (occurrences, constraint_vars) = (&self.reducer.occurrences, &self.constraint_vars);
+            assumptions.retain(|&lit| {
+                constraint_vars.contains(&lit.var())
+                    || occurrences
+                        .get(lit_index(!lit))
+                        .is_some_and(|&count| count > 0)
+            });
+        }
+
+        let mut known_essential = vec![];
+        if self.equivalences {
+            let equivalent_vars: HashSet<u32> = equiv::equivalence_classes(&self.binary_clauses)
+                .iter()
+                .flatten()
+                .map(|lit| lit.var())
+                .collect();
+            assumptions.retain(|lit| {
+                let equivalent = equivalent_vars.contains(&lit.var());
+                if equivalent {
+                    known_essential.push(*lit);
+                }
+                !equivalent
+            });
+        }
+
+        let reduction = if let Some(portfolio) = &mut self.portfolio {
+            portfolio.reduce(
+                &mut self.reducer,
+                &mut self.neg_solver,
+                self.speculation.as_mut(),
+                chain,
+                assumptions,
+                &known_essential,
+            )
+        } else {
+            self.reducer.reduce(
+                &mut self.neg_solver,
+                self.speculation.as_mut(),
+                chain,
+                assumptions,
+                &known_essential,
+            )
+        };
+
+        let reduced = Reduced {
+            model: self.user_lits(&reduction.essential),
+            unverified: self.user_lits(&reduction.unverified),
+            interrupted: reduction.interrupted,
+            checks: reduction.checks,
+        };
+
+        if self.warm_start {
+            let hints: Vec<isize> = model
+                .iter()
+                .cloned()
+                .filter(|lit| !reduced.model.contains(lit))
+                .collect();
+            self.set_hints(&hints);
+        }
+
+        self.stats.reductions += 1;
+        self.stats.reduce_time += start.elapsed();
+        self.stats.checks += reduced.checks;
+        self.stats.check_times += reduction.check_times;
+        self.stats.histograms += &reduction.histograms;
+        reduced
+    }
+
+    /// Minimizes the true `minimized` variables of the full model `model`, keeping the `fixed`
+    /// variables
+    ///
+    /// This is parallel circumscription: the result is a full model whose set of true minimized
+    /// variables has no proper subset among the models that agree with it on the fixed variables.
+    /// All other variables may change. The true minimized variables of `model` are removed until
+    /// the positive solver finds no model with fewer of them, so the result agrees with `model` on
+    /// the fixed variables and on the false minimized ones. Like [`Finder::solve`], this respects
+    /// the assumptions set by [`Finder::set_assumptions`].
+    pub fn circumscribe(
+        &mut self,
+        model: &[isize],
+        minimized: &[isize],
+        fixed: &[isize],
+    ) -> Solved {
+        self.flush_pending();
+        let minimized: HashSet<isize> = minimized.iter().map(|lit| lit.abs()).collect();
+        let fixed: HashSet<isize> = fixed.iter().map(|lit| lit.abs()).collect();
+        let mut model = model.to_vec();
+        loop {
+            let mut assumptions = self.pos_assumptions();
+            let mut shrink = vec![];
+            for &lit in &model {
+                let internal = self.internal_lit(lit);
+                if fixed.contains(&lit.abs()) || (minimized.contains(&lit.abs()) && lit < 0) {
+                    assumptions.push(internal);
+                } else if minimized.contains(&lit) {
+                    shrink.push(!internal);
+                }
+            }
+            if shrink.is_empty() {
+                return Solved::Model(model);
+            }
+
+            // One of the true minimized variables has to become false, which is only required
+            // while `activation` is assumed
+            let index = self.var_map.push(VarName::Aux);
+            let activation = Lit::new(index as u32, false).unwrap();
+            self.ensure_vars();
+            shrink.push(!activation);
+            self.pos_solver.add_clause(&shrink);
+            assumptions.push(activation);
+            let solved = self.pos_solver.solve_with_assumptions(&assumptions);
+            self.pos_solver.add_clause(&[!activation]);
+            match solved {
+                Lbool::True => model = self.full_model(),
+                Lbool::False => return Solved::Model(model),
+                Lbool::Undef => return Solved::Unknown,
+            }
+        }
+    }
+
+    /// Whether `lit` is a brave consequence, i.e. contained in some minimal model
+    ///
+    /// A minimal model contains `lit` iff it is the reduced model of a full model containing
+    /// `lit` that stops being a model when `lit` is flipped, as then no reduced model can drop
+    /// `lit`. So instead of enumerating minimal models, this searches for such a full model. When
+    /// a full model remains a model with `lit` flipped, the reduced model of the flipped one
+    /// without `lit` is part of no such full model and is excluded from the following searches.
+    ///
+    /// Unlike [`Finder::solve`], this ignores the assumptions set by [`Finder::set_assumptions`].
+    pub fn brave(&mut self, lit: isize) -> Consequence {
+        self.flush_pending();
+        let lit = self.internal_lit(lit);
+        self.ensure_vars();
+        match self.check_satisfiable() {
+            Lbool::True => (),
+            Lbool::False => return Consequence::Unsat,
+            Lbool::Undef => return Consequence::Unknown,
+        }
+
+        // The excluded reduced models are only excluded while `activation` is assumed
+        let index = self.var_map.push(VarName::Aux);
+        let activation = Lit::new(index as u32, false).unwrap();
+        self.ensure_vars();
+        let mut assumptions = self.activations();
+        assumptions.extend([activation, lit]);
+        let consequence = loop {
+            let model = match self.pos_solver.solve_with_assumptions(&assumptions) {
+                Lbool::True => self.full_model(),
+                Lbool::False => break Consequence::Fails { witness: None },
+                Lbool::Undef => break Consequence::Unknown,
+            };
+            let user_lit = self.user_lit(lit);
+            let flipped: Vec<isize> = model
+                .iter()
+                .map(|&other| if other == user_lit { -other } else { other })
+                .collect();
+            let mut flipped_assumptions = self.activations();
+            flipped_assumptions.extend(flipped.iter().map(|&other| self.internal_lit(other)));
+            match self.pos_solver.solve_with_assumptions(&flipped_assumptions) {
+                Lbool::True => (),
+                Lbool::False => {
+                    break Consequence::Holds {
+                        witness: Some(self.reduce(&model)),
+                    }
+                }
+                Lbool::Undef => break Consequence::Unknown,
+            }
+            let mut exclude: Vec<Lit> = self
+                .reduce(&flipped)
+                .model
+                .iter()
+                .filter(|&&other| other != -user_lit)
+                .map(|&other| !self.internal_lit(other))
+                .collect();
+            exclude.push(!activation);
+            self.pos_solver.add_clause(&exclude);
+        };
+        self.pos_solver.add_clause(&[!activation]);
+        consequence
+    }
+
+    /// Whether `lit` is a cautious consequence, i.e. contained in all minimal models
+    ///
+    /// This holds iff the formula entails `lit`: a model without `lit` has a reduced model without
+    /// it, while every model of an entailing formula contains `lit`. So this takes a single search,
+    /// for a model containing the negation of `lit`.
+    ///
+    /// Unlike [`Finder::solve`], this ignores the assumptions set by [`Finder::set_assumptions`].
+    pub fn cautious(&mut self, lit: isize) -> Consequence {
+        self.flush_pending();
+        let lit = self.internal_lit(lit);
+        self.ensure_vars();
+        match self.check_satisfiable() {
+            Lbool::True => (),
+            Lbool::False => return Consequence::Unsat,
+            Lbool::Undef => return Consequence::Unknown,
+        }
+        let mut assumptions = self.activations();
+        assumptions.push(!lit);
+        match self.pos_solver.solve_with_assumptions(&assumptions) {
+            Lbool::True => {
+                let model = self.full_model();
+                Consequence::Fails {
+                    witness: Some(self.reduce(&model)),
+                }
+            }
+            Lbool::False => Consequence::Holds { witness: None },
+            Lbool::Undef => Consequence::Unknown,
+        }
+    }
+
+    /// The literals of `lits` that are backbone literals, i.e. true in all models of the formula
+    ///
+    /// Each literal is checked by searching for a model containing its negation, as for
+    /// [`Finder::cautious`]. Such a model also shows that the literals it falsifies aren't backbone
+    /// literals, so they aren't checked anymore. If the formula is unsatisfiable, all literals are
+    /// backbone literals. Returns `None` if a solver gave up.
+    ///
+    /// Unlike [`Finder::solve`], this ignores the assumptions set by [`Finder::set_assumptions`].
+    pub fn backbone(&mut self, lits: &[isize]) -> Option<Vec<isize>> {
+        self.flush_pending();
+        let internal: Vec<Lit> = lits.iter().map(|&lit| self.internal_lit(lit)).collect();
+        self.ensure_vars();
+        let mut refuted = vec![false; lits.len()];
+        for index in 0..lits.len() {
+            if refuted[index] {
+                continue;
+            }
+            let mut assumptions = self.activations();
+            assumptions.push(!internal[index]);
+            match self.pos_solver.solve_with_assumptions(&assumptions) {
+                Lbool::True => {
+                    let model: HashSet<isize> = self.full_model().into_iter().collect();
+                    for (refuted, lit) in refuted.iter_mut().zip(lits) {
+                        *refuted |= !model.contains(lit);
+                    }
+                }
+                Lbool::False => (),
+                Lbool::Undef => return None,
+            }
+        }
+        Some(
+            lits.iter()
+                .zip(refuted)
+                .filter(|&(_, refuted)| !refuted)
+                .map(|(&lit, _)| lit)
+                .collect(),
+        )
+    }
+
+    /// Whether the formula is satisfiable, ignoring the assumptions
+    fn check_satisfiable(&mut self) -> Lbool {
+        let activations = self.activations();
+        if activations.is_empty() {
+            self.pos_solver.solve()
+        } else {
+            self.pos_solver.solve_with_assumptions(&activations)
+        }
+    }
+
+    /// Finds a partial assignment with the fewest literals that is a model
+    ///
+    /// See [`Finder::minimum_model_weighted`].
+    pub fn minimum_model(&mut self) -> Optimized {
+        self.minimum_model_weighted(|_| 1)
+    }
+
+    /// Finds a partial assignment that is a model, minimizing the total weight of its literals
+    ///
+    /// `weight` gives the weight of each user literal. The model may contain further literals of
+    /// weight zero. Unlike the models of [`Finder::reduce`], this is exact: no model has a smaller
+    /// weight. As this is much harder, it can take much longer, and it is only interrupted between
+    /// solver calls. Assumptions, hints and preferred phases are ignored. The search is selected
+    /// with [`Builder::optimizer`].
+    pub fn minimum_model_weighted(&mut self, weight: impl Fn(isize) -> u64) -> Optimized {
+        self.flush_pending();
+        self.ensure_vars();
+        let activations = self.activations();
+        match self.pos_solver.solve_with_assumptions(&activations) {
+            Lbool::True => (),
+            Lbool::False => return Optimized::Unsat,
+            Lbool::Undef => return Optimized::Unknown,
+        }
+        let chain = match self.chain {
+            Some(chain) => chain,
+            None => {
+                return Optimized::Model {
+                    model: vec![],
+                    weight: 0,
+                }
+            }
+        };
+
+        // The selecting solver has a variable for each user literal, which is true when the
+        // literal is part of the candidate
+        let user_vars: Vec<(u32, isize)> = self
+            .var_map
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| match name {
+                VarName::UserVar(user_var) => Some((index as u32, user_var)),
+                _ => None,
+            })
+            .collect();
+        let mut selectors = vec![None; self.var_map.len()];
+        for (selector, &(index, _)) in user_vars.iter().enumerate() {
+            selectors[index as usize] = Some(selector as u32);
+        }
+        let select = |lit: Lit| {
+            let selector = selectors[lit.var() as usize].expect("not a user var");
+            Lit::new(2 * selector + lit.isneg() as u32, false).unwrap()
+        };
+        let selected = |model: &[Lbool]| -> Vec<Lit> {
+            user_vars
+                .iter()
+                .flat_map(|&(index, _)| {
+                    let lit = Lit::new(index, false).unwrap();
+                    [lit, !lit]
+                })
+                .filter(|&lit| model[select(lit).var() as usize] == Lbool::True)
+                .collect()
+        };
+
+        let mut solver = SolverConfig::new(&self.builder.pos_options)
+            .expect("configuration was accepted before")
+            .new_solver();
+        solver.new_vars(2 * user_vars.len());
+        let mut soft = vec![];
+        for &(index, user_var) in &user_vars {
+            let lit = Lit::new(index, false).unwrap();
+            solver.add_clause(&[!select(lit), !select(!lit)]);
+            soft.push((select(lit), weight(user_var)));
+            soft.push((select(!lit), weight(-user_var)));
+        }
+
+        let optimizer = self.builder.optimizer;
+        let interrupt = self.builder.interrupt.clone();
+        let mut verify = |model: &[Lbool]| {
+            if interrupt
+                .as_ref()
+                .is_some_and(|interrupt| interrupt.load(std::sync::atomic::Ordering::SeqCst))
+            {
+                return Verdict::Unknown;
+            }
+            let candidate = selected(model);
+
+            // The candidate is a model iff the negative solver can't falsify the formula with it,
+            // where the user variables are negated
+            let mut assumptions = vec![chain];
+            assumptions.extend(candidate.iter().map(|&lit| !lit));
+            match self.neg_solver.solve_with_assumptions(&assumptions) {
+                Lbool::True => (),
+                Lbool::False => return Verdict::Accept,
+                Lbool::Undef => return Verdict::Unknown,
+            }
+
+            // Every model has to contradict the assignment falsifying the formula, and the
+            // positive solver finds the part of it that already falsifies the formula
+            let mut counterexample = vec![];
+            self.neg_solver.model(&mut counterexample);
+            let mut falsifying: Vec<Lit> = user_vars
+                .iter()
+                .map(|&(index, _)| Lit::new(index, counterexample[index as usize] == Lbool::True))
+                .map(Option::unwrap)
+                .collect();
+            falsifying.extend(&activations);
+            match self.pos_solver.solve_with_assumptions(&falsifying) {
+                Lbool::True => unreachable!("the counterexample falsifies the formula"),
+                Lbool::False => (),
+                Lbool::Undef => return Verdict::Unknown,
+            }
+            let mut conflict = vec![];
+            self.pos_solver.conflict(&mut conflict);
+            Verdict::Refine(
+                conflict
+                    .into_iter()
+                    .filter(|lit| selectors[lit.var() as usize].is_some())
+                    .map(select)
+                    .collect(),
+            )
+        };
+
+        match optimize::minimize(optimizer, &mut *solver, &soft, &mut verify) {
+            Some((model, weight)) => Optimized::Model {
+                model: selected(&model)
+                    .into_iter()
+                    .map(|lit| self.user_lit(lit))
+                    .collect(),
+                weight,
+            },
+            None => Optimized::Unknown,
+        }
+    }
+
+    /// Maps literals of the negative solver's assumptions back to user literals
+    fn user_lits<'a>(&self, lits: impl IntoIterator<Item = &'a Lit>) -> Vec<isize> {
+        lits.into_iter().map(|&lit| self.user_lit(!lit)).collect()
+    }
+}
@@ -0,0 +1,136 @@
+//! Weighted MaxSAT for WCNF inputs, finding the assignment that is reduced afterwards
+//!
+//! Soft clauses only select the full model. The reduction then only considers the hard clauses,
+//! as a partial assignment can't entail an optimum.
+use std::io::BufRead;
+
+use anyhow::Context;
+
+use crate::{
+    backend::{Lbool, Lit},
+    input,
+    optimize::{self, Optimizer, Verdict},
+    solver_config::SolverConfig,
+    SolverOptions,
+};
+
+/// A weighted CNF formula
+#[derive(Clone, Default, Debug)]
+pub struct Wcnf {
+    pub hard: Vec<Vec<isize>>,
+    /// Clauses with the weight of violating them
+    pub soft: Vec<(u64, Vec<isize>)>,
+}
+
+/// Parses a WCNF file
+///
+/// Both the format with hard clauses starting with `h` and the older format with a `p wcnf`
+/// header are accepted. In the latter, clauses with a weight of at least the header's top weight
+/// are hard.
+pub fn parse_wcnf(reader: impl BufRead, max_var: usize) -> anyhow::Result<Wcnf> {
+    let mut wcnf = Wcnf::default();
+    let mut top = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        let context = || format!("line {}", index + 1);
+        if let Some(header) = line.strip_prefix("p ") {
+            let fields: Vec<&str> = header.split_ascii_whitespace().collect();
+            match fields[..] {
+                ["wcnf", _, _, weight] => {
+                    top = Some(weight.parse::<u64>().with_context(context)?);
+                }
+                ["wcnf", _, _] => (),
+                _ => anyhow::bail!("{}: invalid header {:?}", context(), line),
+            }
+            continue;
+        }
+
+        let (weight, lits) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let clause = input::parse_clause(lits, max_var).with_context(context)?;
+        if weight == "h" {
+            wcnf.hard.push(clause);
+            continue;
+        }
+        let weight = weight
+            .parse::<u64>()
+            .with_context(|| format!("{}: invalid weight {:?}", context(), weight))?;
+        if top.is_some_and(|top| weight >= top) {
+            wcnf.hard.push(clause);
+        } else if weight > 0 {
+            wcnf.soft.push((weight, clause));
+        }
+    }
+    Ok(wcnf)
+}
+
+/// Result of [`solve`]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum MaxSat {
+    /// A full model of the hard clauses with the minimum total weight of violated soft clauses
+    Optimum { model: Vec<isize>, cost: u64 },
+    /// The hard clauses are unsatisfiable
+    Unsat,
+    /// The solver gave up
+    Unknown,
+}
+
+/// Finds an optimum of `wcnf` using a solver configured by `options`
+///
+/// Each soft clause is extended by a relaxation variable, whose weight is minimized.
+pub fn solve(wcnf: &Wcnf, options: &SolverOptions, optimizer: Optimizer) -> anyhow::Result<MaxSat> {
+    let mut solver = SolverConfig::new(options)?.new_solver();
+    if wcnf.hard.iter().any(Vec::is_empty) {
+        return Ok(MaxSat::Unsat);
+    }
+    let vars = wcnf
+        .hard
+        .iter()
+        .chain(wcnf.soft.iter().map(|(_, clause)| clause))
+        .flatten()
+        .map(|lit| lit.unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    let internal = |lit: isize| Lit::new(lit.unsigned_abs() as u32 - 1, lit < 0).unwrap();
+
+    // cryptominisat can't return the model of a solver without variables
+    solver.new_vars((vars + wcnf.soft.len()).max(1));
+    for clause in &wcnf.hard {
+        let clause: Vec<Lit> = clause.iter().map(|&lit| internal(lit)).collect();
+        solver.add_clause(&clause);
+    }
+    let mut soft = vec![];
+    for (index, (weight, clause)) in wcnf.soft.iter().enumerate() {
+        let relaxation = Lit::new((vars + index) as u32, false).unwrap();
+        let mut clause: Vec<Lit> = clause.iter().map(|&lit| internal(lit)).collect();
+        clause.push(relaxation);
+        solver.add_clause(&clause);
+        soft.push((relaxation, *weight));
+    }
+
+    // The optimizers require the hard clauses to be satisfiable
+    match solver.solve() {
+        Lbool::True => (),
+        Lbool::False => return Ok(MaxSat::Unsat),
+        Lbool::Undef => return Ok(MaxSat::Unknown),
+    }
+    let optimum = optimize::minimize(optimizer, &mut *solver, &soft, &mut |_| Verdict::Accept);
+    Ok(match optimum {
+        Some((model, cost)) => MaxSat::Optimum {
+            model: (1..=vars as isize)
+                .map(|var| {
+                    if model[var as usize - 1] == Lbool::True {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect(),
+            cost,
+        },
+        None => MaxSat::Unknown,
+    })
+}
@@ -0,0 +1,54 @@
+//! Monitoring of the process' memory usage
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Current resident set size of this process in bytes
+///
+/// Only available on Linux.
+pub fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_ascii_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * page_size())
+}
+
+/// Peak resident set size of this process in bytes
+///
+/// Only available on Linux.
+pub fn peak_resident_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_ascii_whitespace().nth(1)?.parse().ok()?;
+    Some(kib << 10)
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as u64 }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+/// Spawns a thread that sets `exceeded` and `interrupt` once the resident set size exceeds
+/// `limit` bytes
+///
+/// Used together with [`Builder::interrupt`](crate::Builder::interrupt) this stops the reduction
+/// gracefully, but a running solver call can still allocate more memory until it finishes.
+pub fn watch_limit(limit: u64, exceeded: Arc<AtomicBool>, interrupt: Arc<AtomicBool>) {
+    thread::spawn(move || loop {
+        if resident_bytes().is_some_and(|bytes| bytes > limit) {
+            exceeded.store(true, Ordering::SeqCst);
+            interrupt.store(true, Ordering::SeqCst);
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    });
+}
@@ -0,0 +1,214 @@
+//! Prometheus metrics of the command line tool's `--metrics`
+//!
+//! The metrics are served over plain HTTP on a separate thread, answering every request with the
+//! text exposition format, so a scraper can monitor a long running enumeration or `--watch`
+//! without additional dependencies. Counters accumulate over all problems of the input, even
+//! though the statistics of a [`Finder`](crate::Finder) start over for each problem.
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::{memory, Stats};
+
+/// Counters of a problem
+#[derive(Default)]
+struct Totals {
+    queries: usize,
+    models: usize,
+    stats: Stats,
+}
+
+impl std::ops::AddAssign<&Totals> for Totals {
+    fn add_assign(&mut self, other: &Totals) {
+        self.queries += other.queries;
+        self.models += other.models;
+        self.stats += &other.stats;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Of the finished problems
+    finished: Totals,
+    /// Of the current problem
+    current: Totals,
+    problems: usize,
+}
+
+/// Shared state of the metrics, cheap to clone
+#[derive(Clone, Default)]
+pub struct Metrics {
+    state: Arc<Mutex<State>>,
+}
+
+impl Metrics {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        // The state stays consistent even if a panic occurred while it was locked
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Updates the counters of the current problem
+    pub fn update(&self, queries: usize, models: usize, stats: &Stats) {
+        let mut state = self.state();
+        state.current = Totals {
+            queries,
+            models,
+            stats: stats.clone(),
+        };
+    }
+
+    /// Starts a new problem, whose counters are added to those of the previous ones
+    pub fn next_problem(&self) {
+        let mut state = self.state();
+        let current = std::mem::take(&mut state.current);
+        state.finished += &current;
+        state.problems += 1;
+    }
+
+    /// Writes the metrics in the Prometheus text exposition format
+    pub fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        let state = self.state();
+        let mut totals = Totals::default();
+        totals += &state.finished;
+        totals += &state.current;
+        let problems = state.problems;
+        drop(state);
+
+        let stats = &totals.stats;
+        let mut metric = |name: &str, kind: &str, help: &str, values: &[(&str, f64)]| {
+            writeln!(out, "# HELP minimal_models_{} {}", name, help)?;
+            writeln!(out, "# TYPE minimal_models_{} {}", name, kind)?;
+            for (labels, value) in values {
+                writeln!(out, "minimal_models_{}{} {}", name, labels, value)?;
+            }
+            io::Result::Ok(())
+        };
+        metric(
+            "problems_total",
+            "counter",
+            "Problems of the input started so far.",
+            &[("", problems as f64)],
+        )?;
+        metric(
+            "queries_total",
+            "counter",
+            "Requests for a model.",
+            &[("", totals.queries as f64)],
+        )?;
+        metric(
+            "models_total",
+            "counter",
+            "Reported minimal models.",
+            &[("", totals.models as f64)],
+        )?;
+        metric(
+            "full_model_searches_total",
+            "counter",
+            "Searches for a full model.",
+            &[("", stats.solves as f64)],
+        )?;
+        metric(
+            "reductions_total",
+            "counter",
+            "Reductions of full models.",
+            &[("", stats.reductions as f64)],
+        )?;
+        metric(
+            "checks_total",
+            "counter",
+            "Candidate checks by their outcome.",
+            &[
+                ("{result=\"sat\"}", stats.checks.sat as f64),
+                ("{result=\"unsat\"}", stats.checks.unsat as f64),
+                ("{result=\"unknown\"}", stats.checks.unknown as f64),
+            ],
+        )?;
+        metric(
+            "blocking_clauses_total",
+            "counter",
+            "Clauses added to block reported models.",
+            &[("", stats.blocked as f64)],
+        )?;
+        metric(
+            "solve_seconds_total",
+            "counter",
+            "Time spent finding full models.",
+            &[("", stats.solve_time.as_secs_f64())],
+        )?;
+        metric(
+            "reduce_seconds_total",
+            "counter",
+            "Time spent reducing full models.",
+            &[("", stats.reduce_time.as_secs_f64())],
+        )?;
+        metric(
+            "check_seconds_total",
+            "counter",
+            "Time spent in candidate checks by their outcome, not counting speculative checks.",
+            &[
+                ("{result=\"sat\"}", stats.check_times.sat.as_secs_f64()),
+                ("{result=\"unsat\"}", stats.check_times.unsat.as_secs_f64()),
+                (
+                    "{result=\"unknown\"}",
+                    stats.check_times.unknown.as_secs_f64(),
+                ),
+            ],
+        )?;
+        if let Some(bytes) = memory::resident_bytes() {
+            metric(
+                "resident_memory_bytes",
+                "gauge",
+                "Resident set size of the process.",
+                &[("", bytes as f64)],
+            )?;
+        }
+        if let Some(bytes) = memory::peak_resident_bytes() {
+            metric(
+                "peak_resident_memory_bytes",
+                "gauge",
+                "Peak resident set size of the process.",
+                &[("", bytes as f64)],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Serves the metrics over HTTP on `addr`, e.g. `127.0.0.1:9100`, on a separate thread
+    ///
+    /// Every request is answered with the metrics, regardless of its path. The thread runs until
+    /// the process exits.
+    pub fn serve(&self, addr: &str) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let metrics = self.clone();
+        thread::spawn(move || {
+            // A failed connection doesn't affect the solving, so errors are ignored
+            for stream in listener.incoming().flatten() {
+                let _ = metrics.respond(stream);
+            }
+        });
+        Ok(())
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> io::Result<()> {
+        // Scrapers send a short GET request, whose content doesn't matter
+        stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+        let mut request = [0; 4096];
+        let _ = stream.read(&mut request)?;
+        let mut body = vec![];
+        self.render(&mut body)?;
+        write!(
+            stream,
+            "HTTP/1.0 200 OK\r\n\
+             Content-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+        stream.flush()
+    }
+}
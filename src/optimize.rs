@@ -0,0 +1,249 @@
+//! Core-guided search for models of minimum weight
+//!
+//! Finding a partial assignment of minimum weight that is a model isn't a plain MaxSAT problem, as
+//! being a model is only checked by the negative solver. Instead, a separate solver selects the
+//! literals of a candidate, starting without any constraints. Each candidate that isn't a model is
+//! refuted by a counterexample, excluding it and similar candidates, until one is a model.
+//!
+//! The selecting solver is optimized using OLL: it is called assuming that no soft literal is
+//! true. Each core of these assumptions is relaxed by a totalizer over its literals, which allows
+//! one of them to be true, and adds the totalizer's output for two of them as a new soft literal.
+//! As every candidate found this way has minimum weight among those that aren't refuted yet, the
+//! first model has minimum weight.
+//!
+//! Alternatively, the linear search (LSU) starts from any model and requires each following
+//! candidate to weigh less than the best model so far, until there is none.
+use crate::{
+    backend::{Lbool, Lit, SatBackend},
+    cardinality::{self, CardinalityEncoding},
+    pb::{self, PbEncoding, Signal},
+};
+
+/// How [`Finder::minimum_model`](crate::Finder::minimum_model) searches for a minimum
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum Optimizer {
+    /// Raises a lower bound using the cores of unsatisfiable candidates (OLL)
+    #[default]
+    CoreGuided,
+    /// Lowers an upper bound using the models found so far (LSU), which is often faster when
+    /// models of low weight are easy to find
+    Linear,
+}
+
+/// Result of [`Finder::minimum_model`](crate::Finder::minimum_model)
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Optimized {
+    /// A partial assignment that is a model of minimum weight
+    Model {
+        model: Vec<isize>,
+        weight: u64,
+    },
+    Unsat,
+    /// A solver gave up or the search was interrupted
+    Unknown,
+}
+
+/// Outcome of checking a candidate
+pub enum Verdict {
+    /// The candidate is a model
+    Accept,
+    /// A clause for the selecting solver excluding the candidate, but no model
+    Refine(Vec<Lit>),
+    Unknown,
+}
+
+/// Minimizes the total weight of the true `soft` literals of `solver` among the models accepted
+/// by `verify`, using `optimizer`
+///
+/// Returns the accepted model of `solver` and its weight, or `None` if a call gave up. The clauses
+/// of `solver` must remain satisfiable when adding the clauses of [`Verdict::Refine`].
+pub fn minimize(
+    optimizer: Optimizer,
+    solver: &mut dyn SatBackend,
+    soft: &[(Lit, u64)],
+    verify: &mut impl FnMut(&[Lbool]) -> Verdict,
+) -> Option<(Vec<Lbool>, u64)> {
+    match optimizer {
+        Optimizer::CoreGuided => core_guided(solver, soft, verify),
+        Optimizer::Linear => linear(solver, soft, verify),
+    }
+}
+
+/// A soft literal, which costs `weight` when true
+struct Soft {
+    lit: Lit,
+    weight: u64,
+    /// For an output of a totalizer, the index of the totalizer and of the output
+    output: Option<(usize, usize)>,
+}
+
+/// Like [`minimize`], raising a lower bound using cores
+fn core_guided(
+    solver: &mut dyn SatBackend,
+    soft: &[(Lit, u64)],
+    verify: &mut impl FnMut(&[Lbool]) -> Verdict,
+) -> Option<(Vec<Lbool>, u64)> {
+    let mut objective: Vec<Soft> = soft
+        .iter()
+        .filter(|&&(_, weight)| weight > 0)
+        .map(|&(lit, weight)| Soft {
+            lit,
+            weight,
+            output: None,
+        })
+        .collect();
+    // The outputs of each totalizer, the `i`-th one is true iff at least `i + 1` inputs are true
+    let mut totalizers: Vec<Vec<Lit>> = vec![];
+    let mut lower_bound = 0;
+    let mut model = vec![];
+    let mut conflict = vec![];
+
+    loop {
+        let assumptions: Vec<Lit> = objective.iter().map(|soft| !soft.lit).collect();
+        match solver.solve_with_assumptions(&assumptions) {
+            Lbool::True => {
+                solver.model(&mut model);
+                match verify(&model) {
+                    Verdict::Accept => return Some((model, lower_bound)),
+                    Verdict::Refine(clause) => solver.add_clause(&clause),
+                    Verdict::Unknown => return None,
+                }
+            }
+            Lbool::False => {
+                // The conflict contains the soft literals of the core
+                solver.conflict(&mut conflict);
+                let weight = objective
+                    .iter()
+                    .filter(|soft| conflict.contains(&soft.lit))
+                    .map(|soft| soft.weight)
+                    .min()
+                    .expect("the selecting solver is satisfiable without assumptions");
+                lower_bound += weight;
+
+                let mut core = vec![];
+                let mut relaxed = vec![];
+                for soft in &mut objective {
+                    if conflict.contains(&soft.lit) {
+                        soft.weight -= weight;
+                        core.push(soft.lit);
+                        relaxed.extend(soft.output);
+                    }
+                }
+                objective.retain(|soft| soft.weight > 0);
+
+                // Allowing one more input of a totalizer to be true costs the weight of the core
+                // when the next output is true
+                for (totalizer, index) in relaxed {
+                    if let Some(&lit) = totalizers[totalizer].get(index + 1) {
+                        add_soft(&mut objective, lit, weight, Some((totalizer, index + 1)));
+                    }
+                }
+
+                if core.len() > 1 {
+                    let mut clauses = vec![];
+                    let mut nvars = solver.nvars();
+                    let outputs = cardinality::encode(
+                        CardinalityEncoding::Totalizer,
+                        &core,
+                        core.len(),
+                        &mut || {
+                            nvars += 1;
+                            Lit::new(nvars - 1, false).unwrap()
+                        },
+                        &mut |clause: &[Lit]| clauses.push(clause.to_vec()),
+                    );
+                    solver.new_vars((nvars - solver.nvars()) as usize);
+                    for clause in &clauses {
+                        solver.add_clause(clause);
+                    }
+                    // The core makes the first output true, so the second one becomes soft
+                    add_soft(
+                        &mut objective,
+                        outputs[1],
+                        weight,
+                        Some((totalizers.len(), 1)),
+                    );
+                    totalizers.push(outputs);
+                }
+            }
+            Lbool::Undef => return None,
+        }
+    }
+}
+
+/// Adds `weight` to the soft literal `lit`, adding it to the objective if necessary
+fn add_soft(objective: &mut Vec<Soft>, lit: Lit, weight: u64, output: Option<(usize, usize)>) {
+    match objective.iter_mut().find(|soft| soft.lit == lit) {
+        Some(soft) => soft.weight += weight,
+        None => objective.push(Soft {
+            lit,
+            weight,
+            output,
+        }),
+    }
+}
+
+/// Like [`minimize`], bounding the weight by that of the best model so far
+fn linear(
+    solver: &mut dyn SatBackend,
+    soft: &[(Lit, u64)],
+    verify: &mut impl FnMut(&[Lbool]) -> Verdict,
+) -> Option<(Vec<Lbool>, u64)> {
+    let terms: Vec<(u128, Lit)> = soft
+        .iter()
+        .filter(|&&(_, weight)| weight > 0)
+        .map(|&(lit, weight)| (weight as u128, lit))
+        .collect();
+    let mut best = None;
+    let mut model = vec![];
+
+    loop {
+        match solver.solve() {
+            Lbool::True => solver.model(&mut model),
+            // Nothing weighs less than the best model
+            Lbool::False => return best,
+            Lbool::Undef => return None,
+        }
+        match verify(&model) {
+            Verdict::Accept => (),
+            Verdict::Refine(clause) => {
+                solver.add_clause(&clause);
+                continue;
+            }
+            Verdict::Unknown => return None,
+        }
+
+        let weight: u64 = terms
+            .iter()
+            .filter(|&&(_, lit)| model[lit.var() as usize] == Lbool::True)
+            .map(|&(weight, _)| weight as u64)
+            .sum();
+        best = Some((model.clone(), weight));
+        if weight == 0 {
+            return best;
+        }
+
+        // Require the next model to weigh at most `weight - 1`
+        let mut clauses = vec![];
+        let mut nvars = solver.nvars();
+        let exceeds = pb::encode(
+            PbEncoding::Bdd,
+            &terms,
+            weight as u128 - 1,
+            &mut || {
+                nvars += 1;
+                Lit::new(nvars - 1, false).unwrap()
+            },
+            &mut |clause: &[Lit]| clauses.push(clause.to_vec()),
+        );
+        solver.new_vars((nvars - solver.nvars()) as usize);
+        for clause in &clauses {
+            solver.add_clause(clause);
+        }
+        match exceeds {
+            Signal::False => unreachable!("the model exceeds the bound"),
+            Signal::True => return best,
+            Signal::Lit(exceeds) => solver.add_clause(&[!exceeds]),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Brute force answers for small formulas, to cross-check the solvers
+//!
+//! Everything is computed by evaluating the formula on all relevant full assignments, so this is
+//! only feasible for a small number of variables.
+use std::collections::HashMap;
+
+/// Upper bound on the number of variables, as assignments are represented as bit sets
+pub const MAX_VARS: usize = 30;
+
+/// A formula over at most [`MAX_VARS`] variables
+pub struct Oracle {
+    /// Bit positions of the variables
+    positions: HashMap<isize, usize>,
+    /// For each clause the mask of its variables and their values satisfying the clause
+    clauses: Vec<(u64, u64)>,
+    /// Clauses that are satisfied by every assignment
+    tautologies: Vec<bool>,
+}
+
+impl Oracle {
+    /// Returns `None` if the formula has more than `max_vars` variables
+    pub fn new(clauses: &[Vec<isize>], max_vars: usize) -> Option<Oracle> {
+        let mut positions = HashMap::new();
+        for &lit in clauses.iter().flatten() {
+            let next = positions.len();
+            positions.entry(lit.abs()).or_insert(next);
+        }
+        if positions.len() > max_vars.min(MAX_VARS) {
+            return None;
+        }
+
+        let mut oracle = Oracle {
+            positions,
+            clauses: vec![],
+            tautologies: vec![],
+        };
+        for clause in clauses {
+            let (mut mask, mut values) = (0, 0);
+            let mut tautology = false;
+            for &lit in clause {
+                let bit = 1 << oracle.positions[&lit.abs()];
+                let value = if lit > 0 { bit } else { 0 };
+                tautology |= mask & bit != 0 && values & bit != value;
+                mask |= bit;
+                values |= value;
+            }
+            oracle.clauses.push((mask, values));
+            oracle.tautologies.push(tautology);
+        }
+        Some(oracle)
+    }
+
+    fn satisfies(&self, assignment: u64) -> bool {
+        self.clauses
+            .iter()
+            .zip(&self.tautologies)
+            .all(|(&(mask, values), &tautology)| tautology || !(assignment ^ values) & mask != 0)
+    }
+
+    /// Returns the mask and values of a partial assignment, ignoring variables not in the formula
+    fn partial(&self, partial: &[isize]) -> (u64, u64) {
+        let (mut mask, mut values) = (0, 0);
+        for lit in partial {
+            if let Some(&position) = self.positions.get(&lit.abs()) {
+                mask |= 1 << position;
+                if *lit > 0 {
+                    values |= 1 << position;
+                }
+            }
+        }
+        (mask, values)
+    }
+
+    /// Whether all full assignments extending `values` on `mask` satisfy the formula
+    fn entails_masked(&self, mask: u64, values: u64) -> bool {
+        let free = !mask & ((1u64 << self.positions.len()) - 1);
+        // Enumerates all subsets of `free`
+        let mut subset = 0u64;
+        loop {
+            if !self.satisfies(values & mask | subset) {
+                return false;
+            }
+            subset = subset.wrapping_sub(free) & free;
+            if subset == 0 {
+                return true;
+            }
+        }
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        (0..1u64 << self.positions.len()).any(|assignment| self.satisfies(assignment))
+    }
+
+    /// Whether every full assignment extending `partial` satisfies the formula
+    pub fn entails(&self, partial: &[isize]) -> bool {
+        let (mask, values) = self.partial(partial);
+        self.entails_masked(mask, values)
+    }
+
+    /// Whether `partial` entails the formula, but no partial assignment obtained by removing a
+    /// literal does
+    ///
+    /// By monotonicity of entailment, this means no proper subset of `partial` entails the formula.
+    pub fn is_minimal(&self, partial: &[isize]) -> bool {
+        let (mask, values) = self.partial(partial);
+        self.entails_masked(mask, values)
+            && partial.iter().all(|lit| {
+                // Literals of variables that don't occur in the formula can always be removed
+                self.positions
+                    .get(&lit.abs())
+                    .is_some_and(|&position| !self.entails_masked(mask & !(1 << position), values))
+            })
+    }
+}
@@ -0,0 +1,166 @@
+use std::cmp::Reverse;
+
+use crate::{
+    backend::Lit,
+    script::{Candidate, Script},
+};
+
+/// Order in which candidate literals are tested for being essential
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CandidateOrder {
+    /// Whatever order the solver's model and conflicts produce
+    Arbitrary,
+    /// Literals occurring in fewer clauses are tested first
+    Occurrence,
+    /// Literals that appeared in fewer previous conflicts are tested first
+    Core,
+}
+
+impl std::str::FromStr for CandidateOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s {
+            "arbitrary" => CandidateOrder::Arbitrary,
+            "occurrence" => CandidateOrder::Occurrence,
+            "core" => CandidateOrder::Core,
+            _ => anyhow::bail!("unknown candidate order {:?}", s),
+        })
+    }
+}
+
+/// A preference for keeping or dropping a literal of the model when reducing it
+///
+/// Literals are tested in three groups: those preferred to be dropped first, then those without a
+/// preference and last those preferred to be kept. The selected [`CandidateOrder`] applies within
+/// each group. A tested literal is dropped if the literals still present without it suffice, so
+/// testing a literal earlier makes it more likely to be dropped.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Preference {
+    Drop,
+    Keep,
+}
+
+impl Preference {
+    /// Key for [`apply_preferences`], as candidates with larger keys are tested later
+    pub fn key(preference: Option<Preference>) -> u8 {
+        match preference {
+            Some(Preference::Drop) => 0,
+            None => 1,
+            Some(Preference::Keep) => 2,
+        }
+    }
+}
+
+/// Dense index of a literal, used to keep per-literal counters in a `Vec`
+pub fn lit_index(lit: Lit) -> usize {
+    (lit.var() as usize) << 1 | lit.isneg() as usize
+}
+
+pub fn count(counter: &mut Vec<usize>, lit: Lit) {
+    let index = lit_index(lit);
+    if counter.len() <= index {
+        counter.resize(index + 1, 0);
+    }
+    counter[index] += 1;
+}
+
+/// Sorts `assumptions` such that the candidate to test next is at the end
+///
+/// Ties keep their relative order. Instead of using a stable sort, which allocates, `keyed` is used
+/// as a reusable buffer to sort the assumptions together with their key and position.
+pub fn order_candidates(
+    order: CandidateOrder,
+    occurrences: &[usize],
+    core_counts: &[usize],
+    assumptions: &mut [Lit],
+    keyed: &mut Vec<(Reverse<usize>, usize, Lit)>,
+) {
+    let counter = match order {
+        CandidateOrder::Arbitrary => return,
+        CandidateOrder::Occurrence => occurrences,
+        CandidateOrder::Core => core_counts,
+    };
+    sort_keyed(assumptions, keyed, |lit| {
+        counter.get(lit_index(lit)).cloned().unwrap_or(0)
+    });
+}
+
+/// Stably sorts `assumptions` such that the candidates with the largest keys in `preferences` are
+/// tested last, where literals without an entry have the key of no preference
+pub fn apply_preferences(
+    preferences: &[u8],
+    assumptions: &mut [Lit],
+    keyed: &mut Vec<(Reverse<usize>, usize, Lit)>,
+) {
+    if preferences.is_empty() {
+        return;
+    }
+    let neutral = Preference::key(None);
+    sort_keyed(assumptions, keyed, |lit| {
+        preferences.get(lit_index(lit)).cloned().unwrap_or(neutral) as usize
+    });
+}
+
+/// Stably sorts `assumptions` such that the candidates with the smallest rank computed by
+/// `script` are tested first
+pub fn apply_script(
+    script: &Script,
+    candidate: impl Fn(Lit) -> Candidate,
+    assumptions: &mut [Lit],
+    keyed: &mut Vec<(Reverse<usize>, usize, Lit)>,
+) {
+    sort_keyed(assumptions, keyed, |lit| {
+        // Maps the rank to an integer of the same order, where NaN is largest
+        let bits = script.rank(&candidate(lit)).to_bits();
+        let key = if bits >> 63 == 1 {
+            !bits
+        } else {
+            bits | 1 << 63
+        };
+        key as usize
+    });
+}
+
+/// Stably sorts `assumptions` by descending key, so the smallest key ends up at the end
+fn sort_keyed(
+    assumptions: &mut [Lit],
+    keyed: &mut Vec<(Reverse<usize>, usize, Lit)>,
+    key: impl Fn(Lit) -> usize,
+) {
+    keyed.clear();
+    keyed.extend(
+        assumptions
+            .iter()
+            .enumerate()
+            .map(|(position, &lit)| (Reverse(key(lit)), position, lit)),
+    );
+    keyed.sort_unstable();
+    for (assumption, &(_, _, lit)) in assumptions.iter_mut().zip(keyed.iter()) {
+        *assumption = lit;
+    }
+}
+
+/// Small deterministic PRNG (splitmix64), so shuffled runs can be reproduced from a seed
+#[derive(Clone)]
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
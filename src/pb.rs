@@ -0,0 +1,255 @@
+//! Encodings of pseudo-Boolean constraints
+//!
+//! A constraint `c_1 l_1 + ... + c_n l_n <= bound` with positive coefficients is encoded as a
+//! circuit whose output is true exactly when the constraint is violated. Every gate is defined in
+//! both directions, so the clauses can be added to the positive solver as well as to the negative
+//! solver, where the output becomes the indicator of the constraint.
+//!
+//! Gates with constant inputs are simplified away, so the output can be a constant, e.g. when no
+//! assignment satisfies the constraint.
+use std::{cmp::Reverse, collections::HashMap, ops::Not};
+
+use crate::backend::Lit;
+
+/// How a pseudo-Boolean constraint is encoded into clauses
+///
+/// All encodings are equivalent, but differ in size and propagation strength, which affects the
+/// performance of the reduction.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum PbEncoding {
+    /// A decision diagram over the literals, whose nodes are the sums still allowed
+    ///
+    /// Propagates well, but can be exponential in the number of literals for unfavourable
+    /// coefficients.
+    #[default]
+    Bdd,
+    /// A network of binary adders followed by a comparison with the bound, with `O(n log c)`
+    /// clauses for `n` literals and coefficients up to `c`
+    Adder,
+}
+
+/// The output of a gate, which may be constant
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Signal {
+    False,
+    True,
+    Lit(Lit),
+}
+
+impl Not for Signal {
+    type Output = Signal;
+
+    fn not(self) -> Signal {
+        match self {
+            Signal::False => Signal::True,
+            Signal::True => Signal::False,
+            Signal::Lit(lit) => Signal::Lit(!lit),
+        }
+    }
+}
+
+/// Returns the signal that is true iff the weighted sum of `terms` exceeds `bound`
+///
+/// `new_var` allocates auxiliary variables and `add_clause` receives the defining clauses.
+pub fn encode(
+    encoding: PbEncoding,
+    terms: &[(u128, Lit)],
+    bound: u128,
+    new_var: &mut impl FnMut() -> Lit,
+    add_clause: &mut impl FnMut(&[Lit]),
+) -> Signal {
+    let mut circuit = Circuit {
+        new_var,
+        add_clause,
+    };
+    match encoding {
+        PbEncoding::Bdd => bdd(terms, bound, &mut circuit),
+        PbEncoding::Adder => adder(terms, bound, &mut circuit),
+    }
+}
+
+/// Builds gates, each defined by a fresh variable
+struct Circuit<'a, V, C> {
+    new_var: &'a mut V,
+    add_clause: &'a mut C,
+}
+
+impl<V: FnMut() -> Lit, C: FnMut(&[Lit])> Circuit<'_, V, C> {
+    fn and(&mut self, a: Signal, b: Signal) -> Signal {
+        match (a, b) {
+            (Signal::False, _) | (_, Signal::False) => Signal::False,
+            (Signal::True, other) | (other, Signal::True) => other,
+            (Signal::Lit(a), Signal::Lit(b)) if a == b => Signal::Lit(a),
+            (Signal::Lit(a), Signal::Lit(b)) if a == !b => Signal::False,
+            (Signal::Lit(a), Signal::Lit(b)) => {
+                let output = (self.new_var)();
+                (self.add_clause)(&[!output, a]);
+                (self.add_clause)(&[!output, b]);
+                (self.add_clause)(&[output, !a, !b]);
+                Signal::Lit(output)
+            }
+        }
+    }
+
+    fn or(&mut self, a: Signal, b: Signal) -> Signal {
+        !self.and(!a, !b)
+    }
+
+    fn xor(&mut self, a: Signal, b: Signal) -> Signal {
+        match (a, b) {
+            (Signal::False, other) | (other, Signal::False) => other,
+            (Signal::True, other) | (other, Signal::True) => !other,
+            (Signal::Lit(a), Signal::Lit(b)) if a == b => Signal::False,
+            (Signal::Lit(a), Signal::Lit(b)) if a == !b => Signal::True,
+            (Signal::Lit(a), Signal::Lit(b)) => {
+                let output = (self.new_var)();
+                (self.add_clause)(&[!output, a, b]);
+                (self.add_clause)(&[!output, !a, !b]);
+                (self.add_clause)(&[output, !a, b]);
+                (self.add_clause)(&[output, a, !b]);
+                Signal::Lit(output)
+            }
+        }
+    }
+
+    /// If `cond` then `then` else `other`
+    fn ite(&mut self, cond: Lit, then: Signal, other: Signal) -> Signal {
+        match (then, other) {
+            _ if then == other => then,
+            (Signal::True, _) => self.or(Signal::Lit(cond), other),
+            (Signal::False, _) => self.and(Signal::Lit(!cond), other),
+            (_, Signal::True) => self.or(Signal::Lit(!cond), then),
+            (_, Signal::False) => self.and(Signal::Lit(cond), then),
+            (Signal::Lit(then), Signal::Lit(other)) => {
+                let output = (self.new_var)();
+                (self.add_clause)(&[!output, !cond, then]);
+                (self.add_clause)(&[!output, cond, other]);
+                (self.add_clause)(&[output, !cond, !then]);
+                (self.add_clause)(&[output, cond, !other]);
+                // Redundant, but they propagate the output when both branches agree
+                (self.add_clause)(&[!output, then, other]);
+                (self.add_clause)(&[output, !then, !other]);
+                Signal::Lit(output)
+            }
+        }
+    }
+
+    /// Returns the sum and the carry
+    fn half_adder(&mut self, a: Signal, b: Signal) -> (Signal, Signal) {
+        (self.xor(a, b), self.and(a, b))
+    }
+}
+
+/// A decision diagram testing the literals in order of decreasing coefficients
+///
+/// The node for the `i`-th literal and a remaining bound `b` is true iff the sum of the terms from
+/// the `i`-th one on exceeds `b`. Nodes with equal arguments are shared.
+fn bdd<V: FnMut() -> Lit, C: FnMut(&[Lit])>(
+    terms: &[(u128, Lit)],
+    bound: u128,
+    circuit: &mut Circuit<V, C>,
+) -> Signal {
+    let mut terms = terms.to_vec();
+    terms.sort_by_key(|&(coefficient, _)| Reverse(coefficient));
+    // `suffix_sums[i]` is the largest sum of the terms from the `i`-th one on
+    let mut suffix_sums = vec![0; terms.len() + 1];
+    for i in (0..terms.len()).rev() {
+        suffix_sums[i] = suffix_sums[i + 1] + terms[i].0;
+    }
+
+    fn node<V: FnMut() -> Lit, C: FnMut(&[Lit])>(
+        terms: &[(u128, Lit)],
+        suffix_sums: &[u128],
+        nodes: &mut HashMap<(usize, u128), Signal>,
+        i: usize,
+        bound: u128,
+        circuit: &mut Circuit<V, C>,
+    ) -> Signal {
+        // This also covers running out of terms, as the last suffix sum is zero
+        if suffix_sums[i] <= bound {
+            return Signal::False;
+        }
+        if let Some(&signal) = nodes.get(&(i, bound)) {
+            return signal;
+        }
+        let (coefficient, lit) = terms[i];
+        let then = match bound.checked_sub(coefficient) {
+            Some(rest) => node(terms, suffix_sums, nodes, i + 1, rest, circuit),
+            None => Signal::True,
+        };
+        let other = node(terms, suffix_sums, nodes, i + 1, bound, circuit);
+        let signal = circuit.ite(lit, then, other);
+        nodes.insert((i, bound), signal);
+        signal
+    }
+
+    node(&terms, &suffix_sums, &mut HashMap::new(), 0, bound, circuit)
+}
+
+/// Sums the terms in binary and compares the sum with the bound
+///
+/// Each literal is added to the bits set in its coefficient. The bits of one weight are reduced
+/// by full and half adders, passing the carries on to the next weight, until only one remains.
+fn adder<V: FnMut() -> Lit, C: FnMut(&[Lit])>(
+    terms: &[(u128, Lit)],
+    bound: u128,
+    circuit: &mut Circuit<V, C>,
+) -> Signal {
+    let mut buckets: Vec<Vec<Signal>> = vec![];
+    for &(coefficient, lit) in terms {
+        for bit in 0..u128::BITS as usize {
+            if coefficient >> bit & 1 == 1 {
+                if buckets.len() <= bit {
+                    buckets.resize(bit + 1, vec![]);
+                }
+                buckets[bit].push(Signal::Lit(lit));
+            }
+        }
+    }
+
+    let mut sum = vec![];
+    let mut bit = 0;
+    while bit < buckets.len() {
+        // Taking from the front and adding to the back keeps the adder trees balanced
+        let mut bucket = std::mem::take(&mut buckets[bit]);
+        let mut next = 0;
+        let mut carries = vec![];
+        while bucket.len() - next >= 2 {
+            let (a, b) = (bucket[next], bucket[next + 1]);
+            let (partial, carry) = if bucket.len() - next >= 3 {
+                let c = bucket[next + 2];
+                next += 3;
+                let (partial, first) = circuit.half_adder(a, b);
+                let (partial, second) = circuit.half_adder(partial, c);
+                (partial, circuit.or(first, second))
+            } else {
+                next += 2;
+                circuit.half_adder(a, b)
+            };
+            bucket.push(partial);
+            carries.push(carry);
+        }
+        sum.push(bucket.get(next).copied().unwrap_or(Signal::False));
+        if !carries.is_empty() {
+            if buckets.len() <= bit + 1 {
+                buckets.push(vec![]);
+            }
+            buckets[bit + 1].extend(carries);
+        }
+        bit += 1;
+    }
+
+    // From the least significant bit up, whether the lower bits of the sum exceed those of the
+    // bound
+    let bits = sum.len().max((u128::BITS - bound.leading_zeros()) as usize);
+    let mut exceeds = Signal::False;
+    for bit in 0..bits {
+        let digit = sum.get(bit).copied().unwrap_or(Signal::False);
+        exceeds = if bound >> bit & 1 == 1 {
+            circuit.and(digit, exceeds)
+        } else {
+            circuit.or(digit, exceeds)
+        };
+    }
+    exceeds
+}
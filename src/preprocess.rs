@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+
+/// Removes duplicate literals of a clause, keeping their first occurrence, or returns `None` if the
+/// clause is a tautology
+///
+/// Unlike [`simplify`] this keeps the order of the literals.
+pub fn normalize(clause: &[isize]) -> Option<Vec<isize>> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(clause.len());
+    for &lit in clause {
+        if seen.contains(&-lit) {
+            return None;
+        }
+        if seen.insert(lit) {
+            normalized.push(lit);
+        }
+    }
+    Some(normalized)
+}
+
+/// Simplifies a batch of clauses without changing the set of models
+///
+/// As the simplified formula is equivalent to the original one, this also preserves all minimal
+/// models. For this reason there is no pure literal elimination, which only preserves
+/// satisfiability.
+///
+/// This removes tautologies, duplicate literals and duplicate or subsumed clauses and performs
+/// unit propagation. `units` contains the unit clauses of previous batches, which are not repeated
+/// in the output, and is extended with the new units of this batch, which are part of the output.
+/// An unsatisfiable batch is simplified to the empty clause.
+pub fn simplify(clauses: Vec<Vec<isize>>, units: &mut HashSet<isize>) -> Vec<Vec<isize>> {
+    let mut clauses: Vec<Vec<isize>> = clauses
+        .into_iter()
+        .filter_map(|mut clause| {
+            clause.sort_unstable_by_key(|&lit| (lit.abs(), lit));
+            clause.dedup();
+            let tautology = clause.windows(2).any(|pair| pair[0] == -pair[1]);
+            (!tautology).then_some(clause)
+        })
+        .collect();
+
+    // Unit propagation until no new units are found
+    let mut new_units = vec![];
+    loop {
+        let mut found_unit = false;
+        let mut remaining = vec![];
+        for mut clause in clauses {
+            if clause.iter().any(|lit| units.contains(lit)) {
+                continue;
+            }
+            clause.retain(|lit| !units.contains(&-lit));
+            match clause.len() {
+                0 => return vec![vec![]],
+                1 => {
+                    units.insert(clause[0]);
+                    new_units.push(clause[0]);
+                    found_unit = true;
+                }
+                _ => remaining.push(clause),
+            }
+        }
+        clauses = remaining;
+        if !found_unit {
+            break;
+        }
+    }
+
+    // Forward subsumption, checking shorter clauses first. Indexing each kept clause by its first
+    // literal is sufficient, as any subsuming clause's first literal is contained in the subsumed
+    // clause.
+    clauses.sort_by_key(|clause| clause.len());
+    let mut kept: Vec<Vec<isize>> = vec![];
+    let mut occurrences: HashMap<isize, Vec<usize>> = HashMap::new();
+    for clause in clauses {
+        let subsumed = clause.iter().any(|lit| {
+            occurrences.get(lit).is_some_and(|candidates| {
+                candidates
+                    .iter()
+                    .any(|&index| kept[index].iter().all(|lit| clause.contains(lit)))
+            })
+        });
+        if !subsumed {
+            occurrences.entry(clause[0]).or_default().push(kept.len());
+            kept.push(clause);
+        }
+    }
+
+    new_units
+        .into_iter()
+        .map(|lit| vec![lit])
+        .chain(kept)
+        .collect()
+}
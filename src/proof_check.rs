@@ -0,0 +1,267 @@
+//! Checks of the proofs written by [`Builder::pos_proof`](crate::Builder::pos_proof) and
+//! [`Builder::neg_proof`](crate::Builder::neg_proof), for `--check-proofs`
+//!
+//! Every derived clause has to follow by unit propagation over its hints, in the given order: with
+//! the negation of the clause assigned, each hint has to be unit, assigning its remaining literal,
+//! until the last one is falsified. As the hints leave no choice, no search is needed and external
+//! checkers like drat-trim aren't required, though they accept the same proofs. DRAT proofs have
+//! no hints, so they are first found by unit propagation over all clauses, which is much slower.
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    fs,
+    path::Path,
+};
+
+use anyhow::Context;
+
+use crate::{backend::formula_path, ProofFormat};
+
+/// Result of a successful check
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct CheckedProof {
+    /// Number of clauses derived by the proof
+    pub derived: usize,
+    /// Whether the proof derives the empty clause, i.e. refutes the formula without assumptions
+    pub refutation: bool,
+}
+
+/// The clauses of a proof that weren't deleted, by their id
+#[derive(Default)]
+struct Checker {
+    clauses: HashMap<u64, Vec<i64>>,
+    checked: CheckedProof,
+}
+
+impl Checker {
+    fn original(&mut self, id: u64, lits: Vec<i64>) {
+        self.checked.refutation |= lits.is_empty();
+        self.clauses.insert(id, lits);
+    }
+
+    fn add(&mut self, id: u64, lits: Vec<i64>, hints: &[u64]) -> anyhow::Result<()> {
+        let mut assigned: HashSet<i64> = lits.iter().map(|&lit| -lit).collect();
+        let mut falsified = false;
+        for hint in hints {
+            let clause = self
+                .clauses
+                .get(hint)
+                .ok_or_else(|| anyhow::anyhow!("unknown hint {}", hint))?;
+            if clause.iter().any(|lit| assigned.contains(lit)) {
+                anyhow::bail!("hint {} is satisfied", hint);
+            }
+            let mut open = clause.iter().filter(|&lit| !assigned.contains(&-lit));
+            match (open.next(), open.next()) {
+                (None, _) => {
+                    falsified = true;
+                    break;
+                }
+                (Some(&lit), None) => {
+                    assigned.insert(lit);
+                }
+                (Some(_), Some(_)) => anyhow::bail!("hint {} isn't unit", hint),
+            }
+        }
+        if !falsified {
+            anyhow::bail!("hints of clause {} don't lead to a conflict", id);
+        }
+        self.checked.derived += 1;
+        self.original(id, lits);
+        Ok(())
+    }
+
+    /// Hints for `lits`, the clauses that became unit or falsified while unit propagating its
+    /// negation over all clauses, or `None` if there is no conflict
+    fn find_hints(&self, lits: &[i64]) -> Option<Vec<u64>> {
+        let mut ids: Vec<u64> = self.clauses.keys().cloned().collect();
+        ids.sort_unstable();
+        let mut assigned: HashSet<i64> = lits.iter().map(|&lit| -lit).collect();
+        let mut hints = vec![];
+        loop {
+            let mut propagated = false;
+            for id in ids.iter() {
+                let clause = &self.clauses[id];
+                if clause.iter().any(|lit| assigned.contains(lit)) {
+                    continue;
+                }
+                let mut open = clause.iter().filter(|&lit| !assigned.contains(&-lit));
+                match (open.next(), open.next()) {
+                    (None, _) => {
+                        hints.push(*id);
+                        return Some(hints);
+                    }
+                    (Some(&lit), None) => {
+                        assigned.insert(lit);
+                        hints.push(*id);
+                        propagated = true;
+                    }
+                    (Some(_), Some(_)) => (),
+                }
+            }
+            if !propagated {
+                return None;
+            }
+        }
+    }
+
+    fn delete(&mut self, id: u64) -> anyhow::Result<()> {
+        match self.clauses.remove(&id) {
+            Some(_) => Ok(()),
+            None => anyhow::bail!("deleting unknown clause {}", id),
+        }
+    }
+}
+
+/// Parses numbers up to a terminating `0`, returning them and the remaining tokens
+fn numbers<'a>(tokens: &'a [&'a str]) -> anyhow::Result<(Vec<i64>, &'a [&'a str])> {
+    let mut numbers = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        let number: i64 = token
+            .parse()
+            .with_context(|| format!("invalid number {:?}", token))?;
+        if number == 0 {
+            return Ok((numbers, &tokens[index + 1..]));
+        }
+        numbers.push(number);
+    }
+    anyhow::bail!("missing terminating 0")
+}
+
+fn ids(numbers: Vec<i64>) -> anyhow::Result<Vec<u64>> {
+    numbers
+        .into_iter()
+        .map(|number| u64::try_from(number).context("negative clause id"))
+        .collect()
+}
+
+/// Checks an LRAT proof of the DIMACS `formula`, whose clauses have the ids 1, 2, ...
+pub fn check_lrat(formula: &str, proof: &str) -> anyhow::Result<CheckedProof> {
+    let mut checker = Checker::default();
+    let mut id = 0;
+    for (index, line) in formula.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        match tokens.first() {
+            None | Some(&"c") | Some(&"p") => continue,
+            Some(_) => (),
+        }
+        let (lits, _) = numbers(&tokens).with_context(|| format!("formula line {}", index + 1))?;
+        id += 1;
+        checker.original(id, lits);
+    }
+    for (index, line) in proof.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        let result = match tokens[..] {
+            [] => continue,
+            [_, "d", ref rest @ ..] => numbers(rest).and_then(|(deleted, _)| {
+                ids(deleted)?
+                    .into_iter()
+                    .try_for_each(|id| checker.delete(id))
+            }),
+            [id, ref rest @ ..] => id
+                .parse()
+                .with_context(|| format!("invalid clause id {:?}", id))
+                .and_then(|id| {
+                    let (lits, rest) = numbers(rest)?;
+                    let (hints, _) = numbers(rest)?;
+                    checker.add(id, lits, &ids(hints)?)
+                }),
+        };
+        result.with_context(|| format!("proof line {}", index + 1))?;
+    }
+    Ok(checker.checked)
+}
+
+/// Checks a FRAT proof, which contains its formula, requiring hints for all derived clauses
+pub fn check_frat(proof: &str) -> anyhow::Result<CheckedProof> {
+    let mut checker = Checker::default();
+    for (index, line) in proof.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        let result = match tokens[..] {
+            [] => continue,
+            [kind, id, ref rest @ ..] => id
+                .parse()
+                .with_context(|| format!("invalid clause id {:?}", id))
+                .and_then(|id| {
+                    let (lits, rest) = numbers(rest)?;
+                    match (kind, rest) {
+                        ("o", _) => {
+                            checker.original(id, lits);
+                            Ok(())
+                        }
+                        ("a", ["l", rest @ ..]) => {
+                            let (hints, _) = numbers(rest)?;
+                            checker.add(id, lits, &ids(hints)?)
+                        }
+                        ("a", _) => anyhow::bail!("clause {} without hints", id),
+                        ("d", _) => checker.delete(id),
+                        ("f", _) => Ok(()),
+                        _ => anyhow::bail!("unknown line type {:?}", kind),
+                    }
+                }),
+            _ => Err(anyhow::anyhow!("missing clause id")),
+        };
+        result.with_context(|| format!("proof line {}", index + 1))?;
+    }
+    Ok(checker.checked)
+}
+
+/// Checks a DRAT proof of the DIMACS `formula`, requiring each added clause to follow by unit
+/// propagation, without using the RAT property
+pub fn check_drat(formula: &str, proof: &str) -> anyhow::Result<CheckedProof> {
+    let mut checker = Checker::default();
+    // Deletions refer to clauses by their literals
+    let mut ids: HashMap<Vec<i64>, Vec<u64>> = HashMap::new();
+    let mut next_id = 0;
+    let key = |lits: &[i64]| {
+        let mut key = lits.to_vec();
+        key.sort_unstable();
+        key
+    };
+    for (index, line) in formula.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        match tokens.first() {
+            None | Some(&"c") | Some(&"p") => continue,
+            Some(_) => (),
+        }
+        let (lits, _) = numbers(&tokens).with_context(|| format!("formula line {}", index + 1))?;
+        next_id += 1;
+        ids.entry(key(&lits)).or_default().push(next_id);
+        checker.original(next_id, lits);
+    }
+    for (index, line) in proof.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        let result = match tokens[..] {
+            [] => continue,
+            ["d", ref rest @ ..] => numbers(rest).and_then(|(lits, _)| {
+                let id = ids
+                    .get_mut(&key(&lits))
+                    .and_then(|ids| ids.pop())
+                    .ok_or_else(|| anyhow::anyhow!("deleting unknown clause {:?}", lits))?;
+                checker.delete(id)
+            }),
+            _ => numbers(&tokens).and_then(|(lits, _)| {
+                let hints = checker.find_hints(&lits).ok_or_else(|| {
+                    anyhow::anyhow!("clause {:?} doesn't lead to a conflict", lits)
+                })?;
+                next_id += 1;
+                ids.entry(key(&lits)).or_default().push(next_id);
+                checker.add(next_id, lits, &hints)
+            }),
+        };
+        result.with_context(|| format!("proof line {}", index + 1))?;
+    }
+    Ok(checker.checked)
+}
+
+/// Checks the proof at `path` in the given format, reading the formula of an LRAT or DRAT proof
+/// from `path` followed by `.cnf`
+pub fn check_file(path: &Path, format: ProofFormat) -> anyhow::Result<CheckedProof> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))
+    };
+    match format {
+        ProofFormat::Lrat => check_lrat(&read(&formula_path(path))?, &read(path)?),
+        ProofFormat::Frat => check_frat(&read(path)?),
+        ProofFormat::Drat => check_drat(&read(&formula_path(path))?, &read(path)?),
+    }
+}
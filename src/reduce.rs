@@ -0,0 +1,778 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    backend::{Lbool, Lit, SatBackend},
+    order::{
+        apply_preferences, apply_script, count, lit_index, order_candidates, CandidateOrder, Rng,
+    },
+    script::{Candidate, Script},
+    solver_config::SolverConfig,
+    speculate::{ensure_vars, CheckBuffers, Speculation},
+};
+
+/// Outcome of checking whether a candidate is essential
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Check {
+    /// Without the candidate the formula can be falsified, so it is essential
+    Sat,
+    /// The candidate isn't needed
+    Unsat,
+    /// The solver gave up, so we conservatively keep the candidate
+    Unknown,
+}
+
+impl Check {
+    /// Checks the given assumptions, storing the conflict in `conflict` if the result is UNSAT
+    pub fn run(solver: &mut dyn SatBackend, assumptions: &[Lit], conflict: &mut Vec<Lit>) -> Check {
+        match solver.solve_with_assumptions(assumptions) {
+            Lbool::True => Check::Sat,
+            Lbool::False => {
+                solver.conflict(conflict);
+                Check::Unsat
+            }
+            Lbool::Undef => Check::Unknown,
+        }
+    }
+}
+
+/// Number of candidate checks of a reduction by their outcome
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Checks {
+    /// Checks finding an essential literal
+    pub sat: usize,
+    /// Checks removing the candidate
+    pub unsat: usize,
+    /// Checks where the solver gave up
+    pub unknown: usize,
+}
+
+impl Checks {
+    fn record(&mut self, check: Check) {
+        match check {
+            Check::Sat => self.sat += 1,
+            Check::Unsat => self.unsat += 1,
+            Check::Unknown => self.unknown += 1,
+        }
+    }
+
+    /// Number of all checks, including speculative ones
+    pub fn total(&self) -> usize {
+        self.sat + self.unsat + self.unknown
+    }
+}
+
+impl std::ops::AddAssign for Checks {
+    fn add_assign(&mut self, other: Checks) {
+        self.sat += other.sat;
+        self.unsat += other.unsat;
+        self.unknown += other.unknown;
+    }
+}
+
+/// Time spent in candidate checks by their outcome
+///
+/// Only the checks on the calling thread are timed, speculative checks on the workers overlap
+/// with them.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct CheckTimes {
+    pub sat: Duration,
+    pub unsat: Duration,
+    pub unknown: Duration,
+}
+
+impl CheckTimes {
+    fn record(&mut self, check: Check, time: Duration) {
+        match check {
+            Check::Sat => self.sat += time,
+            Check::Unsat => self.unsat += time,
+            Check::Unknown => self.unknown += time,
+        }
+    }
+
+    /// Time spent in all timed checks
+    pub fn total(&self) -> Duration {
+        self.sat + self.unsat + self.unknown
+    }
+}
+
+impl std::ops::AddAssign for CheckTimes {
+    fn add_assign(&mut self, other: CheckTimes) {
+        self.sat += other.sat;
+        self.unsat += other.unsat;
+        self.unknown += other.unknown;
+    }
+}
+
+/// Counts of values grouped into buckets of powers of two, `0`, `1`, `2-3`, `4-7` and so on
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Histogram {
+    /// Count of each bucket by its smallest value
+    buckets: BTreeMap<u64, usize>,
+}
+
+impl Histogram {
+    pub fn record(&mut self, value: u64) {
+        let bucket = match value {
+            0 => 0,
+            _ => 1 << value.ilog2(),
+        };
+        *self.buckets.entry(bucket).or_default() += 1;
+    }
+
+    /// The nonempty buckets as smallest value, largest value and count, in increasing order
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64, usize)> + '_ {
+        self.buckets
+            .iter()
+            .map(|(&low, &count)| (low, (2 * low).max(1) - 1, count))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+impl std::ops::AddAssign<&Histogram> for Histogram {
+    fn add_assign(&mut self, other: &Histogram) {
+        for (&bucket, &count) in &other.buckets {
+            *self.buckets.entry(bucket).or_default() += count;
+        }
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+        for (index, (low, high, count)) in self.buckets().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            if low == high {
+                write!(f, "{}: {}", low, count)?;
+            } else {
+                write!(f, "{}-{}: {}", low, high, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Distributions of the candidate checks of reductions
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct CheckHistograms {
+    /// Number of literals of the model in the core of each UNSAT check, including speculative
+    /// ones
+    pub core_sizes: Histogram,
+    /// Conflicts of each SAT check, only for checks on the calling thread and only if the backend
+    /// reports conflicts
+    pub sat_conflicts: Histogram,
+    /// Like `sat_conflicts` for UNSAT checks
+    pub unsat_conflicts: Histogram,
+}
+
+impl std::ops::AddAssign<&CheckHistograms> for CheckHistograms {
+    fn add_assign(&mut self, other: &CheckHistograms) {
+        self.core_sizes += &other.core_sizes;
+        self.sat_conflicts += &other.sat_conflicts;
+        self.unsat_conflicts += &other.unsat_conflicts;
+    }
+}
+
+/// State of a running reduction, see [`Builder::on_progress`](crate::Builder::on_progress)
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Progress {
+    /// Literals of the model known to be essential so far
+    pub essential: Vec<isize>,
+    /// Literals of the model that remain to be checked, the next one first
+    pub candidates: Vec<isize>,
+    /// Checks of this reduction so far
+    pub checks: Checks,
+}
+
+/// Callback receiving the [`Progress`] of reductions
+#[derive(Clone)]
+pub struct OnProgress(pub Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl fmt::Debug for OnProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OnProgress")
+    }
+}
+
+/// A candidate check of a reduction, see [`Builder::on_check`](crate::Builder::on_check)
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CheckTrace {
+    /// The literal of the model that the check tries to remove
+    pub candidate: isize,
+    /// The literals of the model kept during the check, the known essential ones and the
+    /// remaining candidates
+    ///
+    /// The check is [`Check::Unsat`] iff these still entail the formula.
+    pub assumptions: Vec<isize>,
+    pub result: Check,
+    /// For [`Check::Unsat`], the subset of `assumptions` that suffices for the entailment, to
+    /// which the remaining candidates are restricted
+    pub core: Vec<isize>,
+}
+
+/// Callback receiving a [`CheckTrace`] for each candidate check
+#[derive(Clone)]
+pub struct OnCheck(pub Arc<dyn Fn(&CheckTrace) + Send + Sync>);
+
+impl fmt::Debug for OnCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OnCheck")
+    }
+}
+
+/// Result of a reduction, neither set contains the `chain` literal
+pub struct Reduction {
+    /// Literals kept in the reduced model
+    pub essential: BTreeSet<Lit>,
+    /// Literals of `essential` that were kept because their check was inconclusive or because the
+    /// reduction was interrupted
+    pub unverified: BTreeSet<Lit>,
+    pub interrupted: bool,
+    pub checks: Checks,
+    pub check_times: CheckTimes,
+    pub histograms: CheckHistograms,
+}
+
+/// Maps assumed literals to the literals of the model, leaving out auxiliary variables
+fn model_lits<'a>(user_vars: &[usize], lits: impl IntoIterator<Item = &'a Lit>) -> Vec<isize> {
+    lits.into_iter()
+        .filter_map(|lit| match user_vars.get(lit.var() as usize) {
+            Some(&var) if var != 0 => Some(if lit.isneg() {
+                var as isize
+            } else {
+                -(var as isize)
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Buffers reused across candidate checks and reductions, so that the reduction loop doesn't
+/// allocate once they have grown to size
+#[derive(Clone, Default)]
+struct Buffers {
+    /// Literals known to be essential in the current reduction, kept sorted so that the
+    /// assumptions are passed to the solver in a deterministic order
+    essential: Vec<Lit>,
+    /// Candidates of the current batch
+    candidates: Vec<Lit>,
+    /// Assumptions of the check currently being prepared
+    check: Vec<Lit>,
+    /// Conflict of the check performed on this thread
+    conflict: Vec<Lit>,
+    /// Results of the speculative checks, with the conflict in case of UNSAT
+    speculated: Vec<(Check, CheckBuffers)>,
+    /// Buffers of finished speculative checks, reused for the next ones
+    spare: Vec<CheckBuffers>,
+    /// Assumptions of each check of the current batch, only filled with a check callback
+    traced: Vec<Vec<isize>>,
+    /// Used for sorting candidates
+    keyed: Vec<(Reverse<usize>, usize, Lit)>,
+}
+
+impl Buffers {
+    fn is_essential(&self, lit: Lit) -> bool {
+        self.essential.binary_search(&lit).is_ok()
+    }
+
+    fn insert_essential(&mut self, lit: Lit) {
+        if let Err(index) = self.essential.binary_search(&lit) {
+            self.essential.insert(index, lit);
+        }
+    }
+
+    /// Fills `check` with all current literals but the candidate at position `skip`, counting from
+    /// the end of `candidates`
+    fn prepare_check(&mut self, assumptions: &[Lit], skip: usize) {
+        self.check.clear();
+        self.check.extend_from_slice(assumptions);
+        for (index, &lit) in self.candidates.iter().rev().enumerate() {
+            if index != skip {
+                self.check.push(lit);
+            }
+        }
+        self.check.extend_from_slice(&self.essential);
+    }
+}
+
+/// State of the candidate selection that persists across reductions
+#[derive(Clone)]
+pub struct Reducer {
+    pub order: CandidateOrder,
+    /// Shuffling happens before ordering, so it also breaks ties of the selected order
+    pub rng: Option<Rng>,
+    /// Number of clauses each literal occurs in
+    pub occurrences: Vec<usize>,
+    /// Number of conflicts of the negative solver each assumed literal was part of
+    pub core_counts: Vec<usize>,
+    /// [`Preference::key`](crate::Preference::key) of each assumed literal, applied after the
+    /// selected order
+    pub preferences: Vec<u8>,
+    /// Ranks the candidates after the selected order, before the preferences
+    pub script: Option<Arc<Script>>,
+    /// User variable of each solver variable, or zero for auxiliary variables, only maintained
+    /// with a script or a progress or check callback
+    pub user_vars: Vec<usize>,
+    /// Number of checks of each assumed literal by their outcome, only maintained with a script
+    pub sat_counts: Vec<usize>,
+    pub unsat_counts: Vec<usize>,
+    pub unknown_counts: Vec<usize>,
+    /// Whether to print progress messages
+    pub progress: bool,
+    /// Called before each candidate check
+    pub on_progress: Option<OnProgress>,
+    /// Called after each candidate check
+    pub on_check: Option<OnCheck>,
+    /// When set, the reduction stops before the next candidate check
+    pub interrupt: Option<Arc<AtomicBool>>,
+    buffers: Buffers,
+}
+
+impl Reducer {
+    pub fn new(
+        order: CandidateOrder,
+        rng: Option<Rng>,
+        script: Option<Arc<Script>>,
+        progress: bool,
+        on_progress: Option<OnProgress>,
+        on_check: Option<OnCheck>,
+        interrupt: Option<Arc<AtomicBool>>,
+    ) -> Self {
+        Reducer {
+            order,
+            rng,
+            occurrences: vec![],
+            core_counts: vec![],
+            preferences: vec![],
+            script,
+            user_vars: vec![],
+            sat_counts: vec![],
+            unsat_counts: vec![],
+            unknown_counts: vec![],
+            progress,
+            on_progress,
+            on_check,
+            interrupt,
+            buffers: Buffers::default(),
+        }
+    }
+
+    fn order(&mut self, assumptions: &mut [Lit]) {
+        if let Some(rng) = &mut self.rng {
+            rng.shuffle(assumptions);
+        }
+        order_candidates(
+            self.order,
+            &self.occurrences,
+            &self.core_counts,
+            assumptions,
+            &mut self.buffers.keyed,
+        );
+        if let Some(script) = &self.script {
+            let counter =
+                |counter: &[usize], lit| counter.get(lit_index(lit)).cloned().unwrap_or(0);
+            let (user_vars, occurrences, core_counts) =
+                (&self.user_vars, &self.occurrences, &self.core_counts);
+            let (sat_counts, unsat_counts, unknown_counts) =
+                (&self.sat_counts, &self.unsat_counts, &self.unknown_counts);
+            let candidate = |lit: Lit| Candidate {
+                var: user_vars.get(lit.var() as usize).cloned().unwrap_or(0),
+                // The assumptions are the negated literals of the model
+                positive: lit.isneg(),
+                occurrences: counter(occurrences, lit),
+                cores: counter(core_counts, lit),
+                sat: counter(sat_counts, lit),
+                unsat: counter(unsat_counts, lit),
+                unknown: counter(unknown_counts, lit),
+            };
+            apply_script(script, candidate, assumptions, &mut self.buffers.keyed);
+        }
+        apply_preferences(&self.preferences, assumptions, &mut self.buffers.keyed);
+    }
+
+    /// Reduces the model given by `assumptions` and `known_essential` to a minimal set of
+    /// essential literals
+    ///
+    /// The literals of `known_essential` are never tested. With `speculation`, the candidates
+    /// following the next one are checked concurrently on the worker threads.
+    pub fn reduce(
+        &mut self,
+        neg_solver: &mut dyn SatBackend,
+        speculation: Option<&mut Speculation>,
+        chain: Lit,
+        mut assumptions: Vec<Lit>,
+        known_essential: &[Lit],
+    ) -> Reduction {
+        let workers = speculation
+            .as_ref()
+            .map_or(0, |speculation| speculation.workers());
+
+        self.order(&mut assumptions);
+
+        // We force at least one of the negated clauses in `neg_solver` to be true
+        // by assuming `chain`
+        self.buffers.essential.clear();
+        self.buffers.insert_essential(chain);
+        for &lit in known_essential {
+            self.buffers.insert_essential(lit);
+        }
+        let mut unverified = BTreeSet::new();
+
+        // We then remove one literal of our current model (essential + assumptions)
+        // and see if it can be extended to falsify a clause
+        let mut interrupted = false;
+        let mut checks = Checks::default();
+        let mut check_times = CheckTimes::default();
+        let mut histograms = CheckHistograms::default();
+
+        while !assumptions.is_empty() {
+            if let Some(interrupt) = &self.interrupt {
+                if interrupt.load(Ordering::SeqCst) {
+                    // Our current model (essential + assumptions) is still a model, we just don't
+                    // know which of the remaining candidates are essential
+                    interrupted = true;
+                    unverified.extend(assumptions.iter().cloned());
+                    for lit in assumptions.drain(..) {
+                        self.buffers.insert_essential(lit);
+                    }
+                    break;
+                }
+            }
+
+            let buffers = &mut self.buffers;
+
+            if self.progress {
+                println!(
+                    "solving... {}/{}",
+                    buffers.essential.len() - 1,
+                    buffers.essential.len() - 1 + assumptions.len()
+                );
+            }
+            if let Some(on_progress) = &self.on_progress {
+                on_progress.0(&Progress {
+                    essential: model_lits(&self.user_vars, &buffers.essential),
+                    candidates: model_lits(&self.user_vars, assumptions.iter().rev()),
+                    checks,
+                });
+            }
+
+            // The candidates are taken from the end of `assumptions`, the first one is checked
+            // here, the others speculatively on the workers
+            let batch = assumptions.len().min(workers + 1);
+            buffers.candidates.clear();
+            buffers
+                .candidates
+                .extend(assumptions.drain(assumptions.len() - batch..));
+
+            buffers
+                .spare
+                .extend(buffers.speculated.drain(..).map(|(_, check)| check));
+            if let Some(speculation) = &speculation {
+                for worker in 0..batch - 1 {
+                    buffers.prepare_check(&assumptions, worker + 1);
+                    let mut check = buffers.spare.pop().unwrap_or_default();
+                    check.assumptions.clear();
+                    check.assumptions.extend_from_slice(&buffers.check);
+                    speculation.start(worker, check);
+                }
+            }
+
+            buffers.traced.clear();
+            if self.on_check.is_some() {
+                for position in 0..batch {
+                    buffers.prepare_check(&assumptions, position);
+                    let traced = model_lits(&self.user_vars, &buffers.check);
+                    buffers.traced.push(traced);
+                }
+            }
+
+            buffers.prepare_check(&assumptions, 0);
+            let start = Instant::now();
+            let conflicts = neg_solver.conflicts();
+            let result = Check::run(neg_solver, &buffers.check, &mut buffers.conflict);
+            check_times.record(result, start.elapsed());
+            if let (Some(before), Some(after)) = (conflicts, neg_solver.conflicts()) {
+                match result {
+                    Check::Sat => histograms.sat_conflicts.record(after - before),
+                    Check::Unsat => histograms.unsat_conflicts.record(after - before),
+                    Check::Unknown => (),
+                }
+            }
+
+            if let Some(speculation) = &speculation {
+                buffers
+                    .speculated
+                    .extend((0..batch - 1).map(|worker| speculation.finish(worker)));
+            }
+
+            // Processing the results in order, everything up to the first UNSAT result is exactly
+            // what a sequential reduction would have found. After that the UNSAT results were
+            // invalidated by the removal of literals, but the SAT results stay valid, as removing
+            // more literals can't make a falsifiable assignment unfalsifiable.
+            let mut conflict = None;
+            for position in 0..batch {
+                let candidate = buffers.candidates[batch - 1 - position];
+                let result = if position == 0 {
+                    result
+                } else {
+                    buffers.speculated[position - 1].0
+                };
+                checks.record(result);
+                let check_conflict = if position == 0 {
+                    &buffers.conflict
+                } else {
+                    &buffers.speculated[position - 1].1.conflict
+                };
+                if result == Check::Unsat {
+                    // Every literal of the conflict except `chain` is a literal of the model
+                    let size = check_conflict
+                        .iter()
+                        .filter(|lit| lit.var() != chain.var())
+                        .count();
+                    histograms.core_sizes.record(size as u64);
+                }
+                if let Some(on_check) = &self.on_check {
+                    // The conflict contains the negated assumptions
+                    let core = match result {
+                        Check::Unsat => {
+                            let core: Vec<Lit> = check_conflict.iter().map(|&lit| !lit).collect();
+                            model_lits(&self.user_vars, &core)
+                        }
+                        _ => vec![],
+                    };
+                    on_check.0(&CheckTrace {
+                        candidate: model_lits(&self.user_vars, [&candidate])[0],
+                        assumptions: std::mem::take(&mut buffers.traced[position]),
+                        result,
+                        core,
+                    });
+                }
+                if self.script.is_some() {
+                    let counter = match result {
+                        Check::Sat => &mut self.sat_counts,
+                        Check::Unsat => &mut self.unsat_counts,
+                        Check::Unknown => &mut self.unknown_counts,
+                    };
+                    count(counter, candidate);
+                }
+                match result {
+                    // If it can be falsified our candidate is essential
+                    Check::Sat => buffers.insert_essential(candidate),
+                    Check::Unsat => {
+                        if conflict.is_none() {
+                            conflict = Some(position);
+                        }
+                    }
+                    // Keeping a candidate is always sound, but the result may not be minimal
+                    Check::Unknown => {
+                        buffers.insert_essential(candidate);
+                        unverified.insert(candidate);
+                    }
+                }
+            }
+
+            if let Some(position) = conflict {
+                let conflict = if position == 0 {
+                    &buffers.conflict
+                } else {
+                    &buffers.speculated[position - 1].1.conflict
+                };
+                // Otherwise the candidate isn't needed and the solver produces a
+                // subset of failed literals which we use to update `assumptions`
+                // (removing literals we already know to be `essential`)
+                assumptions.clear();
+                for &lit in conflict {
+                    if !buffers.is_essential(!lit) {
+                        assumptions.push(!lit);
+                    }
+                }
+                for &lit in &assumptions {
+                    count(&mut self.core_counts, lit);
+                }
+                self.order(&mut assumptions);
+            }
+        }
+
+        let mut essential: BTreeSet<Lit> = self.buffers.essential.iter().cloned().collect();
+
+        // The user isn't interested in our auxiliary variable
+        essential.remove(&chain);
+
+        Reduction {
+            essential,
+            unverified,
+            interrupted,
+            checks,
+            check_times,
+            histograms,
+        }
+    }
+}
+
+enum Request {
+    AddClause(Vec<Lit>),
+    Reduce {
+        reducer: Box<Reducer>,
+        nvars: usize,
+        chain: Lit,
+        assumptions: Vec<Lit>,
+        known_essential: Vec<Lit>,
+    },
+}
+
+/// Result of an additional attempt, with its reducer to continue with its statistics
+type Response = (Reduction, Reducer);
+
+struct Worker {
+    requests: Sender<Request>,
+    responses: Receiver<Response>,
+}
+
+/// Runs several reductions with different candidate orders in parallel
+///
+/// As solvers can't be moved between threads, each additional attempt runs on a worker thread
+/// with its own copy of the negative solver, which receives every clause added to the negative
+/// solver.
+pub struct Portfolio {
+    /// Source of seeds for the shuffled orders of the additional attempts
+    rng: Rng,
+    workers: Vec<Worker>,
+}
+
+impl Portfolio {
+    pub fn new(attempts: usize, rng: Rng, neg_config: &SolverConfig) -> Self {
+        let workers = (1..attempts)
+            .map(|_| {
+                let config = neg_config.clone();
+                let (requests, request_receiver) = channel();
+                let (response_sender, responses) = channel();
+                thread::spawn(move || {
+                    let mut neg_solver = config.new_solver();
+                    // The loop ends when the corresponding `Worker` is dropped
+                    for request in request_receiver {
+                        match request {
+                            Request::AddClause(clause) => {
+                                ensure_vars(&mut *neg_solver, &clause);
+                                neg_solver.add_clause(&clause);
+                            }
+                            Request::Reduce {
+                                mut reducer,
+                                nvars,
+                                chain,
+                                assumptions,
+                                known_essential,
+                            } => {
+                                // Assumptions can contain variables that don't appear in any
+                                // clause of the negative solver
+                                let missing = nvars.saturating_sub(neg_solver.nvars() as usize);
+                                neg_solver.new_vars(missing);
+                                let reduction = reducer.reduce(
+                                    &mut *neg_solver,
+                                    None,
+                                    chain,
+                                    assumptions,
+                                    &known_essential,
+                                );
+                                if response_sender.send((reduction, *reducer)).is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                });
+                Worker {
+                    requests,
+                    responses,
+                }
+            })
+            .collect();
+        Portfolio { rng, workers }
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.workers.len() + 1
+    }
+
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        for worker in &self.workers {
+            worker
+                .requests
+                .send(Request::AddClause(clause.to_vec()))
+                .expect("portfolio worker died");
+        }
+    }
+
+    /// Returns the smallest result among all attempts
+    ///
+    /// The first attempt uses `reducer`, `neg_solver` and `speculation` as they are, the others
+    /// use a copy of the first two with a freshly seeded shuffle.
+    pub fn reduce(
+        &mut self,
+        reducer: &mut Reducer,
+        neg_solver: &mut dyn SatBackend,
+        speculation: Option<&mut Speculation>,
+        chain: Lit,
+        assumptions: Vec<Lit>,
+        known_essential: &[Lit],
+    ) -> Reduction {
+        let nvars = neg_solver.nvars() as usize;
+        for worker in &self.workers {
+            let other = Box::new(Reducer {
+                rng: Some(Rng(self.rng.next_u64())),
+                progress: false,
+                on_progress: None,
+                on_check: None,
+                ..reducer.clone()
+            });
+            worker
+                .requests
+                .send(Request::Reduce {
+                    reducer: other,
+                    nvars,
+                    chain,
+                    assumptions: assumptions.clone(),
+                    known_essential: known_essential.to_vec(),
+                })
+                .expect("portfolio worker died");
+        }
+
+        let reduction =
+            reducer.reduce(neg_solver, speculation, chain, assumptions, known_essential);
+        let others: Vec<Response> = self
+            .workers
+            .iter()
+            .map(|worker| worker.responses.recv().expect("portfolio worker died"))
+            .collect();
+
+        let mut best = reduction;
+        for (reduction, other) in others {
+            if reduction.essential.len() < best.essential.len() {
+                // The times stay those of the calling thread
+                best = Reduction {
+                    check_times: best.check_times,
+                    ..reduction
+                };
+                // Continue with the statistics of the winning attempt
+                reducer.core_counts = other.core_counts;
+            }
+        }
+        best
+    }
+}
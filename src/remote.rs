@@ -0,0 +1,218 @@
+//! Reading the input from an `http://` URL
+//!
+//! The request is a plain HTTP/1.0 GET, so the response body isn't chunked and can be read
+//! directly from the connection while solving, without downloading it first. Redirects to other
+//! `http://` URLs, which may be relative, are followed. There is no TLS implementation or
+//! decompressor available, so `https://` URLs, including redirects to them, and compressed bodies
+//! are reported as errors instead of being misread as clauses.
+use std::{
+    io::{self, BufRead, Read, Write},
+    net::TcpStream,
+};
+
+use anyhow::Context;
+
+/// Maximal number of redirects followed for a single request
+const MAX_REDIRECTS: usize = 5;
+
+/// Whether `input` names a URL rather than a file
+pub fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Splits an `http://` URL into the address to connect to, the host header and the path
+fn split_url(url: &str) -> anyhow::Result<(String, String, String)> {
+    let rest = match url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None if url.starts_with("https://") => anyhow::bail!(
+            "https URLs aren't supported, as there is no TLS implementation, download {} first",
+            url
+        ),
+        None => anyhow::bail!("unsupported URL {}", url),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        anyhow::bail!("URL {} has no host", url);
+    }
+    // A bracketed IPv6 address contains colons without a port
+    let addr = if host
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| !port.contains(']'))
+    {
+        host.to_owned()
+    } else {
+        format!("{}:80", host)
+    };
+    Ok((addr, host.to_owned(), path.to_owned()))
+}
+
+/// The URL that `location`, the `Location` header of a redirect from `url`, refers to
+///
+/// Relative references are resolved against the `host` and `path` of `url`.
+fn redirect(url: &str, host: &str, path: &str, location: &str) -> anyhow::Result<String> {
+    let scheme = location
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        });
+    match scheme {
+        Some(scheme) if scheme.eq_ignore_ascii_case("http") => return Ok(location.to_owned()),
+        Some(scheme) if scheme.eq_ignore_ascii_case("https") => anyhow::bail!(
+            "{} redirects to {}, but https URLs aren't supported, as there is no TLS \
+             implementation",
+            url,
+            location
+        ),
+        Some(_) => anyhow::bail!("{} redirects to unsupported URL {}", url, location),
+        None => (),
+    }
+    if location.starts_with("//") {
+        return Ok(format!("http:{}", location));
+    }
+    let target = if location.starts_with('/') {
+        location.to_owned()
+    } else {
+        // Relative to the directory of the path, without its query
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let dir = &path[..path.rfind('/').map_or(0, |index| index + 1)];
+        format!("{}{}", dir, location)
+    };
+    Ok(format!("http://{}{}", host, remove_dot_segments(&target)))
+}
+
+/// Resolves the `.` and `..` segments of an absolute path, leaving its query alone
+fn remove_dot_segments(target: &str) -> String {
+    let (path, query) = match target.find(['?', '#']) {
+        Some(index) => target.split_at(index),
+        None => (target, ""),
+    };
+    let mut segments: Vec<&str> = vec![];
+    let mut parts = path[1..].split('/').peekable();
+    while let Some(part) = parts.next() {
+        match part {
+            "." => (),
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(part),
+        }
+        // A trailing dot segment still names a directory
+        if parts.peek().is_none() && (part == "." || part == "..") {
+            segments.push("");
+        }
+    }
+    format!("/{}{}", segments.join("/"), query)
+}
+
+/// Magic numbers of compressed formats, which are rejected
+const COMPRESSED: &[(&str, &[u8])] = &[
+    ("gzip", &[0x1f, 0x8b]),
+    ("xz", &[0xfd, b'7', b'z', b'X', b'Z']),
+    ("bzip2", b"BZh"),
+    ("zstd", &[0x28, 0xb5, 0x2f, 0xfd]),
+];
+
+/// Length of the longest magic number in [`COMPRESSED`]
+const MAGIC_LEN: usize = 5;
+
+/// Body of a response, the bytes read to check for magic numbers followed by the rest
+pub type Body = io::Chain<io::Cursor<Vec<u8>>, io::BufReader<TcpStream>>;
+
+/// Opens the body of the response to a GET request for `url`, streaming it from the connection
+pub fn open_url(url: &str) -> anyhow::Result<Body> {
+    let mut url = url.to_owned();
+    for _ in 0..=MAX_REDIRECTS {
+        let (addr, host, path) = split_url(&url)?;
+        let mut stream =
+            TcpStream::connect(&addr).with_context(|| format!("connecting to {}", addr))?;
+        let request = format!(
+            "GET {} HTTP/1.0\r\n\
+             Host: {}\r\n\
+             Accept-Encoding: identity\r\n\
+             Connection: close\r\n\r\n",
+            path, host
+        );
+        stream
+            .write_all(request.as_bytes())
+            .with_context(|| format!("requesting {}", url))?;
+
+        let mut reader = io::BufReader::new(stream);
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .with_context(|| format!("reading the response to {}", url))?;
+        let status = line
+            .split_ascii_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_owned();
+        let mut location = None;
+        loop {
+            line.clear();
+            reader
+                .read_line(&mut line)
+                .with_context(|| format!("reading the response to {}", url))?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let (name, value) = match header.split_once(':') {
+                Some((name, value)) => (name.to_ascii_lowercase(), value.trim()),
+                None => continue,
+            };
+            match &name[..] {
+                "location" => location = Some(value.to_owned()),
+                "content-encoding" if value != "identity" => {
+                    anyhow::bail!(
+                        "{} is compressed with {}, which isn't supported",
+                        url,
+                        value
+                    )
+                }
+                _ => (),
+            }
+        }
+
+        match (&status[..], location) {
+            ("301" | "302" | "303" | "307" | "308", Some(location)) => {
+                url = redirect(&url, &host, &path, &location)?;
+                continue;
+            }
+            (status, _) if status.starts_with('2') => (),
+            (status, _) => anyhow::bail!("requesting {} failed with status {}", url, status),
+        }
+
+        // A single read may end within a magic number, so read until the longest one is complete
+        let mut start = vec![];
+        while start.len() < MAGIC_LEN {
+            let buf = reader
+                .fill_buf()
+                .with_context(|| format!("reading {}", url))?;
+            if buf.is_empty() {
+                break;
+            }
+            let len = buf.len().min(MAGIC_LEN - start.len());
+            start.extend_from_slice(&buf[..len]);
+            reader.consume(len);
+        }
+        if let Some((format, _)) = COMPRESSED
+            .iter()
+            .find(|(_, magic)| start.starts_with(magic))
+        {
+            anyhow::bail!(
+                "{} is compressed with {}, which isn't supported",
+                url,
+                format
+            );
+        }
+        return Ok(io::Cursor::new(start).chain(reader));
+    }
+    anyhow::bail!("too many redirects requesting {}", url)
+}
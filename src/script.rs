@@ -0,0 +1,355 @@
+//! Candidate ranking scripts, for experimenting with strategies without recompiling
+//!
+//! A script is a single arithmetic expression computing the rank of a candidate literal, where
+//! candidates with a smaller rank are tested first. It may span multiple lines and contain `#`
+//! comments. Numbers are floating point, and the operators are, from lowest to highest precedence:
+//! `c ? a : b`, `||`, `&&`, the comparisons `==`, `!=`, `<`, `<=`, `>`, `>=`, then `+`, `-` and
+//! `*`, `/`, `%` and finally the unary `-` and `!`. Comparisons and logical operators yield `1` or
+//! `0`, and any non-zero value counts as true.
+//!
+//! The variables describe the candidate, including the outcomes of its previous checks, so a
+//! script can react to them:
+//!
+//! * `var`: its variable
+//! * `positive`: `1` if it is a positive literal of the model, `0` otherwise
+//! * `occurrences`: the number of clauses it occurs in
+//! * `cores`: the number of previous conflicts of the negative solver it was part of
+//! * `sat`: how often it was found to be essential
+//! * `unsat`: how often it was removed
+//! * `unknown`: how often the solver gave up on checking it
+//!
+//! As there is no scripting engine among the dependencies, this small language is implemented
+//! here.
+use std::fmt;
+
+/// Values of the variables of a script for one candidate
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Candidate {
+    pub var: usize,
+    pub positive: bool,
+    pub occurrences: usize,
+    pub cores: usize,
+    pub sat: usize,
+    pub unsat: usize,
+    pub unknown: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Variable {
+    Var,
+    Positive,
+    Occurrences,
+    Cores,
+    Sat,
+    Unsat,
+    Unknown,
+}
+
+impl Variable {
+    fn parse(name: &str) -> Option<Variable> {
+        Some(match name {
+            "var" => Variable::Var,
+            "positive" => Variable::Positive,
+            "occurrences" => Variable::Occurrences,
+            "cores" => Variable::Cores,
+            "sat" => Variable::Sat,
+            "unsat" => Variable::Unsat,
+            "unknown" => Variable::Unknown,
+            _ => return None,
+        })
+    }
+
+    fn value(self, candidate: &Candidate) -> f64 {
+        match self {
+            Variable::Var => candidate.var as f64,
+            Variable::Positive => candidate.positive as usize as f64,
+            Variable::Occurrences => candidate.occurrences as f64,
+            Variable::Cores => candidate.cores as f64,
+            Variable::Sat => candidate.sat as f64,
+            Variable::Unsat => candidate.unsat as f64,
+            Variable::Unknown => candidate.unknown as f64,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    /// Binding strength of the binary operator, higher binds tighter
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Or => 1,
+            Op::And => 2,
+            Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge => 3,
+            Op::Add | Op::Sub => 4,
+            Op::Mul | Op::Div | Op::Rem => 5,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Or => "||",
+            Op::And => "&&",
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Rem => "%",
+        }
+    }
+
+    fn apply(self, a: f64, b: f64) -> f64 {
+        let truth = |value: bool| value as usize as f64;
+        match self {
+            Op::Or => truth(a != 0.0 || b != 0.0),
+            Op::And => truth(a != 0.0 && b != 0.0),
+            Op::Eq => truth(a == b),
+            Op::Ne => truth(a != b),
+            Op::Lt => truth(a < b),
+            Op::Le => truth(a <= b),
+            Op::Gt => truth(a > b),
+            Op::Ge => truth(a >= b),
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Op(Op),
+    Minus,
+    Not,
+    Question,
+    Colon,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Op(op) => write!(f, "{}", op.symbol()),
+            Token::Minus => write!(f, "-"),
+            Token::Not => write!(f, "!"),
+            Token::Question => write!(f, "?"),
+            Token::Colon => write!(f, ":"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = vec![];
+    for line in text.lines() {
+        let line = line.split_once('#').map_or(line, |(code, _)| code);
+        let mut rest = line.trim_start();
+        while let Some(c) = rest.chars().next() {
+            let two = rest.get(..2).unwrap_or("");
+            let (token, len) = match (c, two) {
+                (_, "||") => (Token::Op(Op::Or), 2),
+                (_, "&&") => (Token::Op(Op::And), 2),
+                (_, "==") => (Token::Op(Op::Eq), 2),
+                (_, "!=") => (Token::Op(Op::Ne), 2),
+                (_, "<=") => (Token::Op(Op::Le), 2),
+                (_, ">=") => (Token::Op(Op::Ge), 2),
+                ('<', _) => (Token::Op(Op::Lt), 1),
+                ('>', _) => (Token::Op(Op::Gt), 1),
+                ('+', _) => (Token::Op(Op::Add), 1),
+                ('-', _) => (Token::Minus, 1),
+                ('*', _) => (Token::Op(Op::Mul), 1),
+                ('/', _) => (Token::Op(Op::Div), 1),
+                ('%', _) => (Token::Op(Op::Rem), 1),
+                ('!', _) => (Token::Not, 1),
+                ('?', _) => (Token::Question, 1),
+                (':', _) => (Token::Colon, 1),
+                ('(', _) => (Token::Open, 1),
+                (')', _) => (Token::Close, 1),
+                _ if c.is_ascii_digit() || c == '.' => {
+                    let len = rest
+                        .find(|c: char| !c.is_ascii_digit() && c != '.')
+                        .unwrap_or(rest.len());
+                    let number = rest[..len]
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("invalid number {:?}", &rest[..len]))?;
+                    (Token::Number(number), len)
+                }
+                _ if c.is_ascii_alphabetic() || c == '_' => {
+                    let len = rest
+                        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                        .unwrap_or(rest.len());
+                    (Token::Name(rest[..len].to_owned()), len)
+                }
+                _ => anyhow::bail!("unexpected {:?}", c),
+            };
+            tokens.push(token);
+            rest = rest[len..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of a script
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("unexpected end of script"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            anyhow::bail!("expected {} but found {}", expected, token);
+        }
+        Ok(())
+    }
+
+    fn conditional(&mut self) -> anyhow::Result<Expr> {
+        let condition = self.binary(1)?;
+        if self.peek() != Some(&Token::Question) {
+            return Ok(condition);
+        }
+        self.position += 1;
+        let then = self.conditional()?;
+        self.expect(Token::Colon)?;
+        let otherwise = self.conditional()?;
+        Ok(Expr::Conditional(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    /// Parses binary operators of at least the given precedence, which are left associative
+    fn binary(&mut self, precedence: u8) -> anyhow::Result<Expr> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op(op)) => *op,
+                Some(Token::Minus) => Op::Sub,
+                _ => break,
+            };
+            if op.precedence() < precedence {
+                break;
+            }
+            self.position += 1;
+            let right = self.binary(op.precedence() + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        Ok(match self.next()? {
+            Token::Minus => Expr::Negate(Box::new(self.unary()?)),
+            Token::Not => Expr::Not(Box::new(self.unary()?)),
+            Token::Number(number) => Expr::Number(number),
+            Token::Name(name) => Expr::Variable(
+                Variable::parse(&name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown variable {:?}", name))?,
+            ),
+            Token::Open => {
+                let expr = self.conditional()?;
+                self.expect(Token::Close)?;
+                expr
+            }
+            token => anyhow::bail!("unexpected {}", token),
+        })
+    }
+}
+
+impl Expr {
+    fn eval(&self, candidate: &Candidate) -> f64 {
+        match self {
+            Expr::Number(number) => *number,
+            Expr::Variable(variable) => variable.value(candidate),
+            Expr::Negate(expr) => -expr.eval(candidate),
+            Expr::Not(expr) => (expr.eval(candidate) == 0.0) as usize as f64,
+            Expr::Binary(op, a, b) => op.apply(a.eval(candidate), b.eval(candidate)),
+            Expr::Conditional(condition, then, otherwise) => {
+                if condition.eval(candidate) != 0.0 {
+                    then.eval(candidate)
+                } else {
+                    otherwise.eval(candidate)
+                }
+            }
+        }
+    }
+}
+
+/// A parsed candidate ranking script
+#[derive(Clone, Debug)]
+pub struct Script {
+    expr: Expr,
+}
+
+impl Script {
+    pub fn parse(text: &str) -> anyhow::Result<Script> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let expr = parser.conditional()?;
+        if let Some(token) = parser.peek() {
+            anyhow::bail!("unexpected {} after the end of the expression", token);
+        }
+        Ok(Script { expr })
+    }
+
+    /// Rank of the candidate, candidates with a smaller rank are tested first
+    pub fn rank(&self, candidate: &Candidate) -> f64 {
+        self.expr.eval(candidate)
+    }
+}
@@ -0,0 +1,558 @@
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::Arc;
+
+#[cfg(feature = "cadical")]
+use crate::backend::CaDiCaL;
+#[cfg(feature = "cryptominisat")]
+use crate::backend::CryptoMiniSat;
+#[cfg(feature = "kissat")]
+use crate::backend::Kissat;
+#[cfg(feature = "minisat")]
+use crate::backend::MiniSat;
+use crate::backend::{Builtin, InitialSolve, Proof, SatBackend};
+#[cfg(unix)]
+use crate::backend::{Ipasir, IpasirLibrary};
+
+/// The SAT solver used for a solver
+///
+/// The default is cryptominisat, or the builtin solver when built without it.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum Backend {
+    /// Only available when built with the default `cryptominisat` feature
+    #[cfg_attr(feature = "cryptominisat", default)]
+    CryptoMiniSat,
+    /// A small solver written in Rust, which is always available
+    #[cfg_attr(not(feature = "cryptominisat"), default)]
+    Builtin,
+    /// Only available when built with the `cadical` feature
+    CaDiCaL,
+    /// Only available when built with the `kissat` feature, and only as
+    /// [`SolverOptions::initial_backend`], as it isn't incremental
+    Kissat,
+    /// Only available when built with the `minisat` feature
+    MiniSat,
+    /// The IPASIR solver loaded from [`SolverOptions::ipasir`], only available on unix
+    Ipasir,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s {
+            "cryptominisat" => Backend::CryptoMiniSat,
+            "builtin" => Backend::Builtin,
+            "cadical" => Backend::CaDiCaL,
+            "kissat" => Backend::Kissat,
+            "minisat" => Backend::MiniSat,
+            "ipasir" => Backend::Ipasir,
+            _ => anyhow::bail!("unknown solver backend {:?}", s),
+        })
+    }
+}
+
+/// Format of the proofs written by [`Builder::pos_proof`](crate::Builder::pos_proof) and
+/// [`Builder::neg_proof`](crate::Builder::neg_proof)
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum ProofFormat {
+    /// Checked against a formula that is written next to the proof, e.g. by cake_lpr
+    #[default]
+    Lrat,
+    /// Lists the original clauses itself, checked e.g. by frat-rs
+    Frat,
+    /// Like LRAT, but without hints, checked e.g. by drat-trim
+    Drat,
+}
+
+impl std::str::FromStr for ProofFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s {
+            "lrat" => ProofFormat::Lrat,
+            "frat" => ProofFormat::Frat,
+            "drat" => ProofFormat::Drat,
+            _ => anyhow::bail!("unknown proof format {:?}", s),
+        })
+    }
+}
+
+/// Which value the solver tries first when deciding on a variable
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Polarity {
+    True,
+    False,
+    /// Let the solver choose, e.g. based on previous assignments
+    Auto,
+}
+
+/// Backend independent solver settings
+///
+/// Settings that are left as `None` keep the backend's default. Backend specific options can be
+/// passed through `native` as key value pairs. For cryptominisat these are `verbosity`,
+/// `max-time`, `polarity` (`true`, `false` or `auto`), `simplify`, `simplify-at-startup`,
+/// `equivalent-lit-replacement`, `bva` and `bve` (all can only be `false`) and `comphandler` (can
+/// only be `true`). The cryptominisat bindings don't expose any restart settings. For CaDiCaL
+/// any of its options can be given, with an integer or boolean value. The builtin solver and
+/// MiniSat only accept `verbosity`, which they ignore, as does a solver loaded through IPASIR.
+#[derive(Clone, Default, Debug)]
+pub struct SolverOptions {
+    pub backend: Backend,
+    /// Backend answering only the first call, as long as it finds a model
+    ///
+    /// This allows using a faster non-incremental solver for the first full model. It only uses
+    /// the backend independent settings, as `native` is meant for `backend`.
+    pub initial_backend: Option<Backend>,
+    /// Shared library implementing IPASIR, required by [`Backend::Ipasir`]
+    pub ipasir: Option<PathBuf>,
+    pub threads: Option<u32>,
+    pub polarity: Option<Polarity>,
+    /// Whether to simplify the formula before and during the search
+    pub preprocessing: Option<bool>,
+    /// Limit for each individual solver call in seconds, after which the call gives up
+    pub time_limit: Option<f64>,
+    /// Limit for each individual solver call in conflicts, after which the call gives up
+    ///
+    /// Unlike the time limit, this is deterministic. Only the builtin solver and CaDiCaL support
+    /// it, the cryptominisat bindings don't expose conflict or propagation limits.
+    pub conflict_limit: Option<u64>,
+    pub native: Vec<(String, String)>,
+    /// Use a single thread and reject time limits, so results don't depend on timing
+    ///
+    /// cryptominisat always uses the same fixed random seed, as the bindings can't change it.
+    /// CaDiCaL is deterministic unless its `seed` option is changed between runs.
+    pub deterministic: bool,
+}
+
+/// A native cryptominisat option as given by `--solver-opt key=val`
+///
+/// The bindings only allow disabling most of the inprocessing techniques, so those only accept
+/// `false`.
+#[cfg(feature = "cryptominisat")]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SolverOption {
+    Verbosity(u32),
+    MaxTime(f64),
+    /// `None` selects automatic polarity
+    Polarity(Option<bool>),
+    NoSimplify,
+    NoSimplifyAtStartup,
+    NoEquivalentLitReplacement,
+    NoBva,
+    NoBve,
+    ComponentHandler,
+}
+
+#[cfg(feature = "cryptominisat")]
+impl SolverOption {
+    fn parse(key: &str, value: &str) -> anyhow::Result<Self> {
+        let disable = |option| {
+            if value == "false" {
+                Ok(option)
+            } else {
+                anyhow::bail!("solver option {:?} can only be set to false", key)
+            }
+        };
+
+        Ok(match key {
+            "verbosity" => SolverOption::Verbosity(value.parse()?),
+            "max-time" => SolverOption::MaxTime(value.parse()?),
+            "polarity" => SolverOption::Polarity(match value {
+                "auto" => None,
+                _ => Some(value.parse()?),
+            }),
+            "simplify" => disable(SolverOption::NoSimplify)?,
+            "simplify-at-startup" => disable(SolverOption::NoSimplifyAtStartup)?,
+            "equivalent-lit-replacement" => disable(SolverOption::NoEquivalentLitReplacement)?,
+            "bva" => disable(SolverOption::NoBva)?,
+            "bve" => disable(SolverOption::NoBve)?,
+            "comphandler" => {
+                if value == "true" {
+                    SolverOption::ComponentHandler
+                } else {
+                    anyhow::bail!("solver option {:?} can only be set to true", key)
+                }
+            }
+            _ => anyhow::bail!("unknown solver option {:?}", key),
+        })
+    }
+}
+
+/// Validated [`SolverOptions`], applied to every newly created solver
+#[derive(Clone, Default, Debug)]
+pub struct SolverConfig {
+    backend: BackendConfig,
+    initial: Option<BackendConfig>,
+}
+
+impl SolverConfig {
+    pub fn new(options: &SolverOptions) -> anyhow::Result<Self> {
+        if options.backend == Backend::Kissat {
+            anyhow::bail!("kissat isn't incremental, it can only be used for the first model");
+        }
+        let initial = match options.initial_backend {
+            Some(backend) => {
+                let options = SolverOptions {
+                    native: vec![],
+                    ..options.clone()
+                };
+                Some(BackendConfig::new(backend, &options)?)
+            }
+            None => None,
+        };
+        Ok(SolverConfig {
+            backend: BackendConfig::new(options.backend, options)?,
+            initial,
+        })
+    }
+
+    pub fn new_solver(&self) -> Box<dyn SatBackend> {
+        self.wrap_initial(self.backend.new_solver())
+    }
+
+    /// Like [`SolverConfig::new_solver`], writing a proof of every unsatisfiable call to `path`
+    ///
+    /// Only the builtin solver can write proofs. An initial backend only answers calls with a
+    /// model, so it needs none.
+    pub fn new_proving_solver(
+        &self,
+        path: &Path,
+        format: ProofFormat,
+    ) -> anyhow::Result<Box<dyn SatBackend>> {
+        let solver = match self.backend {
+            BackendConfig::Builtin {
+                polarity,
+                time_limit,
+                conflict_limit,
+            } => {
+                let proof = Proof::create(path, format)?;
+                Box::new(Builtin::new(polarity, time_limit, conflict_limit).with_proof(proof))
+            }
+            _ => anyhow::bail!("only the builtin solver can write proofs"),
+        };
+        Ok(self.wrap_initial(solver))
+    }
+
+    fn wrap_initial(&self, solver: Box<dyn SatBackend>) -> Box<dyn SatBackend> {
+        match &self.initial {
+            Some(initial) => Box::new(InitialSolve::new(initial.new_solver(), solver)),
+            None => solver,
+        }
+    }
+}
+
+/// Validated settings of a single backend
+#[derive(Clone, Debug)]
+enum BackendConfig {
+    #[cfg(feature = "cryptominisat")]
+    CryptoMiniSat {
+        threads: Option<u32>,
+        options: Vec<SolverOption>,
+    },
+    Builtin {
+        polarity: Option<bool>,
+        time_limit: Option<f64>,
+        conflict_limit: Option<u64>,
+    },
+    #[cfg(feature = "cadical")]
+    CaDiCaL {
+        /// Passed to CaDiCaL's `set_option`
+        options: Vec<(String, i32)>,
+        /// CaDiCaL has no time limit of its own, so this is enforced by the backend
+        time_limit: Option<f64>,
+        /// Passed to CaDiCaL's `limit` before each call
+        conflict_limit: Option<u64>,
+    },
+    #[cfg(feature = "kissat")]
+    Kissat {
+        /// Passed to kissat's `set_option`
+        options: Vec<(String, i32)>,
+    },
+    #[cfg(feature = "minisat")]
+    MiniSat {
+        /// IPASIR has no time limit, so this is enforced by the backend
+        time_limit: Option<f64>,
+    },
+    #[cfg(unix)]
+    Ipasir {
+        library: Arc<IpasirLibrary>,
+        time_limit: Option<f64>,
+    },
+}
+
+impl Default for BackendConfig {
+    #[cfg(feature = "cryptominisat")]
+    fn default() -> Self {
+        BackendConfig::CryptoMiniSat {
+            threads: None,
+            options: vec![],
+        }
+    }
+
+    #[cfg(not(feature = "cryptominisat"))]
+    fn default() -> Self {
+        BackendConfig::Builtin {
+            polarity: None,
+            time_limit: None,
+            conflict_limit: None,
+        }
+    }
+}
+
+impl BackendConfig {
+    fn new(backend: Backend, options: &SolverOptions) -> anyhow::Result<Self> {
+        match backend {
+            #[cfg(feature = "cryptominisat")]
+            Backend::CryptoMiniSat => Self::cryptominisat(options),
+            #[cfg(not(feature = "cryptominisat"))]
+            Backend::CryptoMiniSat => anyhow::bail!(
+                "the cryptominisat backend requires building with the cryptominisat feature"
+            ),
+            Backend::Builtin => Self::builtin(options),
+            #[cfg(feature = "cadical")]
+            Backend::CaDiCaL => Self::cadical(options),
+            #[cfg(not(feature = "cadical"))]
+            Backend::CaDiCaL => {
+                anyhow::bail!("the CaDiCaL backend requires building with the cadical feature")
+            }
+            #[cfg(feature = "kissat")]
+            Backend::Kissat => Self::kissat(options),
+            #[cfg(not(feature = "kissat"))]
+            Backend::Kissat => {
+                anyhow::bail!("the kissat backend requires building with the kissat feature")
+            }
+            #[cfg(feature = "minisat")]
+            Backend::MiniSat => Self::minisat(options),
+            #[cfg(not(feature = "minisat"))]
+            Backend::MiniSat => {
+                anyhow::bail!("the MiniSat backend requires building with the minisat feature")
+            }
+            #[cfg(unix)]
+            Backend::Ipasir => Self::ipasir(options),
+            #[cfg(not(unix))]
+            Backend::Ipasir => anyhow::bail!("loading IPASIR solvers is only supported on unix"),
+        }
+    }
+
+    #[cfg(feature = "cryptominisat")]
+    fn cryptominisat(options: &SolverOptions) -> anyhow::Result<Self> {
+        let mut threads = options.threads;
+        let mut solver_options = vec![];
+
+        solver_options.extend(options.polarity.map(|polarity| {
+            SolverOption::Polarity(match polarity {
+                Polarity::True => Some(true),
+                Polarity::False => Some(false),
+                Polarity::Auto => None,
+            })
+        }));
+
+        solver_options.extend(options.time_limit.map(SolverOption::MaxTime));
+
+        if options.preprocessing == Some(false) {
+            solver_options.push(SolverOption::NoSimplify);
+            solver_options.push(SolverOption::NoSimplifyAtStartup);
+        }
+
+        for (key, value) in &options.native {
+            solver_options.push(SolverOption::parse(key, value)?);
+        }
+
+        if options.conflict_limit.is_some() {
+            anyhow::bail!("the cryptominisat bindings don't support conflict limits");
+        }
+        if options.deterministic {
+            if threads.is_some_and(|threads| threads > 1) {
+                anyhow::bail!("multiple solver threads aren't deterministic");
+            }
+            threads = Some(1);
+            if solver_options
+                .iter()
+                .any(|option| matches!(option, SolverOption::MaxTime(_)))
+            {
+                anyhow::bail!("solver time limits aren't deterministic");
+            }
+        }
+
+        Ok(BackendConfig::CryptoMiniSat {
+            threads,
+            options: solver_options,
+        })
+    }
+
+    fn builtin(options: &SolverOptions) -> anyhow::Result<Self> {
+        if options.threads.is_some_and(|threads| threads > 1) {
+            anyhow::bail!("the builtin solver doesn't support multiple threads");
+        }
+        if options.deterministic && options.time_limit.is_some() {
+            anyhow::bail!("solver time limits aren't deterministic");
+        }
+        // The builtin solver never prints anything, so `--verbosity` is accepted but has no effect
+        if let Some((key, _)) = options.native.iter().find(|(key, _)| key != "verbosity") {
+            anyhow::bail!("the builtin solver has no option {:?}", key);
+        }
+        let polarity = match options.polarity {
+            Some(Polarity::True) => Some(true),
+            Some(Polarity::False) => Some(false),
+            Some(Polarity::Auto) | None => None,
+        };
+        Ok(BackendConfig::Builtin {
+            polarity,
+            time_limit: options.time_limit,
+            conflict_limit: options.conflict_limit,
+        })
+    }
+
+    #[cfg(feature = "cadical")]
+    fn cadical(options: &SolverOptions) -> anyhow::Result<Self> {
+        if options.threads.is_some_and(|threads| threads > 1) {
+            anyhow::bail!("CaDiCaL doesn't support multiple threads");
+        }
+        if options.deterministic && options.time_limit.is_some() {
+            anyhow::bail!("solver time limits aren't deterministic");
+        }
+
+        let mut solver_options = vec![];
+        match options.polarity {
+            Some(Polarity::True) => solver_options.push(("phase".to_owned(), 1)),
+            Some(Polarity::False) => solver_options.push(("phase".to_owned(), 0)),
+            Some(Polarity::Auto) | None => (),
+        }
+        if options.preprocessing == Some(false) {
+            for name in ["elim", "subsume", "probe"] {
+                solver_options.push((name.to_owned(), 0));
+            }
+        }
+        for (key, value) in &options.native {
+            // Accept the same key as cryptominisat for `--verbosity`
+            let key = if key == "verbosity" { "verbose" } else { key };
+            let value = match &value[..] {
+                "true" => 1,
+                "false" => 0,
+                _ => value.parse().map_err(|_| {
+                    anyhow::anyhow!(
+                        "CaDiCaL option {:?} requires an integer or boolean value",
+                        key
+                    )
+                })?,
+            };
+            solver_options.push((key.to_owned(), value));
+        }
+
+        Ok(BackendConfig::CaDiCaL {
+            options: solver_options,
+            time_limit: options.time_limit,
+            conflict_limit: options.conflict_limit,
+        })
+    }
+
+    #[cfg(feature = "kissat")]
+    fn kissat(options: &SolverOptions) -> anyhow::Result<Self> {
+        if options.time_limit.is_some() {
+            anyhow::bail!("kissat doesn't support time limits");
+        }
+        if options.conflict_limit.is_some() {
+            anyhow::bail!("kissat doesn't support conflict limits");
+        }
+        let mut solver_options = vec![];
+        match options.polarity {
+            Some(Polarity::True) => solver_options.push(("phase".to_owned(), 1)),
+            Some(Polarity::False) => solver_options.push(("phase".to_owned(), 0)),
+            Some(Polarity::Auto) | None => (),
+        }
+        if options.preprocessing == Some(false) {
+            for name in ["eliminate", "probe"] {
+                solver_options.push((name.to_owned(), 0));
+            }
+        }
+        Ok(BackendConfig::Kissat {
+            options: solver_options,
+        })
+    }
+
+    #[cfg(feature = "minisat")]
+    fn minisat(options: &SolverOptions) -> anyhow::Result<Self> {
+        if options.threads.is_some_and(|threads| threads > 1) {
+            anyhow::bail!("MiniSat doesn't support multiple threads");
+        }
+        if options.deterministic && options.time_limit.is_some() {
+            anyhow::bail!("solver time limits aren't deterministic");
+        }
+        // IPASIR has no way to configure the solver
+        if options.conflict_limit.is_some() {
+            anyhow::bail!("MiniSat doesn't support conflict limits through IPASIR");
+        }
+        if matches!(options.polarity, Some(Polarity::True | Polarity::False)) {
+            anyhow::bail!("MiniSat's polarity can't be set through IPASIR");
+        }
+        if options.preprocessing.is_some() {
+            anyhow::bail!("MiniSat's preprocessing can't be configured through IPASIR");
+        }
+        if let Some((key, _)) = options.native.iter().find(|(key, _)| key != "verbosity") {
+            anyhow::bail!("MiniSat has no option {:?} through IPASIR", key);
+        }
+        Ok(BackendConfig::MiniSat {
+            time_limit: options.time_limit,
+        })
+    }
+
+    #[cfg(unix)]
+    fn ipasir(options: &SolverOptions) -> anyhow::Result<Self> {
+        let path = match &options.ipasir {
+            Some(path) => path,
+            None => anyhow::bail!("the IPASIR backend requires the path of a solver library"),
+        };
+        if options.threads.is_some_and(|threads| threads > 1) {
+            anyhow::bail!("IPASIR doesn't support multiple threads");
+        }
+        if options.deterministic && options.time_limit.is_some() {
+            anyhow::bail!("solver time limits aren't deterministic");
+        }
+        if options.conflict_limit.is_some() {
+            anyhow::bail!("IPASIR doesn't support conflict limits");
+        }
+        if matches!(options.polarity, Some(Polarity::True | Polarity::False)) {
+            anyhow::bail!("the polarity can't be set through IPASIR");
+        }
+        if options.preprocessing.is_some() {
+            anyhow::bail!("preprocessing can't be configured through IPASIR");
+        }
+        if let Some((key, _)) = options.native.iter().find(|(key, _)| key != "verbosity") {
+            anyhow::bail!("IPASIR has no option {:?}", key);
+        }
+        Ok(BackendConfig::Ipasir {
+            library: Arc::new(IpasirLibrary::load(path)?),
+            time_limit: options.time_limit,
+        })
+    }
+
+    fn new_solver(&self) -> Box<dyn SatBackend> {
+        match self {
+            #[cfg(feature = "cryptominisat")]
+            BackendConfig::CryptoMiniSat { threads, options } => {
+                Box::new(CryptoMiniSat::new(*threads, options))
+            }
+            BackendConfig::Builtin {
+                polarity,
+                time_limit,
+                conflict_limit,
+            } => Box::new(Builtin::new(*polarity, *time_limit, *conflict_limit)),
+            #[cfg(feature = "cadical")]
+            BackendConfig::CaDiCaL {
+                options,
+                time_limit,
+                conflict_limit,
+            } => Box::new(CaDiCaL::new(options, *time_limit, *conflict_limit)),
+            #[cfg(feature = "kissat")]
+            BackendConfig::Kissat { options } => Box::new(Kissat::new(options)),
+            #[cfg(feature = "minisat")]
+            BackendConfig::MiniSat { time_limit } => Box::new(MiniSat::new(*time_limit)),
+            #[cfg(unix)]
+            BackendConfig::Ipasir {
+                library,
+                time_limit,
+            } => Box::new(Ipasir::new(library.clone(), *time_limit)),
+        }
+    }
+}
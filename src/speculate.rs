@@ -0,0 +1,120 @@
+use std::{
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+use crate::{
+    backend::{Lit, SatBackend},
+    reduce::Check,
+    solver_config::SolverConfig,
+};
+
+/// Buffers of a check, sent to a worker and returned with the result, so that they can be reused
+/// for later checks
+#[derive(Clone, Default)]
+pub struct CheckBuffers {
+    pub assumptions: Vec<Lit>,
+    /// Conflict in case of UNSAT
+    pub conflict: Vec<Lit>,
+}
+
+enum Request {
+    AddClause(Vec<Lit>),
+    Solve(CheckBuffers),
+}
+
+/// Outcome of the check, with its buffers
+type Response = (Check, CheckBuffers);
+
+struct Worker {
+    requests: Sender<Request>,
+    responses: Receiver<Response>,
+}
+
+/// Copies of the negative solver living on worker threads, used to speculatively check
+/// candidates concurrently
+///
+/// As solvers can't be moved between threads, each worker builds its own copy and receives every
+/// clause added to the negative solver.
+pub struct Speculation {
+    workers: Vec<Worker>,
+}
+
+/// Allocates the variables of `lits` not allocated so far
+pub fn ensure_vars(solver: &mut dyn SatBackend, lits: &[Lit]) {
+    if let Some(max_var) = lits.iter().map(|lit| lit.var()).max() {
+        while solver.nvars() <= max_var {
+            solver.new_var();
+        }
+    }
+}
+
+impl Speculation {
+    pub fn new(workers: usize, config: &SolverConfig) -> Self {
+        let workers = (0..workers)
+            .map(|_| {
+                let config = config.clone();
+                let (requests, request_receiver) = channel();
+                let (response_sender, responses) = channel();
+                thread::spawn(move || {
+                    let mut solver = config.new_solver();
+                    // The loop ends when the corresponding `Worker` is dropped
+                    for request in request_receiver {
+                        match request {
+                            Request::AddClause(clause) => {
+                                ensure_vars(&mut *solver, &clause);
+                                solver.add_clause(&clause);
+                            }
+                            Request::Solve(mut buffers) => {
+                                ensure_vars(&mut *solver, &buffers.assumptions);
+                                let check = Check::run(
+                                    &mut *solver,
+                                    &buffers.assumptions,
+                                    &mut buffers.conflict,
+                                );
+                                let response = (check, buffers);
+                                if response_sender.send(response).is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                });
+                Worker {
+                    requests,
+                    responses,
+                }
+            })
+            .collect();
+        Speculation { workers }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        for worker in &self.workers {
+            worker
+                .requests
+                .send(Request::AddClause(clause.to_vec()))
+                .expect("speculation worker died");
+        }
+    }
+
+    /// Starts solving under `buffers.assumptions` on the given worker
+    pub fn start(&self, worker: usize, buffers: CheckBuffers) {
+        self.workers[worker]
+            .requests
+            .send(Request::Solve(buffers))
+            .expect("speculation worker died");
+    }
+
+    /// Waits for the result of the check started last on the given worker, returning its buffers
+    pub fn finish(&self, worker: usize) -> Response {
+        self.workers[worker]
+            .responses
+            .recv()
+            .expect("speculation worker died")
+    }
+}
@@ -0,0 +1,346 @@
+//! Detection of constraints and communities in clauses, for `analyze stats` and
+//! `analyze communities`
+//!
+//! Both detections only look at the clauses themselves, not at their consequences, so they find
+//! the usual direct encodings, but not every constraint implied by the formula.
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Largest XOR constraint that is detected, which is encoded by `2^(MAX_XOR_LEN - 1)` clauses
+const MAX_XOR_LEN: usize = 6;
+
+/// An XOR constraint `vars[0] ^ ... ^ vars[n - 1] = parity`
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Xor {
+    /// The positive variables, sorted
+    pub vars: Vec<isize>,
+    pub parity: bool,
+}
+
+/// Finds the XOR constraints over at least 3 variables that are encoded directly
+///
+/// An XOR constraint over `n` variables is encoded by the `2^(n - 1)` clauses over these variables
+/// that exclude one assignment of the wrong parity each. A clause excludes the assignment making
+/// all its literals false, whose number of true variables is the number of negative literals.
+/// Clauses with duplicate literals and tautologies are ignored.
+pub fn find_xors(clauses: &[Vec<isize>]) -> Vec<Xor> {
+    // For each set of variables, the clauses over exactly these variables, grouped by the parity
+    // of their negative literals
+    let mut candidates: BTreeMap<Vec<isize>, [BTreeSet<Vec<isize>>; 2]> = BTreeMap::new();
+    for clause in clauses {
+        let mut vars: Vec<isize> = clause.iter().map(|lit| lit.abs()).collect();
+        vars.sort_unstable();
+        vars.dedup();
+        if vars.len() < 3 || vars.len() > MAX_XOR_LEN || vars.len() < clause.len() {
+            continue;
+        }
+        let mut clause = clause.clone();
+        clause.sort_unstable_by_key(|lit| lit.abs());
+        let negative = clause.iter().filter(|&&lit| lit < 0).count();
+        candidates.entry(vars).or_default()[negative % 2].insert(clause);
+    }
+
+    let mut xors = vec![];
+    for (vars, by_parity) in candidates {
+        for (negative, clauses) in by_parity.iter().enumerate() {
+            if clauses.len() == 1 << (vars.len() - 1) {
+                // Excluding the assignments with an even number of true variables requires an odd
+                // one
+                xors.push(Xor {
+                    vars: vars.clone(),
+                    parity: negative == 0,
+                });
+            }
+        }
+    }
+    xors
+}
+
+/// Sizes of the chains of `xors`, the groups of at least two constraints connected by shared
+/// variables, largest first
+///
+/// Long chains typically come from a parity constraint split using auxiliary variables.
+pub fn xor_chains(xors: &[Xor]) -> Vec<usize> {
+    // Union-find over the constraints, joining those with a common variable
+    let mut parent: Vec<usize> = (0..xors.len()).collect();
+    fn root(parent: &mut [usize], mut index: usize) -> usize {
+        while parent[index] != index {
+            parent[index] = parent[parent[index]];
+            index = parent[index];
+        }
+        index
+    }
+    let mut first_with_var: BTreeMap<isize, usize> = BTreeMap::new();
+    for (index, xor) in xors.iter().enumerate() {
+        for &var in &xor.vars {
+            let other = *first_with_var.entry(var).or_insert(index);
+            let (a, b) = (root(&mut parent, index), root(&mut parent, other));
+            parent[a] = b;
+        }
+    }
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    for index in 0..xors.len() {
+        *sizes.entry(root(&mut parent, index)).or_default() += 1;
+    }
+    let mut chains: Vec<usize> = sizes.into_values().filter(|&size| size > 1).collect();
+    chains.sort_unstable_by(|a, b| b.cmp(a));
+    chains
+}
+
+/// Finds at-most-one constraints over at least 3 literals encoded pairwise by binary clauses
+///
+/// The binary clause `-a -b` forbids `a` and `b` to both be true. Groups of literals of which
+/// every pair is forbidden this way are found greedily, starting from the literals in the most
+/// binary clauses, and each binary clause is attributed to at most one group. Returns the groups,
+/// each sorted.
+pub fn find_at_most_one(clauses: &[Vec<isize>]) -> Vec<Vec<isize>> {
+    let mut conflicts: BTreeMap<isize, BTreeSet<isize>> = BTreeMap::new();
+    for clause in clauses {
+        if let [a, b] = clause[..] {
+            if a != b && a != -b {
+                conflicts.entry(-a).or_default().insert(-b);
+                conflicts.entry(-b).or_default().insert(-a);
+            }
+        }
+    }
+
+    let mut order: Vec<isize> = conflicts.keys().copied().collect();
+    order.sort_by_key(|lit| std::cmp::Reverse(conflicts[lit].len()));
+    let mut groups = vec![];
+    for lit in order {
+        let mut neighbours: Vec<isize> = conflicts[&lit].iter().copied().collect();
+        neighbours.sort_by_key(|other| std::cmp::Reverse(conflicts[other].len()));
+        let mut group = vec![lit];
+        for other in neighbours {
+            if group
+                .iter()
+                .all(|member| conflicts[&other].contains(member))
+            {
+                group.push(other);
+            }
+        }
+        if group.len() < 3 {
+            continue;
+        }
+        for (index, &a) in group.iter().enumerate() {
+            for &b in &group[index + 1..] {
+                conflicts.get_mut(&a).unwrap().remove(&b);
+                conflicts.get_mut(&b).unwrap().remove(&a);
+            }
+        }
+        group.sort_unstable();
+        groups.push(group);
+    }
+    groups
+}
+
+/// The variable interaction graph of clauses, whose edges connect variables occurring together in
+/// a clause
+///
+/// Each clause of `k` variables contributes a total weight of 1, split evenly among its
+/// `k (k - 1) / 2` edges, so long clauses don't dominate.
+struct Graph {
+    /// For each node, its neighbours and the weight of the edge to them, sorted by neighbour
+    edges: Vec<Vec<(usize, f64)>>,
+    /// For each node, the weight of its edge to itself, which arises when merging nodes
+    loops: Vec<f64>,
+}
+
+impl Graph {
+    fn degree(&self, node: usize) -> f64 {
+        2.0 * self.loops[node]
+            + self.edges[node]
+                .iter()
+                .map(|&(_, weight)| weight)
+                .sum::<f64>()
+    }
+
+    /// Total weight of all edges
+    fn weight(&self) -> f64 {
+        (0..self.edges.len())
+            .map(|node| self.degree(node))
+            .sum::<f64>()
+            / 2.0
+    }
+
+    /// Merges the nodes of each community into one node
+    fn aggregate(&self, community: &[usize], count: usize) -> Graph {
+        let mut edges: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); count];
+        let mut loops = vec![0.0; count];
+        for (node, neighbours) in self.edges.iter().enumerate() {
+            let from = community[node];
+            loops[from] += self.loops[node];
+            for &(neighbour, weight) in neighbours {
+                let to = community[neighbour];
+                if to == from {
+                    // Each edge inside a community is seen from both of its ends
+                    loops[from] += weight / 2.0;
+                } else {
+                    *edges[from].entry(to).or_default() += weight;
+                }
+            }
+        }
+        Graph {
+            edges: edges
+                .into_iter()
+                .map(|edges| edges.into_iter().collect())
+                .collect(),
+            loops,
+        }
+    }
+}
+
+/// Finds communities of variables, groups that interact much more among themselves than with the
+/// rest, by maximizing the modularity of the variable interaction graph with the Louvain method
+///
+/// Starting with every variable in a community of its own, each variable is repeatedly moved to the
+/// community of a neighbour that increases the modularity most, until no move increases it. Then
+/// each community becomes a single node and this is repeated, until no community changes. Returns
+/// the communities, each sorted, largest first.
+pub fn communities(clauses: &[Vec<isize>]) -> Vec<Vec<isize>> {
+    let vars: Vec<isize> = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.abs())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let index: BTreeMap<isize, usize> = vars.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let mut edges: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); vars.len()];
+    for clause in clauses {
+        let clause_vars: Vec<usize> = clause
+            .iter()
+            .map(|lit| index[&lit.abs()])
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let pairs = clause_vars.len() * (clause_vars.len().saturating_sub(1)) / 2;
+        for (i, &a) in clause_vars.iter().enumerate() {
+            for &b in &clause_vars[i + 1..] {
+                *edges[a].entry(b).or_default() += 1.0 / pairs as f64;
+                *edges[b].entry(a).or_default() += 1.0 / pairs as f64;
+            }
+        }
+    }
+    let mut graph = Graph {
+        edges: edges
+            .into_iter()
+            .map(|edges| edges.into_iter().collect())
+            .collect(),
+        loops: vec![0.0; vars.len()],
+    };
+
+    // The community of each variable, refined by each level
+    let mut membership: Vec<usize> = (0..vars.len()).collect();
+    let total = graph.weight();
+    if total > 0.0 {
+        loop {
+            let (community, count) = move_nodes(&graph, total);
+            if count == graph.edges.len() {
+                break;
+            }
+            for member in &mut membership {
+                *member = community[*member];
+            }
+            graph = graph.aggregate(&community, count);
+        }
+    }
+
+    let mut communities: BTreeMap<usize, Vec<isize>> = BTreeMap::new();
+    for (var, community) in vars.into_iter().zip(membership) {
+        communities.entry(community).or_default().push(var);
+    }
+    let mut communities: Vec<Vec<isize>> = communities.into_values().collect();
+    communities.sort_by_key(|community| std::cmp::Reverse(community.len()));
+    communities
+}
+
+/// Moves the nodes of `graph` between communities while that increases the modularity, returning
+/// the community of each node, numbered consecutively, and the number of communities
+fn move_nodes(graph: &Graph, total: f64) -> (Vec<usize>, usize) {
+    let nodes = graph.edges.len();
+    let degrees: Vec<f64> = (0..nodes).map(|node| graph.degree(node)).collect();
+    let mut community: Vec<usize> = (0..nodes).collect();
+    // The total degree of the nodes in each community
+    let mut community_degrees = degrees.clone();
+    let mut moved = true;
+    while moved {
+        moved = false;
+        for node in 0..nodes {
+            let current = community[node];
+            community_degrees[current] -= degrees[node];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            links.insert(current, 0.0);
+            for &(neighbour, weight) in &graph.edges[node] {
+                *links.entry(community[neighbour]).or_default() += weight;
+            }
+            // The modularity gain of adding the node to a community, up to a common factor
+            let gain = |(target, weight): (&usize, &f64)| {
+                weight - community_degrees[*target] * degrees[node] / (2.0 * total)
+            };
+            let mut best = current;
+            let mut best_gain = gain((&current, &links[&current]));
+            for link in &links {
+                // Small gains are rounding errors, which could make nodes move back and forth
+                if gain(link) > best_gain + 1e-12 {
+                    best = *link.0;
+                    best_gain = gain(link);
+                }
+            }
+            community_degrees[best] += degrees[node];
+            if best != current {
+                community[node] = best;
+                moved = true;
+            }
+        }
+    }
+
+    let mut numbers: BTreeMap<usize, usize> = BTreeMap::new();
+    for member in &mut community {
+        let next = numbers.len();
+        *member = *numbers.entry(*member).or_insert(next);
+    }
+    (community, numbers.len())
+}
+
+/// The modularity of `communities` in the variable interaction graph of `clauses`, between -0.5 and
+/// 1, where higher values mean fewer edges between communities than expected at random
+pub fn modularity(clauses: &[Vec<isize>], communities: &[Vec<isize>]) -> f64 {
+    let mut community_of: BTreeMap<isize, usize> = BTreeMap::new();
+    for (index, community) in communities.iter().enumerate() {
+        for &var in community {
+            community_of.insert(var, index);
+        }
+    }
+    let mut inside = vec![0.0; communities.len()];
+    let mut degrees = vec![0.0; communities.len()];
+    let mut total = 0.0;
+    for clause in clauses {
+        let clause_vars: Vec<isize> = clause
+            .iter()
+            .map(|lit| lit.abs())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let pairs = clause_vars.len() * (clause_vars.len().saturating_sub(1)) / 2;
+        for (i, &a) in clause_vars.iter().enumerate() {
+            for &b in &clause_vars[i + 1..] {
+                let weight = 1.0 / pairs as f64;
+                let (a, b) = (community_of[&a], community_of[&b]);
+                total += weight;
+                degrees[a] += weight;
+                degrees[b] += weight;
+                if a == b {
+                    inside[a] += weight;
+                }
+            }
+        }
+    }
+    if total == 0.0 {
+        return 0.0;
+    }
+    inside
+        .iter()
+        .zip(&degrees)
+        .map(|(inside, degree)| inside / total - (degree / (2.0 * total)).powi(2))
+        .sum()
+}
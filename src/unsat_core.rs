@@ -0,0 +1,73 @@
+//! Unsatisfiable cores in terms of the clauses of a formula or of groups of them
+//!
+//! The solvers of a [`Finder`](crate::Finder) don't keep track of which clauses are responsible
+//! for a conflict, so the core is computed with a separate solver, in which each clause is guarded
+//! by the selector variable of its group, which is assumed. For a core of clauses, each clause is
+//! a group of its own.
+use std::collections::HashMap;
+
+use crate::{
+    backend::{Lbool, Lit, SatBackend},
+    solver_config::SolverConfig,
+};
+
+/// Returns the indices of a minimal unsatisfiable subset of `clauses`, or `None` if they are
+/// satisfiable
+///
+/// Removing any clause of the returned subset makes it satisfiable. The indices are sorted.
+pub fn unsat_core(clauses: &[Vec<isize>]) -> Option<Vec<usize>> {
+    let groups: Vec<usize> = (0..clauses.len()).collect();
+    group_unsat_core(clauses, &groups)
+}
+
+/// Returns a minimal unsatisfiable subset of groups of `clauses`, where `groups[i]` is the group
+/// of the clause with index `i`, or `None` if the clauses are satisfiable
+///
+/// Removing all clauses of any group of the returned subset makes the clauses of the remaining
+/// groups satisfiable. The groups are sorted.
+pub fn group_unsat_core(clauses: &[Vec<isize>], groups: &[usize]) -> Option<Vec<usize>> {
+    assert_eq!(clauses.len(), groups.len());
+    let mut solver = SolverConfig::default().new_solver();
+    // The selector variable of the group with index `i` is the solver variable `i`
+    let group_count = groups.iter().map(|&group| group + 1).max().unwrap_or(0);
+    let selectors: Vec<Lit> = (0..group_count).map(|_| solver.new_var()).collect();
+    let mut vars: HashMap<isize, Lit> = HashMap::new();
+    for (clause, &group) in clauses.iter().zip(groups) {
+        let mut lits = vec![!selectors[group]];
+        for &lit in clause {
+            let var = *vars.entry(lit.abs()).or_insert_with(|| solver.new_var());
+            lits.push(if lit < 0 { !var } else { var });
+        }
+        solver.add_clause(&lits);
+    }
+
+    // Without any assumptions all guarded clauses can be disabled, so every conflict is non-empty
+    let conflict = |solver: &dyn SatBackend| -> Vec<usize> {
+        let mut conflict = vec![];
+        solver.conflict(&mut conflict);
+        conflict.iter().map(|lit| lit.var() as usize).collect()
+    };
+
+    if solver.solve_with_assumptions(&selectors) != Lbool::False {
+        return None;
+    }
+    let mut candidates = conflict(&*solver);
+    let mut required = vec![];
+    // Removing a required group from a satisfiable subset keeps it satisfiable, so every later
+    // conflict contains all required groups again
+    while let Some(candidate) = candidates.pop() {
+        let assumptions: Vec<Lit> = required
+            .iter()
+            .chain(&candidates)
+            .map(|&index| selectors[index])
+            .collect();
+        if solver.solve_with_assumptions(&assumptions) == Lbool::False {
+            let core = conflict(&*solver);
+            candidates.retain(|index| core.contains(index));
+        } else {
+            required.push(candidate);
+        }
+    }
+    required.sort_unstable();
+    Some(required)
+}
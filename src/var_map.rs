@@ -0,0 +1,74 @@
+//! Mapping between the variables of the user and those of the solvers.
+
+/// What a solver variable stands for
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VarName {
+    UserVar(isize),
+    Clause(usize),
+    Chain(usize),
+    /// Used by the encoding of a constraint
+    Aux,
+}
+
+/// Bidirectional map between solver variable indices and their names
+///
+/// Solver variables are allocated densely in insertion order. Only user variables are ever looked
+/// up by name, which is done using a table indexed by the user variable, so user variables should
+/// be reasonably dense, as is the case for DIMACS input.
+#[derive(Default)]
+pub struct VarMap {
+    names: Vec<VarName>,
+    /// Indexed by user variable, contains the solver variable index plus one or zero if unmapped
+    user_vars: Vec<u32>,
+}
+
+impl VarMap {
+    /// Number of solver variables allocated so far
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns the name of the solver variable with the given index
+    pub fn get(&self, index: usize) -> Option<VarName> {
+        self.names.get(index).cloned()
+    }
+
+    /// Returns the index of the solver variable of a user variable, allocating it if necessary
+    pub fn user_var(&mut self, user_var: isize) -> usize {
+        let key = user_var as usize;
+        if let Some(&index) = self.user_vars.get(key) {
+            if index != 0 {
+                return index as usize - 1;
+            }
+        } else {
+            self.user_vars.resize(key + 1, 0);
+        }
+        let index = self.push(VarName::UserVar(user_var));
+        self.user_vars[key] = index as u32 + 1;
+        index
+    }
+
+    /// Allocates a new solver variable for an auxiliary variable, returning its index
+    pub fn push(&mut self, name: VarName) -> usize {
+        let index = self.names.len();
+        assert!(index < u32::MAX as usize, "too many variables");
+        self.names.push(name);
+        index
+    }
+
+    /// Forgets the user variables with an index of at least `start`, which become auxiliary
+    /// variables, so the user variables are allocated again when they occur next
+    pub fn forget_user_vars(&mut self, start: usize) {
+        for name in &mut self.names[start..] {
+            if let VarName::UserVar(user_var) = *name {
+                self.user_vars[user_var as usize] = 0;
+                *name = VarName::Aux;
+            }
+        }
+    }
+
+    /// Iterates over the names of all solver variables in order of their index
+    pub fn iter(&self) -> impl Iterator<Item = VarName> + '_ {
+        self.names.iter().cloned()
+    }
+}
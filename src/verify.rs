@@ -0,0 +1,98 @@
+//! Checks of reduced models that are independent of the solvers and the encoding
+//!
+//! A partial assignment entails a CNF formula exactly when it assigns a literal of every
+//! non-tautological clause, so these checks only need the clauses and no solver at all.
+use std::collections::{HashMap, HashSet};
+
+/// Checks that the partial assignment `model` entails all `clauses`
+pub fn check_entailment(clauses: &[Vec<isize>], model: &[isize]) -> anyhow::Result<()> {
+    let model = assignment(model)?;
+    for clause in clauses {
+        if !is_tautology(clause) && !clause.iter().any(|lit| model.contains(lit)) {
+            anyhow::bail!("model doesn't entail clause {:?}", clause);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the indices of all `clauses` that the partial assignment `model` doesn't entail
+pub fn unentailed(clauses: &[Vec<isize>], model: &[isize]) -> anyhow::Result<Vec<usize>> {
+    let model = assignment(model)?;
+    Ok(clauses
+        .iter()
+        .enumerate()
+        .filter(|(_, clause)| {
+            !is_tautology(clause) && !clause.iter().any(|lit| model.contains(lit))
+        })
+        .map(|(index, _)| index)
+        .collect())
+}
+
+/// Checks that the full model `model` assigns every variable of `clauses` and satisfies them
+pub fn check_full_model(clauses: &[Vec<isize>], model: &[isize]) -> anyhow::Result<()> {
+    let vars: HashSet<isize> = model.iter().map(|lit| lit.abs()).collect();
+    if let Some(lit) = clauses
+        .iter()
+        .flatten()
+        .find(|lit| !vars.contains(&lit.abs()))
+    {
+        anyhow::bail!("full model doesn't assign variable {}", lit.abs());
+    }
+    check_entailment(clauses, model)
+}
+
+/// Checks that no literal can be removed from `model` without losing entailment of `clauses`
+///
+/// Assumes that `model` entails `clauses`.
+pub fn check_minimality(clauses: &[Vec<isize>], model: &[isize]) -> anyhow::Result<()> {
+    for (lit, witness) in model.iter().zip(witnesses(clauses, model)?) {
+        if witness.is_none() {
+            anyhow::bail!("model isn't minimal, literal {} can be removed", lit);
+        }
+    }
+    Ok(())
+}
+
+/// Finds a witness for each literal of `model` being essential
+///
+/// A witness is a non-tautological clause in which the literal is the only one assigned by
+/// `model`, so removing the literal loses entailment of that clause. Literals that can be removed
+/// have no witness.
+pub fn witnesses<'a>(
+    clauses: &'a [Vec<isize>],
+    model: &[isize],
+) -> anyhow::Result<Vec<Option<&'a [isize]>>> {
+    let assignment = assignment(model)?;
+    let mut witnesses: HashMap<isize, &[isize]> = HashMap::new();
+    for clause in clauses {
+        if is_tautology(clause) {
+            continue;
+        }
+        if let Some(lit) = only_assigned(&assignment, clause) {
+            witnesses.entry(lit).or_insert(clause);
+        }
+    }
+    Ok(model
+        .iter()
+        .map(|lit| witnesses.get(lit).cloned())
+        .collect())
+}
+
+/// Returns the only literal of `clause` assigned by `assignment`, ignoring duplicates
+pub(crate) fn only_assigned(assignment: &HashSet<isize>, clause: &[isize]) -> Option<isize> {
+    let mut assigned = clause.iter().filter(|lit| assignment.contains(lit));
+    let &lit = assigned.next()?;
+    assigned.all(|&other| other == lit).then_some(lit)
+}
+
+pub(crate) fn assignment(model: &[isize]) -> anyhow::Result<HashSet<isize>> {
+    let assignment: HashSet<isize> = model.iter().cloned().collect();
+    if let Some(lit) = model.iter().find(|&&lit| assignment.contains(&-lit)) {
+        anyhow::bail!("model assigns both {} and {}", lit, -lit);
+    }
+    Ok(assignment)
+}
+
+pub(crate) fn is_tautology(clause: &[isize]) -> bool {
+    clause.iter().any(|lit| clause.contains(&-lit))
+}
@@ -0,0 +1,116 @@
+//! Random formulas and brute force minimal models shared by the tests comparing against them
+use std::collections::BTreeSet;
+
+use minimal_models::{Finder, Rng, Solved};
+
+/// Random formula over the variables `1..=vars`, which may contain duplicate literals and
+/// tautologies
+pub fn random_formula(rng: &mut Rng, vars: usize) -> Vec<Vec<isize>> {
+    let clauses = rng.below(10);
+    (0..clauses)
+        .map(|_| {
+            let len = 1 + rng.below(3);
+            (0..len)
+                .map(|_| {
+                    let var = 1 + rng.below(vars) as isize;
+                    if rng.below(2) == 0 {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn satisfies(clauses: &[Vec<isize>], assignment: u32) -> bool {
+    clauses.iter().all(|clause| {
+        clause
+            .iter()
+            .any(|&lit| (assignment >> (lit.abs() - 1) & 1 == 1) == (lit > 0))
+    })
+}
+
+/// Whether every full assignment extending `partial` satisfies `clauses`
+pub fn entails(clauses: &[Vec<isize>], vars: usize, partial: &[isize]) -> bool {
+    (0..1u32 << vars).all(|assignment| {
+        let extends = partial
+            .iter()
+            .all(|&lit| (assignment >> (lit.abs() - 1) & 1 == 1) == (lit > 0));
+        !extends || satisfies(clauses, assignment)
+    })
+}
+
+/// All minimal partial assignments entailing `clauses`, each sorted
+pub fn minimal_models(clauses: &[Vec<isize>], vars: usize) -> BTreeSet<Vec<isize>> {
+    let mut minimal = BTreeSet::new();
+    for code in 0..3usize.pow(vars as u32) {
+        let mut partial = vec![];
+        let mut code = code;
+        for var in 1..=vars as isize {
+            match code % 3 {
+                1 => partial.push(var),
+                2 => partial.push(-var),
+                _ => (),
+            }
+            code /= 3;
+        }
+        // By monotonicity it suffices to check the removal of single literals
+        if entails(clauses, vars, &partial)
+            && (0..partial.len()).all(|skip| {
+                let mut smaller = partial.clone();
+                smaller.remove(skip);
+                !entails(clauses, vars, &smaller)
+            })
+        {
+            partial.sort_unstable();
+            minimal.insert(partial);
+        }
+    }
+    minimal
+}
+
+/// Enumerates all minimal models of `clauses` using `finder`, which contains these clauses
+pub fn enumerate(finder: &mut Finder, mut clauses: Vec<Vec<isize>>, vars: usize, context: &str) {
+    for _ in 0..=3usize.pow(vars as u32) {
+        match finder.solve() {
+            Solved::Model(model) => {
+                let full: Vec<isize> = model
+                    .iter()
+                    .cloned()
+                    .filter(|lit| lit.unsigned_abs() <= vars)
+                    .collect();
+                assert!(
+                    entails(&clauses, vars, &full),
+                    "{}: full model {:?}",
+                    context,
+                    model
+                );
+
+                let reduced = finder.reduce(&model);
+                assert!(reduced.is_minimal(), "{}", context);
+                let mut reduced = reduced.model;
+                reduced.sort_unstable();
+                assert!(
+                    minimal_models(&clauses, vars).contains(&reduced),
+                    "{}: reduced model {:?} isn't minimal",
+                    context,
+                    reduced
+                );
+
+                finder.block(&reduced);
+                clauses.push(reduced.iter().map(|&lit| -lit).collect());
+            }
+            Solved::Unsat => {
+                assert!(
+                    (0..1u32 << vars).all(|assignment| !satisfies(&clauses, assignment)),
+                    "{}: wrongly unsat",
+                    context
+                );
+                break;
+            }
+            Solved::Unknown => panic!("{}: unknown without limits", context),
+        }
+    }
+}
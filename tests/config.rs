@@ -0,0 +1,87 @@
+//! Checks the translation of environment variables and configuration files into arguments.
+use minimal_models::config::{self, OptionValue};
+
+const OPTIONS: &[(&str, OptionValue, &str)] = &[
+    ("--all", OptionValue::None, ""),
+    ("--seed", OptionValue::Any("S"), ""),
+    ("--solver-opt", OptionValue::Any("KEY=VAL"), ""),
+];
+
+fn env_args(vars: &[(&str, &str)]) -> anyhow::Result<Vec<String>> {
+    let vars = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+    config::env_args(vars, OPTIONS)
+}
+
+#[test]
+fn env() {
+    assert_eq!(
+        env_args(&[
+            ("MINIMAL_MODELS_SEED", "3"),
+            ("MINIMAL_MODELS_ALL", "1"),
+            ("HOME", "/root"),
+        ])
+        .unwrap(),
+        ["--all", "--seed", "3"]
+    );
+    for value in ["true", "yes", "1", "TRUE"] {
+        assert_eq!(
+            env_args(&[("MINIMAL_MODELS_ALL", value)]).unwrap(),
+            ["--all"]
+        );
+    }
+    for value in ["false", "no", "0"] {
+        assert!(env_args(&[("MINIMAL_MODELS_ALL", value)])
+            .unwrap()
+            .is_empty());
+    }
+    for value in ["", "2", "on"] {
+        let err = env_args(&[("MINIMAL_MODELS_ALL", value)]).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("MINIMAL_MODELS_ALL"),
+            "{:#}",
+            err
+        );
+    }
+    let err = env_args(&[("MINIMAL_MODELS_FOO", "x")]).unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "environment variable MINIMAL_MODELS_FOO: unknown option --foo"
+    );
+}
+
+#[test]
+fn file() {
+    let text = "# comment\nall = true\nseed = 3  # trailing\nsolver-opt = [\"a=1\", \"b=2\"]\n";
+    assert_eq!(
+        config::parse(text, "config.toml", OPTIONS).unwrap(),
+        [
+            "--all",
+            "--seed",
+            "3",
+            "--solver-opt",
+            "a=1",
+            "--solver-opt",
+            "b=2"
+        ]
+    );
+    assert!(config::parse("all = false\n", "config.toml", OPTIONS)
+        .unwrap()
+        .is_empty());
+    for (text, message) in [
+        (
+            "\nall = 1",
+            "config.toml:2: all is a flag, expected true or false, not 1",
+        ),
+        (
+            "seed = true",
+            "config.toml:1: seed takes a value, not a boolean",
+        ),
+        ("foo = 1", "config.toml:1: unknown option foo"),
+        ("[table]", "config.toml:1: tables aren't supported"),
+    ] {
+        let err = config::parse(text, "config.toml", OPTIONS).unwrap_err();
+        assert_eq!(format!("{:#}", err), message);
+    }
+}
@@ -0,0 +1,1305 @@
+//! Compares the reduced models against minimal models computed by brute force on small random
+//! formulas.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+pub mod common;
+
+use common::{entails, enumerate, minimal_models, random_formula, satisfies};
+use minimal_models::{
+    autarky,
+    coverage::{self, count_models, Coverage},
+    definability,
+    learned::Learned,
+    maxsat::{self, MaxSat, Wcnf},
+    script::Script,
+    structure, Assumed, Backend, Builder, CandidateOrder, CardinalityEncoding, Check, Consequence,
+    Finder, Histogram, Optimized, Optimizer, PbEncoding, Preference, Reduced, Rng, Solved,
+    SolverOptions,
+};
+
+/// Enumerates models of random formulas, checking each reduced model against brute force
+fn check(name: &str, builder: impl Fn() -> Builder) {
+    check_with(name, builder, |_, _| ());
+}
+
+/// Like [`check`], calling `setup` with each finder and the index of its formula before solving
+fn check_with(name: &str, builder: impl Fn() -> Builder, setup: impl Fn(&mut Finder, u64)) {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("{} formula {}: {:?}", name, formula_index, clauses);
+
+        let mut finder: Finder = builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        setup(&mut finder, formula_index);
+
+        enumerate(&mut finder, clauses, vars, &context);
+    }
+}
+
+/// A builder checked by [`builders`], named in the failure messages
+type Case = (&'static str, fn() -> Builder);
+
+#[test]
+fn builders() {
+    let builders: &[Case] = &[
+        ("default", Finder::builder),
+        ("occurrence", || {
+            Finder::builder().order(CandidateOrder::Occurrence)
+        }),
+        ("core shuffled", || {
+            Finder::builder()
+                .order(CandidateOrder::Core)
+                .shuffle_candidates(1)
+        }),
+        ("preprocess", || Finder::builder().preprocess(true)),
+        ("equivalences", || Finder::builder().equivalences(true)),
+        ("pure literals", || Finder::builder().pure_literals(true)),
+        ("warm start", || Finder::builder().warm_start(true)),
+        ("speculate", || Finder::builder().speculate(2)),
+        ("portfolio", || Finder::builder().portfolio(3)),
+        ("script", || {
+            let script =
+                Script::parse("unsat - sat + (positive ? var % 3 : -occurrences)").unwrap();
+            Finder::builder().script(script)
+        }),
+        ("initial backend", || {
+            Finder::builder().pos_solver_options(SolverOptions {
+                initial_backend: Some(Backend::Builtin),
+                ..SolverOptions::default()
+            })
+        }),
+        ("builtin", || both_solvers(Backend::Builtin)),
+        ("mixed backends", || {
+            Finder::builder().neg_solver_options(SolverOptions {
+                backend: Backend::Builtin,
+                ..SolverOptions::default()
+            })
+        }),
+        #[cfg(feature = "cadical")]
+        ("cadical", || both_solvers(Backend::CaDiCaL)),
+        #[cfg(feature = "minisat")]
+        ("minisat", || both_solvers(Backend::MiniSat)),
+        #[cfg(feature = "kissat")]
+        ("kissat", || {
+            Finder::builder().pos_solver_options(SolverOptions {
+                initial_backend: Some(Backend::Kissat),
+                ..SolverOptions::default()
+            })
+        }),
+    ];
+    for (name, builder) in builders {
+        check(name, builder);
+    }
+}
+
+/// A builder using `backend` for both solvers
+fn both_solvers(backend: Backend) -> Builder {
+    let options = SolverOptions {
+        backend,
+        ..SolverOptions::default()
+    };
+    Finder::builder()
+        .pos_solver_options(options.clone())
+        .neg_solver_options(options)
+}
+
+/// Clauses excluding every way of having more than `k` of `lits` true
+fn at_most_k_clauses(lits: &[isize], k: usize) -> Vec<Vec<isize>> {
+    (0..1u32 << lits.len())
+        .filter(|subset| subset.count_ones() as usize == k + 1)
+        .map(|subset| {
+            (0..lits.len())
+                .filter(|&index| subset >> index & 1 == 1)
+                .map(|index| -lits[index])
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn at_most_k() {
+    for encoding in [
+        CardinalityEncoding::Totalizer,
+        CardinalityEncoding::SequentialCounter,
+        CardinalityEncoding::SortingNetwork,
+    ] {
+        check_at_most_k(encoding);
+    }
+}
+
+fn check_at_most_k(encoding: CardinalityEncoding) {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let mut clauses = random_formula(&mut rng, vars);
+        // The constraints' literals aren't counted as occurrences, but mustn't be dropped
+        let mut finder = Finder::builder()
+            .pure_literals(formula_index.is_multiple_of(2))
+            .build()
+            .unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let mut constraints = vec![];
+        for _ in 0..1 + rng.below(2) {
+            let lits: Vec<isize> = (0..1 + rng.below(5))
+                .map(|_| {
+                    let var = 1 + rng.below(vars) as isize;
+                    if rng.below(2) == 0 {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect();
+            let k = rng.below(lits.len() + 1);
+            finder.add_at_most_k_with(&lits, k, encoding);
+            clauses.extend(at_most_k_clauses(&lits, k));
+            constraints.push((lits, k));
+        }
+        let context = format!(
+            "{:?} formula {}: {:?} {:?}",
+            encoding, formula_index, clauses, constraints
+        );
+        enumerate(&mut finder, clauses, vars, &context);
+    }
+}
+
+/// Clauses excluding every assignment of the literals' variables that violates the constraint
+fn pb_clauses(terms: &[(i64, isize)], bound: i64) -> Vec<Vec<isize>> {
+    let mut vars: Vec<isize> = terms.iter().map(|&(_, lit)| lit.abs()).collect();
+    vars.sort();
+    vars.dedup();
+    (0..1u32 << vars.len())
+        .filter(|assignment| {
+            let sum: i64 = terms
+                .iter()
+                .filter(|&&(_, lit)| {
+                    let index = vars.binary_search(&lit.abs()).unwrap();
+                    (assignment >> index & 1 == 1) == (lit > 0)
+                })
+                .map(|&(coefficient, _)| coefficient)
+                .sum();
+            sum > bound
+        })
+        .map(|assignment| {
+            (0..vars.len())
+                .map(|index| {
+                    if assignment >> index & 1 == 1 {
+                        -vars[index]
+                    } else {
+                        vars[index]
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn pb() {
+    for encoding in [PbEncoding::Bdd, PbEncoding::Adder] {
+        check_pb(encoding);
+    }
+}
+
+fn check_pb(encoding: PbEncoding) {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let mut clauses = random_formula(&mut rng, vars);
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let mut constraints = vec![];
+        for _ in 0..1 + rng.below(2) {
+            let terms: Vec<(i64, isize)> = (0..1 + rng.below(5))
+                .map(|_| {
+                    let coefficient = rng.below(9) as i64 - 4;
+                    let var = 1 + rng.below(vars) as isize;
+                    if rng.below(2) == 0 {
+                        (coefficient, var)
+                    } else {
+                        (coefficient, -var)
+                    }
+                })
+                .collect();
+            let bound = rng.below(11) as i64 - 3;
+            finder.add_pb_with(&terms, bound, encoding);
+            clauses.extend(pb_clauses(&terms, bound));
+            constraints.push((terms, bound));
+        }
+        let context = format!(
+            "{:?} formula {}: {:?} {:?}",
+            encoding, formula_index, clauses, constraints
+        );
+        enumerate(&mut finder, clauses, vars, &context);
+    }
+}
+
+#[test]
+fn minimum_model() {
+    for optimizer in [Optimizer::CoreGuided, Optimizer::Linear] {
+        check_minimum_model(optimizer);
+    }
+}
+
+fn check_minimum_model(optimizer: Optimizer) {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let mut clauses = random_formula(&mut rng, vars);
+        let mut finder = Finder::builder().optimizer(optimizer).build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        if rng.below(2) == 0 {
+            let lits: Vec<isize> = (1..=vars as isize).collect();
+            let k = rng.below(vars + 1);
+            finder.add_at_most_k(&lits, k);
+            clauses.extend(at_most_k_clauses(&lits, k));
+        }
+        // Weights of the positive and negative literal of each variable
+        let weights: Vec<[u64; 2]> = (0..vars)
+            .map(|_| [rng.below(4) as u64, rng.below(4) as u64])
+            .collect();
+        let weight = |lit: isize| weights[lit.unsigned_abs() - 1][(lit < 0) as usize];
+        let context = format!(
+            "{:?} formula {}: {:?} {:?}",
+            optimizer, formula_index, clauses, weights
+        );
+
+        let minimum = minimal_models(&clauses, vars)
+            .iter()
+            .map(|model| model.iter().map(|&lit| weight(lit)).sum::<u64>())
+            .min();
+        match (finder.minimum_model_weighted(weight), minimum) {
+            (
+                Optimized::Model {
+                    model,
+                    weight: found,
+                },
+                Some(minimum),
+            ) => {
+                assert!(entails(&clauses, vars, &model), "{}: {:?}", context, model);
+                assert!(
+                    model.iter().all(|lit| !model.contains(&-lit)),
+                    "{}: {:?}",
+                    context,
+                    model
+                );
+                let total: u64 = model.iter().map(|&lit| weight(lit)).sum();
+                assert_eq!(
+                    (found, total),
+                    (minimum, minimum),
+                    "{}: {:?}",
+                    context,
+                    model
+                );
+            }
+            (Optimized::Unsat, None) => (),
+            (optimized, minimum) => panic!("{}: {:?} {:?}", context, optimized, minimum),
+        }
+
+        let minimum = minimal_models(&clauses, vars).iter().map(Vec::len).min();
+        match (finder.minimum_model(), minimum) {
+            (Optimized::Model { model, weight }, Some(minimum)) => {
+                assert!(entails(&clauses, vars, &model), "{}: {:?}", context, model);
+                assert_eq!(
+                    (weight, model.len()),
+                    (minimum as u64, minimum),
+                    "{}",
+                    context
+                );
+            }
+            (Optimized::Unsat, None) => (),
+            (optimized, minimum) => panic!("{}: {:?} {:?}", context, optimized, minimum),
+        }
+    }
+}
+
+#[test]
+fn maxsat() {
+    for optimizer in [Optimizer::CoreGuided, Optimizer::Linear] {
+        let mut rng = Rng(0);
+        for formula_index in 0..200u64 {
+            let vars = 1 + rng.below(6);
+            let wcnf = Wcnf {
+                hard: random_formula(&mut rng, vars),
+                soft: random_formula(&mut rng, vars)
+                    .into_iter()
+                    .map(|clause| (1 + rng.below(5) as u64, clause))
+                    .collect(),
+            };
+            let context = format!("{:?} formula {}: {:?}", optimizer, formula_index, wcnf);
+
+            let cost = |assignment: u32| -> u64 {
+                wcnf.soft
+                    .iter()
+                    .filter(|(_, clause)| !satisfies(std::slice::from_ref(clause), assignment))
+                    .map(|&(weight, _)| weight)
+                    .sum()
+            };
+            let minimum = (0..1u32 << vars)
+                .filter(|&assignment| satisfies(&wcnf.hard, assignment))
+                .map(cost)
+                .min();
+            match (
+                maxsat::solve(&wcnf, &SolverOptions::default(), optimizer).unwrap(),
+                minimum,
+            ) {
+                (MaxSat::Optimum { model, cost: found }, Some(minimum)) => {
+                    let assignment = model
+                        .iter()
+                        .filter(|&&lit| lit > 0)
+                        .map(|&lit| 1 << (lit - 1))
+                        .sum();
+                    assert!(satisfies(&wcnf.hard, assignment), "{}", context);
+                    assert_eq!((found, cost(assignment)), (minimum, minimum), "{}", context);
+                }
+                (MaxSat::Unsat, None) => (),
+                (solved, minimum) => panic!("{}: {:?} {:?}", context, solved, minimum),
+            }
+        }
+    }
+}
+
+#[test]
+fn group_unsat_core() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(3);
+        let clauses = random_formula(&mut rng, vars);
+        let group_count = 1 + rng.below(4);
+        let groups: Vec<usize> = clauses.iter().map(|_| rng.below(group_count)).collect();
+        let context = format!("formula {}: {:?} {:?}", formula_index, clauses, groups);
+
+        let satisfiable = |selected: &[usize]| {
+            let subset: Vec<Vec<isize>> = clauses
+                .iter()
+                .zip(&groups)
+                .filter(|(_, group)| selected.contains(group))
+                .map(|(clause, _)| clause.clone())
+                .collect();
+            (0..1u32 << vars).any(|assignment| satisfies(&subset, assignment))
+        };
+        match minimal_models::unsat_core::group_unsat_core(&clauses, &groups) {
+            Some(core) => {
+                assert!(!satisfiable(&core), "{}: {:?}", context, core);
+                for &group in &core {
+                    let rest: Vec<usize> = core.iter().copied().filter(|&g| g != group).collect();
+                    assert!(
+                        satisfiable(&rest),
+                        "{}: {:?} without {}",
+                        context,
+                        core,
+                        group
+                    );
+                }
+            }
+            None => assert!(satisfiable(&groups), "{}", context),
+        }
+    }
+}
+
+#[test]
+fn coverage() {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        // Counting over all variables, unused ones double the count
+        let used: HashSet<isize> = clauses.iter().flatten().map(|lit| lit.abs()).collect();
+        let models = (0..1u32 << vars)
+            .filter(|&assignment| satisfies(&clauses, assignment))
+            .count();
+        let counted = count_models(&clauses, coverage::DEFAULT_BUDGET).unwrap();
+        assert_eq!(
+            counted * 2f64.powi((vars - used.len()) as i32),
+            models as f64,
+            "{}",
+            context
+        );
+
+        let mut finder = Finder::builder().build().unwrap();
+        let mut coverage = Coverage::default();
+        for clause in &clauses {
+            finder.add_clause(clause);
+            coverage.add_clause(clause);
+        }
+        let mut reported = vec![];
+        while let Solved::Model(model) = finder.solve() {
+            let reduced = finder.reduce(&model).model;
+            let blocking: Vec<isize> = reduced.iter().map(|&lit| -lit).collect();
+            finder.add_clause(&blocking);
+            coverage.add_blocking(&blocking);
+            reported.push(reduced);
+
+            let covered = (0..1u32 << vars)
+                .filter(|&assignment| {
+                    satisfies(&clauses, assignment)
+                        && reported.iter().any(|model| {
+                            model
+                                .iter()
+                                .all(|&lit| (assignment >> (lit.abs() - 1) & 1 == 1) == (lit > 0))
+                        })
+                })
+                .count();
+            let fraction = coverage.fraction(coverage::DEFAULT_BUDGET).unwrap();
+            assert!(
+                (fraction - covered as f64 / models as f64).abs() < 1e-9,
+                "{}: {:?}",
+                context,
+                reported
+            );
+        }
+        assert_eq!(
+            coverage.fraction(coverage::DEFAULT_BUDGET),
+            (models > 0).then_some(1.0),
+            "{}",
+            context
+        );
+    }
+}
+
+#[test]
+fn communities() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(10);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let communities = structure::communities(&clauses);
+
+        let used: BTreeSet<isize> = clauses.iter().flatten().map(|lit| lit.abs()).collect();
+        let mut seen = BTreeSet::new();
+        for community in &communities {
+            assert!(!community.is_empty(), "{}", context);
+            for &var in community {
+                assert!(seen.insert(var), "{}: {:?}", context, communities);
+            }
+        }
+        assert_eq!(seen, used, "{}", context);
+
+        // Merging unconnected variables only lowers the modularity
+        let mut component: HashMap<isize, isize> = used.iter().map(|&var| (var, var)).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for clause in &clauses {
+                let min = clause
+                    .iter()
+                    .map(|lit| component[&lit.abs()])
+                    .min()
+                    .unwrap();
+                for lit in clause {
+                    if component[&lit.abs()] != min {
+                        component.insert(lit.abs(), min);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        for community in &communities {
+            assert!(
+                community
+                    .iter()
+                    .all(|var| component[var] == component[&community[0]]),
+                "{}: {:?}",
+                context,
+                communities
+            );
+        }
+
+        let singletons: Vec<Vec<isize>> = used.iter().map(|&var| vec![var]).collect();
+        let modularity = structure::modularity(&clauses, &communities);
+        assert!(
+            modularity >= structure::modularity(&clauses, &singletons) - 1e-9,
+            "{}: {:?}",
+            context,
+            communities
+        );
+        assert!((-0.5..=1.0).contains(&modularity), "{}", context);
+    }
+}
+
+#[test]
+fn circumscribe() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        // Each variable is minimized, fixed or varying
+        let partition: Vec<usize> = (0..vars).map(|_| rng.below(3)).collect();
+        let select = |part: usize| -> Vec<isize> {
+            (1..=vars as isize)
+                .filter(|&var| partition[var as usize - 1] == part)
+                .collect()
+        };
+        let (minimized, fixed) = (select(0), select(1));
+        let context = format!("formula {}: {:?} {:?}", formula_index, clauses, partition);
+
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let model = match finder.solve() {
+            Solved::Model(model) => model,
+            Solved::Unsat => continue,
+            Solved::Unknown => panic!("{}: unknown", context),
+        };
+        let circumscribed = match finder.circumscribe(&model, &minimized, &fixed) {
+            Solved::Model(circumscribed) => circumscribed,
+            solved => panic!("{}: {:?}", context, solved),
+        };
+
+        let assignment = |model: &[isize]| -> u32 {
+            model
+                .iter()
+                .filter(|&&lit| lit > 0 && lit as usize <= vars)
+                .map(|&lit| 1 << (lit - 1))
+                .sum()
+        };
+        let (before, after) = (assignment(&model), assignment(&circumscribed));
+        let mask = |part: usize| -> u32 { select(part).iter().map(|&var| 1 << (var - 1)).sum() };
+        let (minimized, fixed) = (mask(0), mask(1));
+        assert!(
+            satisfies(&clauses, after),
+            "{}: {:?}",
+            context,
+            circumscribed
+        );
+        assert_eq!(after & fixed, before & fixed, "{}", context);
+        assert_eq!(after & minimized & !before, 0, "{}", context);
+        let smaller = (0..1u32 << vars).find(|&other| {
+            satisfies(&clauses, other)
+                && other & fixed == after & fixed
+                && other & minimized != after & minimized
+                && other & minimized & !after == 0
+        });
+        assert_eq!(smaller, None, "{}: {:?}", context, circumscribed);
+    }
+}
+
+#[test]
+fn solve_under_assumptions() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let mut assumptions = vec![];
+        for var in 1..=vars as isize {
+            match rng.below(3) {
+                0 => assumptions.push(var),
+                1 => assumptions.push(-var),
+                _ => (),
+            }
+        }
+        let context = format!("formula {}: {:?} {:?}", formula_index, clauses, assumptions);
+
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let with_units = |lits: &[isize]| -> Vec<Vec<isize>> {
+            let mut clauses = clauses.clone();
+            clauses.extend(lits.iter().map(|&lit| vec![lit]));
+            clauses
+        };
+        let satisfiable = |lits: &[isize]| {
+            (0..1u32 << vars).any(|assignment| satisfies(&with_units(lits), assignment))
+        };
+
+        match finder.solve_under_assumptions(&assumptions) {
+            Assumed::Model(reduced) => {
+                assert!(satisfiable(&assumptions), "{}", context);
+                let mut model = reduced.model;
+                model.sort_unstable();
+                assert!(
+                    minimal_models(&clauses, vars).contains(&model),
+                    "{}: {:?}",
+                    context,
+                    model
+                );
+                // Some full model agreeing with the assumptions extends the reduced model
+                model.extend(&assumptions);
+                assert!(satisfiable(&model), "{}: {:?}", context, model);
+            }
+            Assumed::Unsat { core } => {
+                assert!(
+                    core.iter().all(|lit| assumptions.contains(lit)),
+                    "{}: {:?}",
+                    context,
+                    core
+                );
+                assert!(!satisfiable(&core), "{}: {:?}", context, core);
+            }
+            Assumed::Unknown => panic!("{}: unknown", context),
+        }
+        // The assumptions don't persist
+        if satisfiable(&[]) {
+            assert!(
+                matches!(finder.solve_under_assumptions(&[]), Assumed::Model(_)),
+                "{}",
+                context
+            );
+        }
+    }
+}
+
+#[test]
+fn use_hint() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let assignment = rng.below(1 << vars) as u32;
+        // Also hints a variable the formula doesn't contain, which is ignored
+        let hint: Vec<isize> = (1..=vars as isize + 1)
+            .map(|var| {
+                if assignment >> (var - 1) & 1 == 1 {
+                    var
+                } else {
+                    -var
+                }
+            })
+            .collect();
+        let context = format!("formula {}: {:?} {:?}", formula_index, clauses, hint);
+
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        match finder.use_hint(&hint) {
+            Some(model) => {
+                assert!(satisfies(&clauses, assignment), "{}", context);
+                assert!(model.iter().all(|lit| hint.contains(lit)), "{}", context);
+                assert!(entails(&clauses, vars, &model), "{}", context);
+            }
+            None => assert!(!satisfies(&clauses, assignment), "{}", context),
+        }
+        assert_eq!(finder.stats().solves, 1, "{}", context);
+
+        // A hint leaving out a variable of the formula is never used as full model
+        if let Some(lit) = clauses.first().and_then(|clause| clause.first()) {
+            let partial: Vec<isize> = hint
+                .iter()
+                .copied()
+                .filter(|hinted| hinted.abs() != lit.abs())
+                .collect();
+            assert_eq!(finder.use_hint(&partial), None, "{}", context);
+            assert_eq!(finder.stats().solves, 1, "{}", context);
+        }
+    }
+}
+
+#[test]
+fn maximal_autarky() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(5);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let found = autarky::maximal_autarky(&clauses, &SolverOptions::default())
+            .unwrap()
+            .unwrap();
+
+        // Whether each clause touched by the partial assignment is also satisfied by it
+        let is_autarky = |clauses: &[Vec<isize>], partial: &[isize]| {
+            clauses.iter().all(|clause| {
+                !clause.iter().any(|lit| partial.contains(&-lit))
+                    || clause.iter().any(|lit| partial.contains(lit))
+            })
+        };
+        assert!(is_autarky(&clauses, &found.assignment), "{}", context);
+        let kernel: Vec<Vec<isize>> = (0..clauses.len())
+            .filter(|index| {
+                !clauses[*index].iter().any(|lit| {
+                    found
+                        .assignment
+                        .iter()
+                        .any(|other| other.abs() == lit.abs())
+                })
+            })
+            .map(|index| clauses[index].clone())
+            .collect();
+        let expected: Vec<Vec<isize>> = found
+            .kernel
+            .iter()
+            .map(|&index| clauses[index].clone())
+            .collect();
+        assert_eq!(kernel, expected, "{}", context);
+
+        // The kernel has no nonempty autarky touching it, checked over all partial assignments
+        for partial in 1..3u32.pow(vars as u32) {
+            let partial: Vec<isize> = (1..=vars as isize)
+                .filter_map(|var| match partial / 3u32.pow(var as u32 - 1) % 3 {
+                    0 => None,
+                    1 => Some(var),
+                    _ => Some(-var),
+                })
+                .collect();
+            let touches = kernel
+                .iter()
+                .flatten()
+                .any(|lit| partial.contains(lit) || partial.contains(&-lit));
+            assert!(
+                !touches || !is_autarky(&kernel, &partial),
+                "{}: {:?}",
+                context,
+                partial
+            );
+        }
+    }
+}
+
+#[test]
+fn definability() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(5);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let used: BTreeSet<isize> = clauses.iter().flatten().map(|lit| lit.abs()).collect();
+        let models: Vec<u32> = (0..1u32 << vars)
+            .filter(|&assignment| satisfies(&clauses, assignment))
+            .collect();
+        let bit = |assignment: u32, var: isize| assignment >> (var - 1) & 1;
+        let is_defined = |var: isize, basis: &[isize]| {
+            models.iter().all(|&a| {
+                models.iter().all(|&b| {
+                    basis.iter().any(|&other| bit(a, other) != bit(b, other))
+                        || bit(a, var) == bit(b, var)
+                })
+            })
+        };
+
+        let basis: Vec<isize> = used.iter().copied().filter(|_| rng.below(2) == 0).collect();
+        let expected: Vec<isize> = used
+            .iter()
+            .copied()
+            .filter(|var| !basis.contains(var) && is_defined(*var, &basis))
+            .collect();
+        let defined = definability::defined_vars(&clauses, &basis, &SolverOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(defined, expected, "{}: basis {:?}", context, basis);
+
+        let support = definability::independent_support(&clauses, &SolverOptions::default())
+            .unwrap()
+            .unwrap();
+        for &var in &used {
+            assert!(is_defined(var, &support), "{}: {:?}", context, support);
+        }
+        for &var in &support {
+            let rest: Vec<isize> = support
+                .iter()
+                .copied()
+                .filter(|&other| other != var)
+                .collect();
+            assert!(!is_defined(var, &rest), "{}: {:?}", context, support);
+        }
+    }
+}
+
+#[test]
+fn backbone() {
+    let mut rng = Rng(0);
+    for formula_index in 0..300u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let lits: Vec<isize> = (1..=vars as isize).flat_map(|var| [var, -var]).collect();
+
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let expected: Vec<isize> = lits
+            .iter()
+            .copied()
+            .filter(|&lit| {
+                (0..1u32 << vars).all(|assignment| {
+                    !satisfies(&clauses, assignment)
+                        || (assignment >> (lit.abs() - 1) & 1 == 1) == (lit > 0)
+                })
+            })
+            .collect();
+        assert_eq!(finder.backbone(&lits), Some(expected), "{}", context);
+    }
+}
+
+#[test]
+fn consequences() {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(5);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let minimal = minimal_models(&clauses, vars);
+        let satisfiable = (0..1u32 << vars).any(|assignment| satisfies(&clauses, assignment));
+
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let witness = |reduced: Option<Reduced>| {
+            let mut model = reduced.expect("witness").model;
+            model.sort_unstable();
+            assert!(minimal.contains(&model), "{}: {:?}", context, model);
+            model
+        };
+        for var in 1..=vars as isize {
+            for lit in [var, -var] {
+                let context = format!("{} lit {}", context, lit);
+                match finder.brave(lit) {
+                    Consequence::Holds { witness: model } => {
+                        assert!(witness(model).contains(&lit), "{}", context);
+                    }
+                    Consequence::Fails { witness: None } => assert!(
+                        satisfiable && minimal.iter().all(|model| !model.contains(&lit)),
+                        "{}: brave",
+                        context
+                    ),
+                    Consequence::Unsat => assert!(!satisfiable, "{}", context),
+                    other => panic!("{}: brave {:?}", context, other),
+                }
+                match finder.cautious(lit) {
+                    Consequence::Holds { witness: None } => assert!(
+                        satisfiable && minimal.iter().all(|model| model.contains(&lit)),
+                        "{}: cautious",
+                        context
+                    ),
+                    Consequence::Fails { witness: model } => {
+                        assert!(!witness(model).contains(&lit), "{}", context);
+                    }
+                    Consequence::Unsat => assert!(!satisfiable, "{}", context),
+                    other => panic!("{}: cautious {:?}", context, other),
+                }
+            }
+        }
+        // The queries leave the formula unchanged
+        enumerate(&mut finder, clauses, vars, &context);
+    }
+}
+
+#[test]
+fn retractable_clauses() {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        let mut finder = Finder::builder().build().unwrap();
+        let ids: Vec<_> = clauses
+            .iter()
+            .map(|clause| finder.add_retractable_clause(clause))
+            .collect();
+        for round in 0..4 {
+            for &id in &ids {
+                if rng.below(3) == 0 {
+                    if finder.is_active(id) {
+                        finder.deactivate(id);
+                    } else {
+                        finder.activate(id);
+                    }
+                }
+            }
+            let active: Vec<Vec<isize>> = clauses
+                .iter()
+                .zip(&ids)
+                .filter(|&(_, &id)| finder.is_active(id))
+                .map(|(clause, _)| clause.clone())
+                .collect();
+            let context = format!("{} round {}: {:?}", context, round, active);
+            if round == 3 {
+                enumerate(&mut finder, active, vars, &context);
+                break;
+            }
+
+            match finder.solve() {
+                Solved::Model(model) => {
+                    let mut reduced = finder.reduce(&model).model;
+                    reduced.sort_unstable();
+                    assert!(
+                        minimal_models(&active, vars).contains(&reduced),
+                        "{}: reduced model {:?} isn't minimal",
+                        context,
+                        reduced
+                    );
+                    let fewest = minimal_models(&active, vars)
+                        .iter()
+                        .map(Vec::len)
+                        .min()
+                        .unwrap();
+                    assert!(
+                        matches!(finder.minimum_model(), Optimized::Model { weight, .. } if weight == fewest as u64),
+                        "{}",
+                        context
+                    );
+                }
+                Solved::Unsat => assert!(
+                    (0..1u32 << vars).all(|assignment| !satisfies(&active, assignment)),
+                    "{}: wrongly unsat",
+                    context
+                ),
+                Solved::Unknown => panic!("{}: unknown", context),
+            }
+        }
+    }
+}
+
+#[test]
+fn scopes() {
+    for (name, builder) in [
+        ("default", Finder::builder()),
+        (
+            "preprocess",
+            Finder::builder().preprocess(true).equivalences(true),
+        ),
+    ] {
+        let mut rng = Rng(0);
+        for formula_index in 0..200u64 {
+            let vars = 1 + rng.below(6);
+            let layers: Vec<Vec<Vec<isize>>> =
+                (0..3).map(|_| random_formula(&mut rng, vars)).collect();
+            let context = format!("{} formula {}: {:?}", name, formula_index, layers);
+
+            let mut finder = builder.clone().build().unwrap();
+            let mut clauses = vec![];
+            let mut depths = vec![];
+            let mut counts = vec![];
+            for (depth, layer) in layers.iter().enumerate() {
+                if depth > 0 {
+                    finder.push();
+                    counts.push(finder.clause_count());
+                }
+                for clause in layer {
+                    finder.add_clause(clause);
+                }
+                depths.push(clauses.len());
+                clauses.extend(layer.iter().cloned());
+            }
+            // Blocking clauses only last until the innermost scope is closed
+            enumerate(&mut finder, clauses.clone(), vars, &context);
+
+            for depth in (1..layers.len()).rev() {
+                finder.pop();
+                clauses.truncate(depths[depth]);
+                let context = format!("{} depth {}", context, depth - 1);
+                assert_eq!(finder.clause_count(), counts[depth - 1], "{}", context);
+                let seen: BTreeSet<isize> = clauses.iter().flatten().map(|lit| lit.abs()).collect();
+                match finder.solve() {
+                    Solved::Model(model) => {
+                        assert!(
+                            model.iter().all(|lit| seen.contains(&lit.abs())),
+                            "{}: full model {:?} contains forgotten variables",
+                            context,
+                            model
+                        );
+                        let mut reduced = finder.reduce(&model).model;
+                        reduced.sort_unstable();
+                        assert!(
+                            minimal_models(&clauses, vars).contains(&reduced),
+                            "{}: reduced model {:?} isn't minimal",
+                            context,
+                            reduced
+                        );
+                    }
+                    Solved::Unsat => assert!(
+                        (0..1u32 << vars).all(|assignment| !satisfies(&clauses, assignment)),
+                        "{}: wrongly unsat",
+                        context
+                    ),
+                    Solved::Unknown => panic!("{}: unknown", context),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn preferences() {
+    check_with("preferences", Finder::builder, |finder, formula_index| {
+        let mut rng = Rng(formula_index);
+        for var in 1..=6 {
+            let lit = if rng.below(2) == 0 { var } else { -var };
+            let preference = [None, Some(Preference::Drop), Some(Preference::Keep)][rng.below(3)];
+            finder.set_preference(lit, preference);
+        }
+    });
+}
+
+#[test]
+fn prefer() {
+    check_with("prefer", Finder::builder, |finder, formula_index| {
+        let mut rng = Rng(formula_index);
+        for var in 1..=6 {
+            match rng.below(3) {
+                0 => finder.prefer(&[var]),
+                1 => finder.prefer(&[-var]),
+                _ => (),
+            }
+        }
+    });
+}
+
+#[test]
+fn reset() {
+    // A single finder is reused for all formulas
+    let mut finder = Finder::builder()
+        .order(CandidateOrder::Occurrence)
+        .build()
+        .unwrap();
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("reset formula {}: {:?}", formula_index, clauses);
+
+        finder.reset(formula_index % 2 == 0);
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        enumerate(&mut finder, clauses, vars, &context);
+    }
+}
+
+#[test]
+fn on_progress() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    check("on progress", || {
+        Finder::builder().on_progress(|progress| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            assert!(!progress.candidates.is_empty());
+            for lit in progress.essential.iter().chain(&progress.candidates) {
+                assert!(lit.unsigned_abs() <= 6, "{:?}", progress);
+                assert!(!progress.essential.contains(&-lit), "{:?}", progress);
+            }
+        })
+    });
+    assert!(CALLS.load(Ordering::SeqCst) > 0);
+}
+
+#[test]
+fn on_check() {
+    let mut rng = Rng(0);
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        let traces = Arc::new(Mutex::new(vec![]));
+        let recorded = traces.clone();
+        let mut finder = Finder::builder()
+            .speculate(formula_index as usize % 3)
+            .on_check(move |trace| recorded.lock().unwrap().push(trace.clone()))
+            .build()
+            .unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let model = match finder.solve() {
+            Solved::Model(model) => model,
+            _ => continue,
+        };
+        let reduced = finder.reduce(&model);
+        let traces = traces.lock().unwrap();
+        assert_eq!(traces.len(), reduced.checks.total(), "{}", context);
+        for trace in traces.iter() {
+            let context = format!("{}: {:?}", context, trace);
+            assert!(model.contains(&trace.candidate), "{}", context);
+            assert!(!trace.assumptions.contains(&trace.candidate), "{}", context);
+            assert!(
+                trace.assumptions.iter().all(|lit| model.contains(lit)),
+                "{}",
+                context
+            );
+            let entailed = entails(&clauses, vars, &trace.assumptions);
+            match trace.result {
+                Check::Sat => assert!(!entailed, "{}", context),
+                Check::Unsat => {
+                    assert!(entailed, "{}", context);
+                    assert!(
+                        trace.core.iter().all(|lit| trace.assumptions.contains(lit)),
+                        "{}",
+                        context
+                    );
+                    assert!(entails(&clauses, vars, &trace.core), "{}", context);
+                }
+                Check::Unknown => panic!("{}", context),
+            }
+        }
+    }
+}
+
+#[test]
+fn check_times() {
+    let mut rng = Rng(0);
+    for formula_index in 0..100u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        let mut finder = Finder::builder()
+            .portfolio(1 + formula_index as usize % 2)
+            .build()
+            .unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        while let Solved::Model(model) = finder.solve() {
+            let reduced = finder.reduce(&model);
+            finder.block(&reduced.model);
+        }
+        let stats = finder.stats();
+        assert!(
+            stats.check_times.total() <= stats.reduce_time,
+            "{}",
+            context
+        );
+        // Without speculation every check is timed, but a portfolio may report the checks of
+        // another attempt
+        if formula_index % 2 == 0 {
+            if stats.checks.sat == 0 {
+                assert_eq!(stats.check_times.sat, Duration::ZERO, "{}", context);
+            }
+            if stats.checks.unsat == 0 {
+                assert_eq!(stats.check_times.unsat, Duration::ZERO, "{}", context);
+            }
+        }
+    }
+}
+
+#[test]
+fn check_histograms() {
+    let options = SolverOptions {
+        backend: Backend::Builtin,
+        ..SolverOptions::default()
+    };
+    let mut rng = Rng(0);
+    for formula_index in 0..100u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        let speculate = formula_index as usize % 2;
+        let mut finder = Finder::builder()
+            .speculate(speculate)
+            .neg_solver_options(options.clone())
+            .build()
+            .unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        while let Solved::Model(model) = finder.solve() {
+            let reduced = finder.reduce(&model);
+            finder.block(&reduced.model);
+        }
+        let stats = finder.stats();
+        let total = |histogram: &Histogram| -> usize {
+            histogram.buckets().map(|(_, _, count)| count).sum()
+        };
+        let histograms = &stats.histograms;
+        assert_eq!(
+            total(&histograms.core_sizes),
+            stats.checks.unsat,
+            "{}",
+            context
+        );
+        assert!(
+            histograms
+                .core_sizes
+                .buckets()
+                .all(|(low, _, _)| low <= vars as u64),
+            "{}",
+            context
+        );
+        // Speculative checks have no conflict counts
+        let checks = total(&histograms.sat_conflicts) + total(&histograms.unsat_conflicts);
+        if speculate == 0 {
+            assert_eq!(checks, stats.checks.total(), "{}", context);
+        } else {
+            assert!(checks <= stats.checks.total(), "{}", context);
+        }
+    }
+}
+
+#[test]
+fn learned_clauses() {
+    let dir = std::env::temp_dir().join(format!("minimal_models_learned_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("learned");
+    let builder = || {
+        Finder::builder().solver_options(SolverOptions {
+            backend: Backend::Builtin,
+            ..SolverOptions::default()
+        })
+    };
+    let mut rng = Rng(0);
+    let mut previous = Learned::default();
+    let mut exported = 0;
+    for formula_index in 0..200u64 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+
+        // Learn from finding and reducing a single model
+        let mut first = builder().build().unwrap();
+        for clause in &clauses {
+            first.add_clause(clause);
+        }
+        if let Solved::Model(model) = first.solve() {
+            first.reduce(&model);
+        }
+        first.learned().save(&path).unwrap();
+        let learned = Learned::load(&path).unwrap();
+        assert_eq!(learned, first.learned(), "{}", context);
+        exported += learned.len();
+
+        // The clauses are added for the same formula, which is exported again, while those of
+        // the previous formula are ignored
+        let mut second = builder().build().unwrap();
+        for clause in &clauses {
+            second.add_clause(clause);
+        }
+        second.import_learned(&previous);
+        second.import_learned(&learned);
+        enumerate(&mut second, clauses, vars, &context);
+        let reexported = second.learned();
+        for (imported, reexported) in [
+            (&learned.pos, &reexported.pos),
+            (&learned.neg, &reexported.neg),
+        ] {
+            let (imported, reexported) = (imported.as_ref().unwrap(), reexported.as_ref().unwrap());
+            for clause in &imported.clauses {
+                assert!(
+                    reexported.clauses.contains(clause),
+                    "{}: {:?}",
+                    context,
+                    clause
+                );
+            }
+        }
+        previous = learned;
+    }
+    assert!(exported > 0);
+    assert!(Finder::builder().build().unwrap().learned().is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
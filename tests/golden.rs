@@ -0,0 +1,98 @@
+//! Runs the binary on each `.cnf` input in `tests/golden`, comparing its output against the
+//! corresponding `.out` file.
+//!
+//! Command line arguments are read from a `.args` file next to the input, if present. Progress
+//! lines are ignored and a non-zero exit code is recorded together with the error output. Set
+//! `UPDATE_GOLDEN=1` to write the current output to the `.out` files instead.
+//!
+//! Without the `cryptominisat` feature the builtin solver finds other models, so its output is
+//! compared against a `.builtin.out` file instead, where it differs from the `.out` file.
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Runs the binary on `input`, returning the relevant parts of its output
+fn run(input: &Path) -> String {
+    let args = fs::read_to_string(input.with_extension("args")).unwrap_or_default();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_minimal_models"));
+    command
+        .args(args.split_ascii_whitespace())
+        // Keep a configuration of the user from affecting the output
+        .env("XDG_CONFIG_HOME", input.parent().unwrap())
+        .stdin(Stdio::from(fs::File::open(input).unwrap()));
+    for (name, _) in std::env::vars_os() {
+        if name.to_string_lossy().starts_with("MINIMAL_MODELS_") {
+            command.env_remove(name);
+        }
+    }
+    let output = command.output().unwrap();
+
+    let mut result = String::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if !line.starts_with("solving...") {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+    if !output.status.success() {
+        let code = output
+            .status
+            .code()
+            .map_or("none".to_owned(), |code| code.to_string());
+        result.push_str(&format!("exit code {}:\n", code));
+        result.push_str(&String::from_utf8_lossy(&output.stderr));
+    }
+    result
+}
+
+#[test]
+fn golden() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "cnf"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty());
+
+    let mut failed = vec![];
+    for input in &inputs {
+        let output = run(input);
+        let mut expected_path = input.with_extension("out");
+        if cfg!(not(feature = "cryptominisat")) {
+            let builtin_path = input.with_extension("builtin.out");
+            if update {
+                // Only keep the outputs that differ from those of cryptominisat
+                if fs::read_to_string(&expected_path).ok().as_ref() == Some(&output) {
+                    fs::remove_file(&builtin_path).ok();
+                } else {
+                    fs::write(&builtin_path, &output).unwrap();
+                }
+                continue;
+            }
+            if builtin_path.exists() {
+                expected_path = builtin_path;
+            }
+        }
+        if update {
+            fs::write(&expected_path, &output).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+        if output != expected {
+            eprintln!(
+                "{}: expected\n{}\ngot\n{}",
+                input.display(),
+                expected,
+                output
+            );
+            failed.push(input.file_name().unwrap().to_string_lossy().into_owned());
+        }
+    }
+    assert!(failed.is_empty(), "output differs for {:?}", failed);
+}
@@ -0,0 +1,45 @@
+//! Checks that finders stop forwarding their interrupt flag when dropped, counting the threads
+//! of the process in `/proc`.
+#![cfg(target_os = "linux")]
+use std::{
+    fs,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use minimal_models::{Backend, Finder, Solved, SolverOptions};
+
+/// Number of threads of this process
+fn threads() -> usize {
+    let status = fs::read_to_string("/proc/self/status").unwrap();
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn forwarder_threads_exit() {
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let options = SolverOptions {
+        backend: Backend::Builtin,
+        ..SolverOptions::default()
+    };
+    let before = threads();
+    let mut finder = Finder::builder()
+        .pos_solver_options(options.clone())
+        .neg_solver_options(options)
+        .interrupt(interrupt)
+        .build()
+        .unwrap();
+    for _ in 0..100 {
+        finder.reset(false);
+        finder.add_clause(&[1, 2]);
+        assert!(matches!(finder.solve(), Solved::Model(_)));
+    }
+    assert_eq!(threads(), before + 1);
+    drop(finder);
+    assert_eq!(threads(), before);
+}
@@ -0,0 +1,50 @@
+//! Checks the Prometheus metrics rendered for enumerations of random formulas
+pub mod common;
+
+use common::random_formula;
+use minimal_models::{metrics::Metrics, Finder, Rng, Solved};
+
+#[test]
+fn metrics() {
+    let metrics = Metrics::default();
+    let mut rng = Rng(0);
+    let mut models = 0;
+    for _ in 0..20 {
+        let vars = 1 + rng.below(6);
+        let clauses = random_formula(&mut rng, vars);
+        metrics.next_problem();
+        let mut finder = Finder::builder().build().unwrap();
+        for clause in &clauses {
+            finder.add_clause(clause);
+        }
+        let mut problem_models = 0;
+        while let Solved::Model(model) = finder.solve() {
+            let reduced = finder.reduce(&model);
+            finder.block(&reduced.model);
+            problem_models += 1;
+            metrics.update(problem_models, problem_models, finder.stats());
+        }
+        models += problem_models;
+    }
+    let mut text = vec![];
+    metrics.render(&mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(
+        text.contains("minimal_models_problems_total 20\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(&format!("minimal_models_models_total {}\n", models)),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(&format!(
+            "minimal_models_blocking_clauses_total {}\n",
+            models
+        )),
+        "{}",
+        text
+    );
+}
@@ -0,0 +1,59 @@
+//! Compares the one-shot functions against brute force on small random formulas
+pub mod common;
+
+use common::{minimal_models, random_formula};
+use minimal_models::Rng;
+
+#[test]
+fn one_shot() {
+    let mut rng = Rng(0);
+    for formula_index in 0..100u64 {
+        let vars = 1 + rng.below(5);
+        let mut clauses = random_formula(&mut rng, vars);
+        let context = format!("formula {}: {:?}", formula_index, clauses);
+        let narrow: Vec<Vec<i32>> = clauses
+            .iter()
+            .map(|clause| clause.iter().map(|&lit| lit as i32).collect())
+            .collect();
+        let widen = |model: &[i32]| -> Vec<isize> {
+            let mut model: Vec<isize> = model.iter().map(|&lit| lit as isize).collect();
+            model.sort_unstable();
+            model
+        };
+
+        match minimal_models::minimal_model(&narrow).unwrap() {
+            Some(model) => assert!(
+                minimal_models(&clauses, vars).contains(&widen(&model)),
+                "{}: {:?}",
+                context,
+                model
+            ),
+            None => assert!(minimal_models(&clauses, vars).is_empty(), "{}", context),
+        }
+
+        // Each model is minimal for the formula with the blocking clauses of the earlier ones
+        for model in minimal_models::all_minimal_models(&narrow).unwrap() {
+            let model = widen(&model);
+            assert!(
+                minimal_models(&clauses, vars).contains(&model),
+                "{}: {:?}",
+                context,
+                model
+            );
+            clauses.push(model.iter().map(|&lit| -lit).collect());
+        }
+        assert!(minimal_models(&clauses, vars).is_empty(), "{}", context);
+    }
+    assert!(minimal_models::minimal_model(&[vec![1, 0]]).is_err());
+    assert!(minimal_models::minimal_model(&[vec![i32::MAX]]).is_err());
+    assert!(minimal_models::all_minimal_models(&[vec![1, i32::MIN]]).is_err());
+    let max = minimal_models::ONE_SHOT_MAX_VAR as i32;
+    assert_eq!(
+        minimal_models::minimal_model(&[vec![-max]]).unwrap(),
+        Some(vec![-max])
+    );
+    assert_eq!(
+        minimal_models::all_minimal_models(&[]).unwrap(),
+        vec![Vec::<i32>::new()]
+    );
+}
@@ -0,0 +1,85 @@
+//! Checks that the options listed by `--help`, which are also those completed by the shell
+//! completions and accepted in configurations, are exactly those the binary parses.
+use std::{collections::BTreeSet, fs, path::Path, process::Command};
+
+/// Standard output of the binary run with `args`
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_minimal_models"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Options listed by `--help`
+fn listed() -> BTreeSet<String> {
+    run(&["--help"])
+        .lines()
+        .filter_map(|line| line.strip_prefix("  --"))
+        .map(|line| format!("--{}", line.split(' ').next().unwrap()))
+        .collect()
+}
+
+/// Options matched by the argument parsers, as `"--option" =>` or `arg == "--option"`
+fn parsed() -> BTreeSet<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/bin/minimal_models");
+    let mut options = BTreeSet::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let source = fs::read_to_string(entry.unwrap().path()).unwrap();
+        for line in source.lines() {
+            let line = line.trim();
+            let patterns = if line.contains("=>") && line.starts_with("\"--") {
+                line.split("=>").next().unwrap()
+            } else if let Some((_, rest)) = line.split_once("arg == ") {
+                rest
+            } else {
+                continue;
+            };
+            for pattern in patterns.split('|') {
+                let pattern = pattern.trim();
+                if let Some(option) = pattern.strip_prefix('"').and_then(|rest| {
+                    rest.split('"')
+                        .next()
+                        .filter(|option| option.starts_with("--"))
+                }) {
+                    options.insert(option.to_owned());
+                }
+            }
+        }
+    }
+    options
+}
+
+#[test]
+fn options_are_listed() {
+    let parsed = parsed();
+    assert!(parsed.contains("--order") && parsed.contains("--basis"));
+    assert_eq!(listed(), parsed);
+}
+
+#[test]
+fn options_are_completed() {
+    let listed = listed();
+    for shell in ["bash", "zsh", "fish"].iter() {
+        let script = run(&["completions", shell]);
+        let words: Vec<&str> = script
+            .split(|c: char| c.is_whitespace() || "'\"():*".contains(c))
+            .collect();
+        // fish names long options by `-l` without their dashes
+        let completed: BTreeSet<String> = if *shell == "fish" {
+            words
+                .windows(2)
+                .filter(|pair| pair[0] == "-l")
+                .map(|pair| format!("--{}", pair[1]))
+                .collect()
+        } else {
+            words
+                .iter()
+                .filter(|word| word.starts_with("--") && word.len() > 2)
+                .map(|word| word.to_string())
+                .collect()
+        };
+        assert_eq!(completed, listed, "{} completions", shell);
+    }
+}
@@ -0,0 +1,239 @@
+//! Checks the proofs of the builtin solver, with a separate checker and the embedded one
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+pub mod common;
+
+use common::{enumerate, random_formula};
+use minimal_models::{proof_check, Backend, Finder, ProofFormat, Rng, SolverOptions};
+
+/// A line of an LRAT or FRAT proof, the hints of a FRAT proof are required
+enum ProofLine {
+    Original(u64, Vec<i64>),
+    Add(u64, Vec<i64>, Vec<u64>),
+    Delete(u64),
+}
+
+fn parse_numbers(tokens: &[&str]) -> Vec<i64> {
+    tokens
+        .iter()
+        .map(|token| token.parse().unwrap())
+        .take_while(|&number| number != 0)
+        .collect()
+}
+
+fn parse_lrat(formula: &str, proof: &str) -> Vec<ProofLine> {
+    let mut lines: Vec<ProofLine> = formula
+        .lines()
+        .skip(1)
+        .enumerate()
+        .map(|(index, line)| {
+            let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+            ProofLine::Original(index as u64 + 1, parse_numbers(&tokens))
+        })
+        .collect();
+    for line in proof.lines() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        if tokens[1] == "d" {
+            lines.extend(
+                parse_numbers(&tokens[2..])
+                    .into_iter()
+                    .map(|id| ProofLine::Delete(id as u64)),
+            );
+            continue;
+        }
+        let lits = parse_numbers(&tokens[1..]);
+        let hints = parse_numbers(&tokens[lits.len() + 2..]);
+        lines.push(ProofLine::Add(
+            tokens[0].parse().unwrap(),
+            lits,
+            hints.into_iter().map(|id| id as u64).collect(),
+        ));
+    }
+    lines
+}
+
+fn parse_frat(proof: &str) -> Vec<ProofLine> {
+    let mut lines = vec![];
+    for line in proof.lines() {
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        let id = tokens[1].parse().unwrap();
+        let lits = parse_numbers(&tokens[2..]);
+        match tokens[0] {
+            "o" => lines.push(ProofLine::Original(id, lits)),
+            "a" => {
+                assert_eq!(tokens[lits.len() + 3], "l", "{}", line);
+                let hints = parse_numbers(&tokens[lits.len() + 4..]);
+                lines.push(ProofLine::Add(
+                    id,
+                    lits,
+                    hints.into_iter().map(|id| id as u64).collect(),
+                ));
+            }
+            "d" => lines.push(ProofLine::Delete(id)),
+            "f" => (),
+            kind => panic!("unknown proof line {:?}", kind),
+        }
+    }
+    lines
+}
+
+/// Checks that each derived clause follows by unit propagation over its hints, in order, returning
+/// whether the proof contains the empty clause
+fn check_proof(lines: Vec<ProofLine>) -> bool {
+    let mut clauses: HashMap<u64, Vec<i64>> = HashMap::new();
+    let mut empty = false;
+    for line in lines {
+        match line {
+            ProofLine::Original(id, lits) => {
+                empty |= lits.is_empty();
+                clauses.insert(id, lits);
+            }
+            ProofLine::Add(id, lits, hints) => {
+                let mut assigned: HashSet<i64> = lits.iter().map(|&lit| -lit).collect();
+                let mut falsified = false;
+                for hint in &hints {
+                    let clause = &clauses[hint];
+                    assert!(
+                        !clause.iter().any(|lit| assigned.contains(lit)),
+                        "hint {} of {} is satisfied",
+                        hint,
+                        id
+                    );
+                    let open: Vec<i64> = clause
+                        .iter()
+                        .cloned()
+                        .filter(|lit| !assigned.contains(&-lit))
+                        .collect();
+                    match open[..] {
+                        [] => {
+                            falsified = true;
+                            break;
+                        }
+                        [lit] => {
+                            assigned.insert(lit);
+                        }
+                        _ => panic!("hint {} of {} isn't unit", hint, id),
+                    }
+                }
+                assert!(falsified, "hints of {} don't lead to a conflict", id);
+                empty |= lits.is_empty();
+                clauses.insert(id, lits);
+            }
+            ProofLine::Delete(id) => {
+                clauses.remove(&id).unwrap();
+            }
+        }
+    }
+    empty
+}
+
+#[test]
+fn proofs() {
+    let dir = std::env::temp_dir().join(format!("minimal_models_proofs_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let options = SolverOptions {
+        backend: Backend::Builtin,
+        ..SolverOptions::default()
+    };
+    for format in [ProofFormat::Lrat, ProofFormat::Frat, ProofFormat::Drat] {
+        let mut rng = Rng(0);
+        for formula_index in 0..100u64 {
+            let vars = 1 + rng.below(6);
+            let clauses = random_formula(&mut rng, vars);
+            let context = format!("{:?} formula {}: {:?}", format, formula_index, clauses);
+
+            let (pos, neg) = (dir.join("pos.proof"), dir.join("neg.proof"));
+            let mut finder = Finder::builder()
+                .solver_options(options.clone())
+                .pos_proof(&pos, format)
+                .neg_proof(&neg, format)
+                .build()
+                .unwrap();
+            for clause in &clauses {
+                finder.add_clause(clause);
+            }
+            enumerate(&mut finder, clauses.clone(), vars, &context);
+            drop(finder);
+
+            let read = |path: &std::path::Path| fs::read_to_string(path).unwrap();
+            let parse = |path: &std::path::Path| match format {
+                ProofFormat::Lrat => {
+                    let mut formula = path.as_os_str().to_owned();
+                    formula.push(".cnf");
+                    Some(parse_lrat(&read(formula.as_ref()), &read(path)))
+                }
+                ProofFormat::Frat => Some(parse_frat(&read(path))),
+                // Without hints only the embedded checker is used
+                ProofFormat::Drat => None,
+            };
+            if let (Some(pos), Some(neg)) = (parse(&pos), parse(&neg)) {
+                // Enumeration ends once the blocking clauses make the formula unsatisfiable
+                assert!(check_proof(pos), "{}: no empty clause", context);
+                check_proof(neg);
+            }
+            // The embedded checker agrees
+            assert!(
+                proof_check::check_file(&pos, format).unwrap().refutation,
+                "{}",
+                context
+            );
+            proof_check::check_file(&neg, format).unwrap();
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_proofs() {
+    let formula = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+    let checked = proof_check::check_lrat(formula, "5 1 0 1 3 0\n6 0 5 2 4 0\n").unwrap();
+    assert_eq!(
+        checked,
+        proof_check::CheckedProof {
+            derived: 2,
+            refutation: true
+        }
+    );
+    for proof in [
+        // The second hint is satisfied
+        "5 1 0 1 2 3 0\n",
+        // No conflict
+        "5 1 0 1 0\n",
+        // Unknown hint
+        "5 1 0 1 9 0\n",
+        // Hint deleted before
+        "4 d 3 0\n5 1 0 1 3 0\n",
+    ] {
+        assert!(
+            proof_check::check_lrat(formula, proof).is_err(),
+            "{}",
+            proof
+        );
+    }
+    assert!(
+        proof_check::check_frat("o 1 1 0\no 2 -1 0\na 3 0 l 1 2 0\n")
+            .unwrap()
+            .refutation
+    );
+    assert!(proof_check::check_frat("o 1 1 0\no 2 -1 0\na 3 0 0\n").is_err());
+    assert!(
+        proof_check::check_drat(formula, "1 0\nd 1 2 0\n0\n")
+            .unwrap()
+            .refutation
+    );
+    for proof in [
+        // No conflict
+        "1 0\nd -1 2 0\n0\n",
+        // Unknown clause
+        "d 1 0\n",
+    ] {
+        assert!(
+            proof_check::check_drat(formula, proof).is_err(),
+            "{}",
+            proof
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Reads inputs from a local HTTP server
+use minimal_models::remote;
+
+#[test]
+fn remote_input() {
+    use std::{
+        io::{BufRead, Read, Write},
+        net::TcpListener,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Responses are written in chunks, so that magic numbers can be split between reads
+    let responses: [&[&[u8]]; 9] = [
+        &[b"HTTP/1.0 302 Found\r\nLocation: /formula.cnf\r\n\r\n"],
+        &[
+            b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\np c",
+            b"nf 2 1\n1 2 0\n",
+        ],
+        &[b"HTTP/1.0 404 Not Found\r\n\r\n"],
+        &[b"HTTP/1.0 200 OK\r\n\r\n\x1f\x8b\x08\x00"],
+        &[b"HTTP/1.0 200 OK\r\n\r\n\xfd7z", b"XZ\x00\x00"],
+        &[b"HTTP/1.0 200 OK\r\n\r\n\n"],
+        &[b"HTTP/1.0 301 Moved Permanently\r\nLocation: ../formula.cnf?v=2\r\n\r\n"],
+        &[b"HTTP/1.0 200 OK\r\n\r\n1 0\n"],
+        &[b"HTTP/1.0 302 Found\r\nLocation: https://example.com/input.cnf\r\n\r\n"],
+    ];
+    let server = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for (response, stream) in responses.iter().zip(listener.incoming()) {
+            let mut stream = stream.unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 4096];
+            while !request.ends_with(b"\r\n\r\n") {
+                let len = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..len]);
+            }
+            requests.push(String::from_utf8(request).unwrap());
+            for chunk in response.iter() {
+                stream.write_all(chunk).unwrap();
+                stream.flush().unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+        }
+        requests
+    });
+
+    let lines: Vec<String> = remote::open_url(&format!("http://{}/input", addr))
+        .unwrap()
+        .lines()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines, ["p cnf 2 1", "1 2 0"]);
+    assert!(remote::open_url(&format!("http://{}/missing", addr)).is_err());
+    assert!(remote::open_url(&format!("http://{}/compressed", addr)).is_err());
+    assert!(remote::open_url(&format!("http://{}/split", addr)).is_err());
+    let lines: Vec<String> = remote::open_url(&format!("http://{}/short", addr))
+        .unwrap()
+        .lines()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines, [""]);
+    // A relative redirect is resolved against the directory of the request
+    let lines: Vec<String> = remote::open_url(&format!("http://{}/dir/sub/input?v=1", addr))
+        .unwrap()
+        .lines()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(lines, ["1 0"]);
+    let error = remote::open_url(&format!("http://{}/secure", addr))
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(
+        error.contains("redirects to https://example.com/input.cnf"),
+        "{}",
+        error
+    );
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /input HTTP/1.0\r\n"));
+    assert!(requests[1].starts_with("GET /formula.cnf HTTP/1.0\r\n"));
+    assert!(requests[6].starts_with("GET /dir/sub/input?v=1 HTTP/1.0\r\n"));
+    assert!(requests[7].starts_with("GET /dir/formula.cnf?v=2 HTTP/1.0\r\n"));
+    assert!(remote::open_url("https://example.com/input.cnf").is_err());
+}